@@ -0,0 +1,123 @@
+use std::{collections::HashSet, str::FromStr, sync::Arc};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rudra::{
+    config::{OpenapiSource, PathMatching, Runtime},
+    evaluator::{evaluate, evaluate_details},
+    models::{EndpointConfiguration, MediaType, Method, Observation, Schema},
+};
+use url::Url;
+
+const METHODS: [Method; 4] = [Method::GET, Method::POST, Method::PUT, Method::DELETE];
+
+fn create_runtime() -> Arc<Runtime> {
+    Arc::new(Runtime {
+        openapi_source: OpenapiSource::Url(Url::from_str("https://example.com/openapi.yaml").unwrap()),
+        app_base_url: Url::from_str("http://localhost:8080").unwrap(),
+        port: 13750,
+    })
+}
+
+// an object with a required id, so every recorded response body is validated
+fn create_schema() -> Arc<Schema> {
+    Arc::new(Schema {
+        schema_types: vec!["object".to_string()],
+        properties: vec![
+            ("id".to_string(), Schema { schema_types: vec!["integer".to_string()], ..Schema::default() }),
+            ("name".to_string(), Schema { schema_types: vec!["string".to_string()], ..Schema::default() }),
+        ],
+        required: vec!["id".to_string()],
+        ..Schema::default()
+    })
+}
+
+// a mix of concrete and templated paths, each with a success and a not found response,
+// with media types every request and response declares several representations with schemas
+fn create_spec(operations: usize, with_media_types: bool, runtime: &Arc<Runtime>) -> Vec<EndpointConfiguration> {
+    let schema = create_schema();
+    let mut openapi_endpoints = vec![];
+    for index in 0..operations {
+        let method = METHODS[index % METHODS.len()].clone();
+        let path = match index % 3 {
+            0 => format!("/resources{}", index / METHODS.len()),
+            1 => format!("/resources{}/{{id}}", index / METHODS.len()),
+            _ => format!("/resources{}/{{id}}/items/{{item}}", index / METHODS.len()),
+        };
+        for status_code in [200, 404] {
+            let mut endpoint = EndpointConfiguration::new(method.clone(), &path, status_code, runtime.clone(), false).unwrap();
+            if with_media_types {
+                endpoint = endpoint
+                    .with_request_media_types(vec!["application/json".to_string(), "application/x-www-form-urlencoded".to_string()])
+                    .with_response_media_types(vec![
+                        MediaType::new("application/json", Some(schema.clone())),
+                        MediaType::new("application/problem+json", Some(schema.clone())),
+                        MediaType::new("application/xml", None),
+                    ]);
+            }
+            openapi_endpoints.push(endpoint);
+        }
+    }
+    openapi_endpoints
+}
+
+// requests spread over the spec, some of them aren't documented, some responses don't conform to the schema
+fn create_observations(requests: usize, operations: usize, runtime: &Arc<Runtime>) -> Vec<Observation> {
+    (0..requests)
+        .map(|index| {
+            let resource = index % (operations / METHODS.len());
+            let method = METHODS[index % METHODS.len()].clone();
+            let request_target = match index % 4 {
+                0 => format!("/resources{}", resource),
+                1 => format!("/resources{}/{}?page={}", resource, index, index % 10),
+                2 => format!("/resources{}/{}/items/{}", resource, index, index % 7),
+                _ => format!("/undocumented/{}", index),
+            };
+            let status_code = if index % 5 == 0 { 404 } else { 200 };
+            let (content_type, body) = match index % 3 {
+                0 => ("application/json", format!(r#"{{"id": {}, "name": "item"}}"#, index)),
+                1 => ("application/problem+json", format!(r#"{{"name": "{}"}}"#, index)),
+                _ => ("application/xml", String::from("<item/>")),
+            };
+            Observation::new(method, &request_target, status_code, runtime.clone())
+                .unwrap()
+                .with_request_headers(Some(content_type), None)
+                .with_response(Some(content_type), body.as_bytes())
+        })
+        .collect()
+}
+
+// with the route index the duration grows with the number of requests, not with the number of requests times the size of the spec
+fn bench_evaluation(c: &mut Criterion) {
+    let runtime = create_runtime();
+    let mut group = c.benchmark_group("evaluate");
+    group.sample_size(10);
+    for (operations, requests) in [(40, 100_000), (400, 100_000), (400, 1_000_000)] {
+        let openapi_endpoints = create_spec(operations, false, &runtime);
+        let openapi_endpoints_with_media_types = create_spec(operations, true, &runtime);
+        let observations = create_observations(requests, operations, &runtime);
+
+        group.throughput(Throughput::Elements(requests as u64));
+        group.bench_with_input(
+            BenchmarkId::new("coverage", format!("{} operations, {} requests", operations, requests)),
+            &observations,
+            |b, observations| b.iter(|| evaluate(&openapi_endpoints, &None, observations, &HashSet::new(), &PathMatching::MostSpecific)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("details", format!("{} operations, {} requests", operations, requests)),
+            &observations,
+            |b, observations| b.iter(|| evaluate_details(&openapi_endpoints, observations, &HashSet::new(), true, &PathMatching::MostSpecific)),
+        );
+        // response conformance and media type coverage only have work to do if the spec declares media types and schemas
+        group.bench_with_input(
+            BenchmarkId::new("details with media types", format!("{} operations, {} requests", operations, requests)),
+            &observations,
+            |b, observations| {
+                b.iter(|| evaluate_details(&openapi_endpoints_with_media_types, observations, &HashSet::new(), true, &PathMatching::MostSpecific))
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_evaluation);
+criterion_main!(benches);
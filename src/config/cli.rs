@@ -0,0 +1,234 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::utils::Error;
+
+use super::environment::{
+    ENV_VAR_ACCESS_LOG, ENV_VAR_ACCOUNT_FOR_FORBIDDEN, ENV_VAR_ACCOUNT_FOR_UNAUTORIZED, ENV_VAR_APP_BASE_URL, ENV_VAR_CONFIG_FILE, ENV_VAR_DEBUG,
+    ENV_VAR_DEFAULT_RESPONSE_POLICY, ENV_VAR_GROUPINGS, ENV_VAR_IS_MERGE, ENV_VAR_JSON_REPORT, ENV_VAR_JUNIT_REPORT, ENV_VAR_MAPPING,
+    ENV_VAR_MEDIA_TYPE_COVERAGE, ENV_VAR_ONLY_ACCOUNT_MERGE, ENV_VAR_OPENAPI_SOURCE, ENV_VAR_PATH_MATCHING, ENV_VAR_PORT, ENV_VAR_PROXY_BACKEND,
+    ENV_VAR_QUERY_PARAMETER_COVERAGE, ENV_VAR_REDACTED_HEADERS, ENV_VAR_REJECTED_LINES_LIMIT, ENV_VAR_REPOSITORY, ENV_VAR_TEST_COVERAGE, ENV_VAR_UNDOCUMENTED_LIMIT, LIST_SEPERATOR,
+};
+
+const ENV_VAR_PREFIX: &str = "RUDRA_";
+const FLAG_PREFIX: &str = "--";
+
+// every environment variable can be set by a flag of the same name, e.g. RUDRA_APP_BASE_URL by --app-base-url,
+// switches don't need a value
+const OPTIONS: [(&str, bool); 24] = [
+    (ENV_VAR_REPOSITORY, false),
+    (ENV_VAR_CONFIG_FILE, false),
+    (ENV_VAR_APP_BASE_URL, false),
+    (ENV_VAR_OPENAPI_SOURCE, false),
+    (ENV_VAR_PORT, false),
+    (ENV_VAR_MAPPING, false),
+    (ENV_VAR_DEBUG, true),
+    (ENV_VAR_ACCOUNT_FOR_FORBIDDEN, true),
+    (ENV_VAR_ACCOUNT_FOR_UNAUTORIZED, true),
+    (ENV_VAR_TEST_COVERAGE, false),
+    (ENV_VAR_IS_MERGE, true),
+    (ENV_VAR_ONLY_ACCOUNT_MERGE, true),
+    (ENV_VAR_GROUPINGS, false),
+    (ENV_VAR_PROXY_BACKEND, false),
+    (ENV_VAR_DEFAULT_RESPONSE_POLICY, false),
+    (ENV_VAR_PATH_MATCHING, false),
+    (ENV_VAR_UNDOCUMENTED_LIMIT, false),
+    (ENV_VAR_REJECTED_LINES_LIMIT, false),
+    (ENV_VAR_QUERY_PARAMETER_COVERAGE, false),
+    (ENV_VAR_MEDIA_TYPE_COVERAGE, true),
+    (ENV_VAR_REDACTED_HEADERS, false),
+    (ENV_VAR_ACCESS_LOG, false),
+    (ENV_VAR_JSON_REPORT, false),
+    (ENV_VAR_JUNIT_REPORT, false),
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Proxy,
+    Evaluate,
+    CheckConfig,
+    ListEndpoints,
+    Report(PathBuf),
+    Help,
+}
+
+// options are stored like the environment variables they override
+#[derive(Debug)]
+pub struct CliArguments {
+    pub command: Command,
+    pub options: HashMap<String, String>,
+}
+
+// without a subcommand rudra captures traffic and evaluates it afterwards, like it always did inside of docker
+pub fn parse_cli_arguments(args: &[String]) -> Result<CliArguments, Error> {
+    let mut args = args.iter().peekable();
+    let command_str = match args.peek() {
+        Some(arg) if !arg.starts_with('-') => args.next().map(|arg| arg.as_str()),
+        _ => None,
+    };
+
+    let mut options: HashMap<String, String> = HashMap::new();
+    let mut positionals = vec![];
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            return Ok(CliArguments {
+                command: Command::Help,
+                options,
+            });
+        }
+        let flag = match arg.strip_prefix(FLAG_PREFIX) {
+            Some(flag) => flag,
+            None => {
+                positionals.push(arg);
+                continue;
+            }
+        };
+        let (name, inline_value) = match flag.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (flag, None),
+        };
+        let (env_var, is_switch) = match find_option(name) {
+            Some(option) => option,
+            None => return Err(Error::InvalidCliArgument(format!("unknown option {}{}", FLAG_PREFIX, name))),
+        };
+        let value = match (inline_value, is_switch) {
+            (Some(value), _) => value,
+            (None, true) => "true".to_string(),
+            (None, false) => match args.next() {
+                Some(value) => value.clone(),
+                None => return Err(Error::InvalidCliArgument(format!("{}{} requires a value", FLAG_PREFIX, name))),
+            },
+        };
+        add_option(&mut options, env_var, &value);
+    }
+
+    let command = match (command_str, positionals.as_slice()) {
+        (None, []) | (Some("proxy"), []) => Command::Proxy,
+        (Some("evaluate"), []) => Command::Evaluate,
+        (Some("check-config"), []) => Command::CheckConfig,
+        (Some("list-endpoints"), []) => Command::ListEndpoints,
+        (Some("report"), [report]) => Command::Report(PathBuf::from(report)),
+        (Some("report"), _) => return Err(Error::InvalidCliArgument("report requires the path of a json report".to_string())),
+        (Some("help"), _) => Command::Help,
+        (Some(command_str), []) => return Err(Error::InvalidCliArgument(format!("unknown command {}", command_str))),
+        (_, positionals) => return Err(Error::InvalidCliArgument(format!("unexpected argument {}", positionals[0]))),
+    };
+
+    Ok(CliArguments { command, options })
+}
+
+pub fn get_usage() -> String {
+    let mut usage = String::from(
+        "Usage: rudra [COMMAND] [OPTIONS]
+
+Commands:
+  proxy           Capture requests to your services and evaluate them once stopped (default)
+  evaluate        Evaluate a recorded access log, nginx's by default
+  check-config    Validate the configuration and openapi specs
+  list-endpoints  List the endpoints of the openapi specs
+  report <PATH>   Print a previously written json report
+  help            Print this message
+
+Options (override the respective RUDRA_* environment variables and the configuration file):
+",
+    );
+    for (env_var, is_switch) in OPTIONS {
+        let value = if is_switch { "" } else { " <VALUE>" };
+        usage.push_str(&format!("  {}{}{} ({})\n", FLAG_PREFIX, get_flag_name(env_var), value, env_var));
+    }
+    usage
+}
+
+fn find_option(name: &str) -> Option<(&'static str, bool)> {
+    OPTIONS.iter().find(|(env_var, _)| get_flag_name(env_var) == name).copied()
+}
+
+fn get_flag_name(env_var: &str) -> String {
+    env_var.trim_start_matches(ENV_VAR_PREFIX).to_lowercase().replace('_', "-")
+}
+
+// services, groupings and redacted headers can be passed multiple times
+fn add_option(options: &mut HashMap<String, String>, env_var: &str, value: &str) {
+    let value = value.replace('\n', LIST_SEPERATOR);
+    let seperator = match env_var {
+        ENV_VAR_MAPPING | ENV_VAR_GROUPINGS => LIST_SEPERATOR,
+        ENV_VAR_REDACTED_HEADERS => ",",
+        _ => {
+            options.insert(env_var.to_string(), value);
+            return;
+        }
+    };
+    options
+        .entry(env_var.to_string())
+        .and_modify(|existing| {
+            existing.push_str(seperator);
+            existing.push_str(&value);
+        })
+        .or_insert(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::{
+        config::environment::{
+            ENV_VAR_APP_BASE_URL, ENV_VAR_DEBUG, ENV_VAR_GROUPINGS, ENV_VAR_MEDIA_TYPE_COVERAGE, ENV_VAR_TEST_COVERAGE, LIST_SEPERATOR,
+        },
+        utils::Error,
+    };
+
+    use super::{get_usage, parse_cli_arguments, CliArguments, Command};
+
+    fn parse(args: &[&str]) -> Result<CliArguments, Error> {
+        parse_cli_arguments(&args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>())
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(parse(&[]).unwrap().command, Command::Proxy);
+        assert_eq!(parse(&["--debug"]).unwrap().command, Command::Proxy);
+        assert_eq!(parse(&["evaluate"]).unwrap().command, Command::Evaluate);
+        assert_eq!(parse(&["check-config"]).unwrap().command, Command::CheckConfig);
+        assert_eq!(parse(&["list-endpoints"]).unwrap().command, Command::ListEndpoints);
+        assert_eq!(parse(&["report", "rudra-report.json"]).unwrap().command, Command::Report(PathBuf::from("rudra-report.json")));
+        assert_eq!(parse(&["evaluate", "--help"]).unwrap().command, Command::Help);
+        assert_eq!(parse(&["-h"]).unwrap().command, Command::Help);
+
+        assert!(parse(&["report"]).is_err());
+        assert!(parse(&["evaluate", "foo"]).is_err());
+        assert!(parse(&["foo"]).is_err());
+    }
+
+    #[test]
+    fn maps_flags_to_environment_variables() {
+        let arguments = parse(&[
+            "evaluate",
+            "--app-base-url",
+            "http://localhost:8080",
+            "--test-coverage=80%",
+            "--debug",
+            "--media-type-coverage=false",
+            "--groupings",
+            "/foo; GET; 200; true;",
+            "--groupings",
+            "/bar; GET; 200; true;",
+        ])
+        .unwrap();
+
+        assert_eq!(arguments.options[ENV_VAR_APP_BASE_URL], "http://localhost:8080");
+        assert_eq!(arguments.options[ENV_VAR_TEST_COVERAGE], "80%");
+        assert_eq!(arguments.options[ENV_VAR_DEBUG], "true");
+        assert_eq!(arguments.options[ENV_VAR_MEDIA_TYPE_COVERAGE], "false");
+        assert_eq!(
+            arguments.options[ENV_VAR_GROUPINGS],
+            format!("/foo; GET; 200; true;{}/bar; GET; 200; true;", LIST_SEPERATOR)
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_or_incomplete_flags() {
+        assert!(parse(&["--instance-url", "http://localhost:8080"]).is_err());
+        assert!(parse(&["evaluate", "--port"]).is_err());
+        assert!(get_usage().contains("--account-for-unautorized (RUDRA_ACCOUNT_FOR_UNAUTORIZED)"));
+    }
+}
@@ -4,24 +4,53 @@ use std::{collections::{HashMap, HashSet}, env, path::Path, str::FromStr, sync::
 use url::Url;
 use lazy_static::lazy_static;
 
-use super::{OpenapiSource, RudraConfig, Runtime};
-
-const ENV_VAR_APP_BASE_URL: &str = "RUDRA_APP_BASE_URL";
-const ENV_VAR_DEBUG: &str = "RUDRA_DEBUG";
-const ENV_VAR_OPENAPI_SOURCE: &str = "RUDRA_OPENAPI_SOURCE";
-const ENV_VAR_ACCOUNT_FOR_FORBIDDEN: &str = "RUDRA_ACCOUNT_FOR_FORBIDDEN";
-const ENV_VAR_ACCOUNT_FOR_UNAUTORIZED: &str = "RUDRA_ACCOUNT_FOR_UNAUTORIZED";
-const ENV_VAR_TEST_COVERAGE: &str = "RUDRA_TEST_COVERAGE";
-const ENV_VAR_PORT: &str = "RUDRA_PORT";
-const ENV_VAR_MAPPING: &str = "RUDRA_MAPPING";
-const ENV_VAR_IS_MERGE: &str = "RUDRA_IS_MERGE";
-const ENV_VAR_ONLY_ACCOUNT_MERGE: &str = "RUDRA_ONLY_ACCOUNT_MERGE";
-const ENV_VAR_GROUPINGS: &str = "RUDRA_GROUPINGS";
+use super::{
+    file::{find_config_file, parse_config_file, FileConfig},
+    DefaultResponsePolicy, OpenapiSource, PathMatching, ProxyBackend, RudraConfig, Runtime, REPOSITORY_MOUNT_POINT,
+};
+
+pub(super) const ENV_VAR_APP_BASE_URL: &str = "RUDRA_APP_BASE_URL";
+pub(super) const ENV_VAR_DEBUG: &str = "RUDRA_DEBUG";
+pub(super) const ENV_VAR_OPENAPI_SOURCE: &str = "RUDRA_OPENAPI_SOURCE";
+pub(super) const ENV_VAR_ACCOUNT_FOR_FORBIDDEN: &str = "RUDRA_ACCOUNT_FOR_FORBIDDEN";
+pub(super) const ENV_VAR_ACCOUNT_FOR_UNAUTORIZED: &str = "RUDRA_ACCOUNT_FOR_UNAUTORIZED";
+pub(super) const ENV_VAR_TEST_COVERAGE: &str = "RUDRA_TEST_COVERAGE";
+pub(super) const ENV_VAR_PORT: &str = "RUDRA_PORT";
+pub(super) const ENV_VAR_MAPPING: &str = "RUDRA_MAPPING";
+pub(super) const ENV_VAR_IS_MERGE: &str = "RUDRA_IS_MERGE";
+pub(super) const ENV_VAR_ONLY_ACCOUNT_MERGE: &str = "RUDRA_ONLY_ACCOUNT_MERGE";
+pub(super) const ENV_VAR_GROUPINGS: &str = "RUDRA_GROUPINGS";
+pub(super) const ENV_VAR_PROXY_BACKEND: &str = "RUDRA_PROXY_BACKEND";
+pub(super) const ENV_VAR_UNDOCUMENTED_LIMIT: &str = "RUDRA_UNDOCUMENTED_LIMIT";
+pub(super) const ENV_VAR_JSON_REPORT: &str = "RUDRA_JSON_REPORT";
+pub(super) const ENV_VAR_JUNIT_REPORT: &str = "RUDRA_JUNIT_REPORT";
+pub(super) const ENV_VAR_DEFAULT_RESPONSE_POLICY: &str = "RUDRA_DEFAULT_RESPONSE_POLICY";
+pub(super) const ENV_VAR_QUERY_PARAMETER_COVERAGE: &str = "RUDRA_QUERY_PARAMETER_COVERAGE";
+pub(super) const ENV_VAR_MEDIA_TYPE_COVERAGE: &str = "RUDRA_MEDIA_TYPE_COVERAGE";
+pub(super) const ENV_VAR_REDACTED_HEADERS: &str = "RUDRA_REDACTED_HEADERS";
+pub(super) const ENV_VAR_CONFIG_FILE: &str = "RUDRA_CONFIG_FILE";
+pub(super) const ENV_VAR_REPOSITORY: &str = "RUDRA_REPOSITORY";
+pub(super) const ENV_VAR_ACCESS_LOG: &str = "RUDRA_ACCESS_LOG";
+pub(super) const ENV_VAR_PATH_MATCHING: &str = "RUDRA_PATH_MATCHING";
+pub(super) const ENV_VAR_REJECTED_LINES_LIMIT: &str = "RUDRA_REJECTED_LINES_LIMIT";
 
 const DEFAULT_TEST_COVERAGE: f32 = 0.7;
 const DEFAULT_PORT: u16 = 13750;
+// any line that can't be parsed, like a tls handshake sent to the plain port, fails the evaluation unless a limit is configured
+const DEFAULT_REJECTED_LINES_LIMIT: f32 = 0.0;
+// credentials are never recorded, additional headers can be configured
+const DEFAULT_REDACTED_HEADERS: [&str; 3] = ["authorization", "proxy-authorization", "cookie"];
 
-const LIST_SEPERATOR: &str = "RUDRA_LINE_SEPERATOR";
+const PROXY_BACKEND_NATIVE: &str = "native";
+const PROXY_BACKEND_NGINX: &str = "nginx";
+
+const DEFAULT_RESPONSE_POLICY_IGNORE: &str = "ignore";
+const DEFAULT_RESPONSE_POLICY_MATCH_UNDOCUMENTED: &str = "match-undocumented";
+
+const PATH_MATCHING_MOST_SPECIFIC: &str = "most-specific";
+const PATH_MATCHING_ALL: &str = "all";
+
+pub(super) const LIST_SEPERATOR: &str = "RUDRA_LINE_SEPERATOR";
 const MAPPING_SUBDELIMITER: &str = ";";
 
 lazy_static! {
@@ -30,8 +59,34 @@ lazy_static! {
 
 impl RudraConfig {
     pub fn from_raw(env_vars: &HashMap<String, String>) -> Result<RudraConfig, Error> {
+        RudraConfig::from_file_and_env(FileConfig::default(), env_vars)
+    }
+
+    // environment variables take precedence over the configuration file, unless they are empty
+    pub fn from_file_and_env(file_config: FileConfig, env_vars: &HashMap<String, String>) -> Result<RudraConfig, Error> {
+        let mut merged_vars = file_config.values;
+        for (key, value) in env_vars {
+            if !value.is_empty() || !merged_vars.contains_key(key) {
+                merged_vars.insert(key.clone(), value.clone());
+            }
+        }
+        let env_vars = &merged_vars;
+
+        // runtimes of the environment replace those of the file entirely
+        let file_runtimes = match file_config.runtimes {
+            Some(runtimes)
+                if ![ENV_VAR_MAPPING, ENV_VAR_OPENAPI_SOURCE, ENV_VAR_APP_BASE_URL, ENV_VAR_PORT]
+                    .iter()
+                    .any(|key| key_exists_and_is_not_empty(key, env_vars)) =>
+            {
+                Some(runtimes)
+            }
+            _ => None,
+        };
+
         // Check if all enviroment variables exist
-        if !key_exists_and_is_not_empty(ENV_VAR_MAPPING, env_vars)
+        if file_runtimes.is_none()
+            && !key_exists_and_is_not_empty(ENV_VAR_MAPPING, env_vars)
             && (!key_exists_and_is_not_empty(ENV_VAR_OPENAPI_SOURCE, env_vars)
                 || !key_exists_and_is_not_empty(ENV_VAR_APP_BASE_URL, env_vars))
         {
@@ -55,12 +110,49 @@ impl RudraConfig {
         };
         let is_merge = get_bool_env_var(ENV_VAR_IS_MERGE, env_vars);
         let only_account_for_merge = get_bool_env_var(ENV_VAR_ONLY_ACCOUNT_MERGE, env_vars);
-        let groupings = match env_vars.get(ENV_VAR_GROUPINGS) {
-            Some(grouping_str) => parse_grouping(grouping_str)?,
-            None => HashSet::new(),
+        let groupings = match (env_vars.get(ENV_VAR_GROUPINGS), file_config.groupings) {
+            (Some(grouping_str), _) if grouping_str.trim() != "" => parse_grouping(grouping_str)?,
+            (_, Some(groupings)) => groupings,
+            (Some(grouping_str), None) => parse_grouping(grouping_str)?,
+            (None, None) => HashSet::new(),
         };
-
-        let runtimes = if !key_exists_and_is_not_empty(ENV_VAR_MAPPING, env_vars) {
+        let proxy_backend = match env_vars.get(ENV_VAR_PROXY_BACKEND) {
+            Some(proxy_backend_str) => parse_proxy_backend(proxy_backend_str)?,
+            None => ProxyBackend::Native,
+        };
+        let default_response_policy = match env_vars.get(ENV_VAR_DEFAULT_RESPONSE_POLICY) {
+            Some(default_response_policy_str) => parse_default_response_policy(default_response_policy_str)?,
+            None => DefaultResponsePolicy::Ignore,
+        };
+        let path_matching = match env_vars.get(ENV_VAR_PATH_MATCHING) {
+            Some(path_matching_str) => parse_path_matching(path_matching_str)?,
+            None => PathMatching::MostSpecific,
+        };
+        let undocumented_limit = match env_vars.get(ENV_VAR_UNDOCUMENTED_LIMIT) {
+            Some(undocumented_limit_str) => parse_undocumented_limit(undocumented_limit_str)?,
+            None => None,
+        };
+        let rejected_lines_limit = match env_vars.get(ENV_VAR_REJECTED_LINES_LIMIT) {
+            Some(rejected_lines_limit_str) => parse_rejected_lines_limit(rejected_lines_limit_str)?,
+            None => DEFAULT_REJECTED_LINES_LIMIT,
+        };
+        let query_parameter_coverage = match env_vars.get(ENV_VAR_QUERY_PARAMETER_COVERAGE) {
+            Some(coverage_str) => parse_optional_coverage(coverage_str)?,
+            None => None,
+        };
+        let media_type_coverage = get_bool_env_var(ENV_VAR_MEDIA_TYPE_COVERAGE, env_vars);
+        let redacted_headers = match env_vars.get(ENV_VAR_REDACTED_HEADERS) {
+            Some(redacted_headers_str) => parse_redacted_headers(redacted_headers_str),
+            None => parse_redacted_headers(""),
+        };
+        let repository = get_path_env_var(ENV_VAR_REPOSITORY, env_vars).unwrap_or_else(|| Box::from(Path::new(REPOSITORY_MOUNT_POINT)));
+        let access_log = get_path_env_var(ENV_VAR_ACCESS_LOG, env_vars);
+        let json_report = get_path_env_var(ENV_VAR_JSON_REPORT, env_vars);
+        let junit_report = get_path_env_var(ENV_VAR_JUNIT_REPORT, env_vars);
+
+        let runtimes = if let Some(runtimes) = file_runtimes {
+            runtimes
+        } else if !key_exists_and_is_not_empty(ENV_VAR_MAPPING, env_vars) {
             let openapi_source_str = match env_vars.get(ENV_VAR_OPENAPI_SOURCE) {
                 Some(openapi_source) => openapi_source,
                 None => return Err(Error::MissingConfiguration),
@@ -105,15 +197,40 @@ impl RudraConfig {
             is_merge,
             only_account_for_merge,
             groupings,
+            proxy_backend,
+            default_response_policy,
+            path_matching,
+            undocumented_limit,
+            rejected_lines_limit,
+            query_parameter_coverage,
+            media_type_coverage,
+            redacted_headers,
+            repository,
+            access_log,
+            json_report,
+            junit_report,
         })
     }
 
-    pub fn from_env() -> Result<RudraConfig, Error> {
+    // command line options are named like the environment variables they override
+    pub fn from_env_and_cli(cli_options: &HashMap<String, String>) -> Result<RudraConfig, Error> {
         let mut env_vars = HashMap::new();
         for var in env::vars() {
             env_vars.insert(var.0, var.1);
         }
-        RudraConfig::from_raw(&env_vars)
+        for (key, value) in cli_options {
+            env_vars.insert(key.clone(), value.clone());
+        }
+        // the configuration file can't move the repository it is looked up in
+        let repository = match env_vars.get(ENV_VAR_REPOSITORY) {
+            Some(repository) if repository.trim() != "" => Path::new(repository.trim()),
+            _ => Path::new(REPOSITORY_MOUNT_POINT),
+        };
+        let file_config = match find_config_file(env_vars.get(ENV_VAR_CONFIG_FILE), repository)? {
+            Some(path) => parse_config_file(&path)?,
+            None => FileConfig::default(),
+        };
+        RudraConfig::from_file_and_env(file_config, &env_vars)
     }
 
     pub fn global_is_debug() -> bool {
@@ -204,7 +321,7 @@ fn parse_untill_mapping_subdelimiter<'a>(index: usize, base: &'a str) -> Result<
     }
 }
 
-fn check_runtime_compatability(runtimes: &Vec<Arc<Runtime>>) -> bool {
+pub(super) fn check_runtime_compatability(runtimes: &Vec<Arc<Runtime>>) -> bool {
     let mut ports = HashSet::new();
     for runtime in runtimes {
         if !ports.insert(runtime.port) {
@@ -218,7 +335,7 @@ fn replace_escaped_sequences(base: &str) -> String {
     base.replace("\\;", ";")
 }
 
-fn parse_runtime(openapi_source_str: &str, app_base_url_str: &str, port_str: Option<&str>) -> Result<Arc<Runtime>, Error> {
+pub(super) fn parse_runtime(openapi_source_str: &str, app_base_url_str: &str, port_str: Option<&str>) -> Result<Arc<Runtime>, Error> {
     let openapi_source = match Url::from_str(openapi_source_str.trim()) {
         Ok(openapi_url) => OpenapiSource::Url(openapi_url),
         Err(_) => OpenapiSource::Path(Box::from(Path::new(openapi_source_str.trim()))),
@@ -248,12 +365,19 @@ fn get_bool_env_var(key: &str, env_vars: &HashMap<String, String>) -> bool {
     }
 }
 
+fn get_path_env_var(key: &str, env_vars: &HashMap<String, String>) -> Option<Box<Path>> {
+    match env_vars.get(key) {
+        Some(path_str) if path_str.trim() != "" => Some(Box::from(Path::new(path_str.trim()))),
+        _ => None,
+    }
+}
+
 fn parse_bool(bool_str: &str) -> bool {
     // the "nope" is just a fun easter egg
     bool_str != "0" && bool_str != "" && bool_str != "false" && bool_str != "nope"
 }
 
-fn translate_test_coverage(coverage_str: &str) -> Result<f32, Error> {
+pub(super) fn translate_test_coverage(coverage_str: &str) -> Result<f32, Error> {
     if coverage_str.trim() == "" {
         return Ok(DEFAULT_TEST_COVERAGE);
     }
@@ -282,7 +406,77 @@ fn translate_test_coverage(coverage_str: &str) -> Result<f32, Error> {
     }
 }
 
-fn parse_grouping_strings(path_str: &str, methods_str: &str, status_str: &str, is_ignore_group_str: &str) -> Result<Grouping, Error> {
+// unlike the test coverage, optional thresholds aren't enforced unless set
+pub(super) fn parse_optional_coverage(coverage_str: &str) -> Result<Option<f32>, Error> {
+    if coverage_str.trim() == "" {
+        return Ok(None);
+    }
+    Ok(Some(translate_test_coverage(coverage_str.trim())?))
+}
+
+pub(super) fn parse_proxy_backend(proxy_backend_str: &str) -> Result<ProxyBackend, Error> {
+    match proxy_backend_str.trim().to_lowercase().as_str() {
+        "" | PROXY_BACKEND_NATIVE => Ok(ProxyBackend::Native),
+        PROXY_BACKEND_NGINX => Ok(ProxyBackend::Nginx),
+        _ => Err(Error::InvalidProxyBackend(proxy_backend_str.to_string())),
+    }
+}
+
+pub(super) fn parse_default_response_policy(default_response_policy_str: &str) -> Result<DefaultResponsePolicy, Error> {
+    match default_response_policy_str.trim().to_lowercase().as_str() {
+        "" | DEFAULT_RESPONSE_POLICY_IGNORE => Ok(DefaultResponsePolicy::Ignore),
+        DEFAULT_RESPONSE_POLICY_MATCH_UNDOCUMENTED => Ok(DefaultResponsePolicy::MatchUndocumented),
+        _ => Err(Error::InvalidDefaultResponsePolicy(default_response_policy_str.to_string())),
+    }
+}
+
+pub(super) fn parse_path_matching(path_matching_str: &str) -> Result<PathMatching, Error> {
+    match path_matching_str.trim().to_lowercase().as_str() {
+        "" | PATH_MATCHING_MOST_SPECIFIC => Ok(PathMatching::MostSpecific),
+        PATH_MATCHING_ALL => Ok(PathMatching::All),
+        _ => Err(Error::InvalidPathMatching(path_matching_str.to_string())),
+    }
+}
+
+pub(super) fn parse_undocumented_limit(undocumented_limit_str: &str) -> Result<Option<usize>, Error> {
+    if undocumented_limit_str.trim() == "" {
+        return Ok(None);
+    }
+    match undocumented_limit_str.trim().parse() {
+        Ok(undocumented_limit) => Ok(Some(undocumented_limit)),
+        Err(_) => Err(Error::InvalidUndocumentedLimit(undocumented_limit_str.to_string())),
+    }
+}
+
+// share of the lines of an access log that may be skipped, written like the test coverage
+pub(super) fn parse_rejected_lines_limit(rejected_lines_limit_str: &str) -> Result<f32, Error> {
+    let trimmed = rejected_lines_limit_str.trim();
+    if trimmed.is_empty() {
+        return Ok(DEFAULT_REJECTED_LINES_LIMIT);
+    }
+    let rejected_lines_limit = match trimmed.strip_suffix('%') {
+        Some(percentage) => percentage.trim().parse::<f32>().map(|percentage| percentage / 100.0),
+        None => trimmed.parse::<f32>(),
+    };
+    match rejected_lines_limit {
+        Ok(rejected_lines_limit) if (0.0..=1.0).contains(&rejected_lines_limit) => Ok(rejected_lines_limit),
+        _ => Err(Error::InvalidRejectedLinesLimit(rejected_lines_limit_str.to_string())),
+    }
+}
+
+// header names are case insensitive, so they are stored in lowercase
+fn parse_redacted_headers(redacted_headers_str: &str) -> Vec<String> {
+    let mut redacted_headers: Vec<String> = DEFAULT_REDACTED_HEADERS.iter().map(|header| header.to_string()).collect();
+    for header in redacted_headers_str.split(',') {
+        let header = header.trim().to_lowercase();
+        if !header.is_empty() && !redacted_headers.contains(&header) {
+            redacted_headers.push(header);
+        }
+    }
+    redacted_headers
+}
+
+pub(super) fn parse_grouping_strings(path_str: &str, methods_str: &str, status_str: &str, is_ignore_group_str: &str) -> Result<Grouping, Error> {
     let path = OpenapiPath::from_str(path_str.trim())?;
     let mut methods = vec![];
     for method_str in methods_str.split(",") {
@@ -315,10 +509,11 @@ mod test {
             get_bool_env_var, key_exists_and_is_not_empty, translate_test_coverage,
             DEFAULT_TEST_COVERAGE, ENV_VAR_MAPPING, ENV_VAR_PORT, parse_complex_mapping, replace_escaped_sequences, ENV_VAR_ACCOUNT_FOR_UNAUTORIZED, ENV_VAR_ONLY_ACCOUNT_MERGE,
         },
-        OpenapiSource,
+        file::FileConfig,
+        DefaultResponsePolicy, OpenapiSource, PathMatching, ProxyBackend,
     };
 
-    use super::{RudraConfig, ENV_VAR_APP_BASE_URL, ENV_VAR_DEBUG, ENV_VAR_OPENAPI_SOURCE, parse_untill_mapping_subdelimiter, ENV_VAR_ACCOUNT_FOR_FORBIDDEN, ENV_VAR_IS_MERGE};
+    use super::{RudraConfig, parse_runtime, ENV_VAR_APP_BASE_URL, ENV_VAR_DEBUG, ENV_VAR_OPENAPI_SOURCE, parse_untill_mapping_subdelimiter, ENV_VAR_ACCOUNT_FOR_FORBIDDEN, ENV_VAR_IS_MERGE, ENV_VAR_PROXY_BACKEND, ENV_VAR_UNDOCUMENTED_LIMIT, ENV_VAR_JSON_REPORT, ENV_VAR_JUNIT_REPORT, ENV_VAR_DEFAULT_RESPONSE_POLICY, ENV_VAR_QUERY_PARAMETER_COVERAGE, ENV_VAR_MEDIA_TYPE_COVERAGE, ENV_VAR_REDACTED_HEADERS, ENV_VAR_REPOSITORY, ENV_VAR_ACCESS_LOG, ENV_VAR_PATH_MATCHING, ENV_VAR_REJECTED_LINES_LIMIT, DEFAULT_REJECTED_LINES_LIMIT};
 
     fn generate_config_map() -> HashMap<String, String> {
         let mut config_map = HashMap::new();
@@ -688,4 +883,212 @@ mod test {
         let config = RudraConfig::from_raw(&env_vars).unwrap();
         assert!(config.only_account_for_merge);
     }
+
+    #[test]
+    fn defaults_to_native_proxy_backend() {
+        let env_vars = generate_config_map();
+        let config = RudraConfig::from_raw(&env_vars).unwrap();
+        assert_eq!(config.proxy_backend, ProxyBackend::Native);
+
+        let mut env_vars = generate_config_map();
+        env_vars.insert(ENV_VAR_PROXY_BACKEND.to_string(), " ".to_string());
+        let config = RudraConfig::from_raw(&env_vars).unwrap();
+        assert_eq!(config.proxy_backend, ProxyBackend::Native);
+    }
+
+    #[test]
+    fn recognises_nginx_proxy_backend() {
+        let mut env_vars = generate_config_map();
+        env_vars.insert(ENV_VAR_PROXY_BACKEND.to_string(), "Nginx".to_string());
+        let config = RudraConfig::from_raw(&env_vars).unwrap();
+        assert_eq!(config.proxy_backend, ProxyBackend::Nginx);
+    }
+
+    #[test]
+    fn unknown_proxy_backend_leads_to_error() {
+        let mut env_vars = generate_config_map();
+        env_vars.insert(ENV_VAR_PROXY_BACKEND.to_string(), "apache".to_string());
+        assert!(RudraConfig::from_raw(&env_vars).is_err());
+    }
+
+    #[test]
+    fn has_no_undocumented_limit_by_default() {
+        let env_vars = generate_config_map();
+        let config = RudraConfig::from_raw(&env_vars).unwrap();
+        assert_eq!(config.undocumented_limit, None);
+    }
+
+    #[test]
+    fn recognises_undocumented_limit() {
+        let mut env_vars = generate_config_map();
+        env_vars.insert(ENV_VAR_UNDOCUMENTED_LIMIT.to_string(), " 0 ".to_string());
+        assert_eq!(RudraConfig::from_raw(&env_vars).unwrap().undocumented_limit, Some(0));
+
+        env_vars.insert(ENV_VAR_UNDOCUMENTED_LIMIT.to_string(), "".to_string());
+        assert_eq!(RudraConfig::from_raw(&env_vars).unwrap().undocumented_limit, None);
+    }
+
+    #[test]
+    fn invalid_undocumented_limit_leads_to_error() {
+        let mut env_vars = generate_config_map();
+        env_vars.insert(ENV_VAR_UNDOCUMENTED_LIMIT.to_string(), "-1".to_string());
+        assert!(RudraConfig::from_raw(&env_vars).is_err());
+    }
+
+    #[test]
+    fn recognises_rejected_lines_limit() {
+        let mut env_vars = generate_config_map();
+        assert_float_eq!(RudraConfig::from_raw(&env_vars).unwrap().rejected_lines_limit, DEFAULT_REJECTED_LINES_LIMIT, abs <= 0.0001);
+
+        env_vars.insert(ENV_VAR_REJECTED_LINES_LIMIT.to_string(), "5%".to_string());
+        assert_float_eq!(RudraConfig::from_raw(&env_vars).unwrap().rejected_lines_limit, 0.05, abs <= 0.0001);
+
+        env_vars.insert(ENV_VAR_REJECTED_LINES_LIMIT.to_string(), "0.5%".to_string());
+        assert_float_eq!(RudraConfig::from_raw(&env_vars).unwrap().rejected_lines_limit, 0.005, abs <= 0.0001);
+
+        env_vars.insert(ENV_VAR_REJECTED_LINES_LIMIT.to_string(), "150%".to_string());
+        assert!(RudraConfig::from_raw(&env_vars).is_err());
+
+        env_vars.insert(ENV_VAR_REJECTED_LINES_LIMIT.to_string(), "-0.1".to_string());
+        assert!(RudraConfig::from_raw(&env_vars).is_err());
+    }
+
+    #[test]
+    fn recognises_json_report_path() {
+        let mut env_vars = generate_config_map();
+        assert_eq!(RudraConfig::from_raw(&env_vars).unwrap().json_report, None);
+
+        env_vars.insert(ENV_VAR_JSON_REPORT.to_string(), " reports/rudra.json ".to_string());
+        assert_eq!(
+            RudraConfig::from_raw(&env_vars).unwrap().json_report,
+            Some(Box::from(Path::new("reports/rudra.json")))
+        );
+    }
+
+    #[test]
+    fn recognises_junit_report_path() {
+        let mut env_vars = generate_config_map();
+        assert_eq!(RudraConfig::from_raw(&env_vars).unwrap().junit_report, None);
+
+        env_vars.insert(ENV_VAR_JUNIT_REPORT.to_string(), "reports/rudra.xml".to_string());
+        assert_eq!(
+            RudraConfig::from_raw(&env_vars).unwrap().junit_report,
+            Some(Box::from(Path::new("reports/rudra.xml")))
+        );
+    }
+
+    #[test]
+    fn ignores_default_responses_by_default() {
+        let env_vars = generate_config_map();
+        assert_eq!(RudraConfig::from_raw(&env_vars).unwrap().default_response_policy, DefaultResponsePolicy::Ignore);
+    }
+
+    #[test]
+    fn recognises_default_response_policy() {
+        let mut env_vars = generate_config_map();
+        env_vars.insert(ENV_VAR_DEFAULT_RESPONSE_POLICY.to_string(), "match-undocumented".to_string());
+        assert_eq!(
+            RudraConfig::from_raw(&env_vars).unwrap().default_response_policy,
+            DefaultResponsePolicy::MatchUndocumented
+        );
+
+        env_vars.insert(ENV_VAR_DEFAULT_RESPONSE_POLICY.to_string(), "always".to_string());
+        assert!(RudraConfig::from_raw(&env_vars).is_err());
+    }
+
+    #[test]
+    fn has_no_query_parameter_coverage_by_default() {
+        let env_vars = generate_config_map();
+        assert_eq!(RudraConfig::from_raw(&env_vars).unwrap().query_parameter_coverage, None);
+    }
+
+    #[test]
+    fn recognises_query_parameter_coverage() {
+        let mut env_vars = generate_config_map();
+        env_vars.insert(ENV_VAR_QUERY_PARAMETER_COVERAGE.to_string(), "80%".to_string());
+        assert_eq!(RudraConfig::from_raw(&env_vars).unwrap().query_parameter_coverage, Some(0.8));
+
+        env_vars.insert(ENV_VAR_QUERY_PARAMETER_COVERAGE.to_string(), " ".to_string());
+        assert_eq!(RudraConfig::from_raw(&env_vars).unwrap().query_parameter_coverage, None);
+
+        env_vars.insert(ENV_VAR_QUERY_PARAMETER_COVERAGE.to_string(), "1.5%".to_string());
+        assert!(RudraConfig::from_raw(&env_vars).unwrap().query_parameter_coverage.is_some());
+
+        env_vars.insert(ENV_VAR_QUERY_PARAMETER_COVERAGE.to_string(), "150%".to_string());
+        assert!(RudraConfig::from_raw(&env_vars).is_err());
+    }
+
+    #[test]
+    fn media_type_coverage_is_opt_in() {
+        let mut env_vars = generate_config_map();
+        assert!(!RudraConfig::from_raw(&env_vars).unwrap().media_type_coverage);
+
+        env_vars.insert(ENV_VAR_MEDIA_TYPE_COVERAGE.to_string(), "true".to_string());
+        assert!(RudraConfig::from_raw(&env_vars).unwrap().media_type_coverage);
+    }
+
+    #[test]
+    fn redacts_credentials_and_configured_headers() {
+        let mut env_vars = generate_config_map();
+        assert_eq!(RudraConfig::from_raw(&env_vars).unwrap().redacted_headers, vec!["authorization", "proxy-authorization", "cookie"]);
+
+        env_vars.insert(ENV_VAR_REDACTED_HEADERS.to_string(), "X-Api-Key, ,Cookie".to_string());
+        assert_eq!(
+            RudraConfig::from_raw(&env_vars).unwrap().redacted_headers,
+            vec!["authorization", "proxy-authorization", "cookie", "x-api-key"]
+        );
+    }
+
+    #[test]
+    fn environment_overrides_config_file_unless_empty() {
+        let create_file_config = || {
+            let mut file_config = FileConfig::default();
+            file_config.values.insert(ENV_VAR_DEBUG.to_string(), "false".to_string());
+            file_config.values.insert(ENV_VAR_JSON_REPORT.to_string(), "rudra-report.json".to_string());
+            file_config.runtimes = Some(vec![parse_runtime("./test/resource/swagger.json", "http://localhost:8081", Some("13751")).unwrap()]);
+            file_config
+        };
+
+        let mut env_vars = HashMap::new();
+        env_vars.insert(ENV_VAR_DEBUG.to_string(), "".to_string());
+        env_vars.insert(ENV_VAR_OPENAPI_SOURCE.to_string(), "".to_string());
+        let config = RudraConfig::from_file_and_env(create_file_config(), &env_vars).unwrap();
+        assert!(!config.debug);
+        assert_eq!(config.json_report.as_deref(), Some(Path::new("rudra-report.json")));
+        assert_eq!(config.runtimes[0].port, 13751);
+
+        let mut env_vars = generate_config_map();
+        env_vars.insert(ENV_VAR_JSON_REPORT.to_string(), "".to_string());
+        let config = RudraConfig::from_file_and_env(create_file_config(), &env_vars).unwrap();
+        assert!(config.debug);
+        assert_eq!(config.json_report.as_deref(), Some(Path::new("rudra-report.json")));
+        assert_eq!(config.runtimes.len(), 1);
+        assert_eq!(config.runtimes[0].port, 13750);
+    }
+
+    #[test]
+    fn resolves_files_in_configured_repository() {
+        let mut env_vars = generate_config_map();
+        let config = RudraConfig::from_raw(&env_vars).unwrap();
+        assert_eq!(config.repository.as_ref(), Path::new("/repo"));
+        assert_eq!(config.access_log, None);
+
+        env_vars.insert(ENV_VAR_REPOSITORY.to_string(), " ./ci ".to_string());
+        env_vars.insert(ENV_VAR_ACCESS_LOG.to_string(), "rudra-access.log".to_string());
+        let config = RudraConfig::from_raw(&env_vars).unwrap();
+        assert_eq!(config.repository.as_ref(), Path::new("./ci"));
+        assert_eq!(config.access_log.as_deref(), Some(Path::new("rudra-access.log")));
+    }
+
+    #[test]
+    fn matches_most_specific_paths_by_default() {
+        let mut env_vars = generate_config_map();
+        assert_eq!(RudraConfig::from_raw(&env_vars).unwrap().path_matching, PathMatching::MostSpecific);
+
+        env_vars.insert(ENV_VAR_PATH_MATCHING.to_string(), " All ".to_string());
+        assert_eq!(RudraConfig::from_raw(&env_vars).unwrap().path_matching, PathMatching::All);
+
+        env_vars.insert(ENV_VAR_PATH_MATCHING.to_string(), "first".to_string());
+        assert!(RudraConfig::from_raw(&env_vars).is_err());
+    }
 }
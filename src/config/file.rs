@@ -0,0 +1,411 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use json::JsonValue;
+use yaml_rust::YamlLoader;
+
+use super::{
+    environment::{
+        check_runtime_compatability, parse_default_response_policy, parse_grouping_strings, parse_optional_coverage,
+        parse_path_matching, parse_proxy_backend, parse_rejected_lines_limit, parse_runtime, parse_undocumented_limit, translate_test_coverage, ENV_VAR_ACCESS_LOG, ENV_VAR_ACCOUNT_FOR_FORBIDDEN,
+        ENV_VAR_ACCOUNT_FOR_UNAUTORIZED, ENV_VAR_DEBUG, ENV_VAR_DEFAULT_RESPONSE_POLICY, ENV_VAR_JSON_REPORT, ENV_VAR_JUNIT_REPORT,
+        ENV_VAR_MEDIA_TYPE_COVERAGE, ENV_VAR_ONLY_ACCOUNT_MERGE, ENV_VAR_PATH_MATCHING, ENV_VAR_PROXY_BACKEND, ENV_VAR_QUERY_PARAMETER_COVERAGE, ENV_VAR_REJECTED_LINES_LIMIT,
+        ENV_VAR_REDACTED_HEADERS, ENV_VAR_TEST_COVERAGE, ENV_VAR_UNDOCUMENTED_LIMIT,
+    },
+    Runtime,
+};
+use crate::{
+    models::Grouping,
+    parser::yaml_to_json,
+    utils::{read_file_to_string_or_err, Error},
+};
+
+// looked up in the repository if no configuration file is specified
+const CONFIG_FILE_NAMES: [&str; 3] = ["rudra.yaml", "rudra.yml", "rudra.toml"];
+
+const KEY_RUNTIMES: &str = "runtimes";
+const KEY_GROUPINGS: &str = "groupings";
+
+enum OptionKind {
+    Bool,
+    Coverage,
+    OptionalCoverage,
+    Limit,
+    ProxyBackend,
+    DefaultResponsePolicy,
+    PathMatching,
+    RejectedLinesLimit,
+    Path,
+    List,
+}
+
+// options of the configuration file with the environment variable they can be overriden by, nested keys are joined by "."
+const OPTIONS: [(&str, &str, OptionKind); 16] = [
+    ("debug", ENV_VAR_DEBUG, OptionKind::Bool),
+    ("proxy-backend", ENV_VAR_PROXY_BACKEND, OptionKind::ProxyBackend),
+    ("default-response-policy", ENV_VAR_DEFAULT_RESPONSE_POLICY, OptionKind::DefaultResponsePolicy),
+    ("path-matching", ENV_VAR_PATH_MATCHING, OptionKind::PathMatching),
+    ("account-for-security-forbidden", ENV_VAR_ACCOUNT_FOR_FORBIDDEN, OptionKind::Bool),
+    ("account-for-security-unautorized", ENV_VAR_ACCOUNT_FOR_UNAUTORIZED, OptionKind::Bool),
+    ("only-account-for-pr", ENV_VAR_ONLY_ACCOUNT_MERGE, OptionKind::Bool),
+    ("media-type-coverage", ENV_VAR_MEDIA_TYPE_COVERAGE, OptionKind::Bool),
+    ("redacted-headers", ENV_VAR_REDACTED_HEADERS, OptionKind::List),
+    ("access-log", ENV_VAR_ACCESS_LOG, OptionKind::Path),
+    ("thresholds.test-coverage", ENV_VAR_TEST_COVERAGE, OptionKind::Coverage),
+    ("thresholds.query-parameter-coverage", ENV_VAR_QUERY_PARAMETER_COVERAGE, OptionKind::OptionalCoverage),
+    ("thresholds.undocumented-limit", ENV_VAR_UNDOCUMENTED_LIMIT, OptionKind::Limit),
+    ("thresholds.rejected-lines-limit", ENV_VAR_REJECTED_LINES_LIMIT, OptionKind::RejectedLinesLimit),
+    ("reports.json", ENV_VAR_JSON_REPORT, OptionKind::Path),
+    ("reports.junit", ENV_VAR_JUNIT_REPORT, OptionKind::Path),
+];
+
+// the validated content of a configuration file, options are stored like the environment variables overriding them
+#[derive(Debug, Default)]
+pub struct FileConfig {
+    pub values: HashMap<String, String>,
+    pub runtimes: Option<Vec<Arc<Runtime>>>,
+    pub groupings: Option<HashSet<Grouping>>,
+}
+
+// an explicitly configured file has to exist, otherwise the default names are tried
+pub fn find_config_file(config_file: Option<&String>, mount_point: &Path) -> Result<Option<PathBuf>, Error> {
+    match config_file {
+        Some(config_file) if config_file.trim() != "" => {
+            let path = mount_point.join(config_file.trim());
+            match path.is_file() {
+                true => Ok(Some(path)),
+                false => Err(Error::InvalidConfigFile(format!("{:?} doesn't exist", path))),
+            }
+        }
+        _ => Ok(CONFIG_FILE_NAMES.iter().map(|name| mount_point.join(name)).find(|path| path.is_file())),
+    }
+}
+
+pub fn parse_config_file(path: &Path) -> Result<FileConfig, Error> {
+    let config_string = read_file_to_string_or_err(path, Error::ProblemOpeningFile(Box::from(path)))?;
+    let config = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("yaml") | Some("yml") => parse_yaml(&config_string)?,
+        Some("toml") => parse_toml(&config_string)?,
+        _ => return Err(Error::InvalidConfigFile(format!("{:?} has to be a yaml or toml file", path))),
+    };
+    parse_config(&config)
+}
+
+fn parse_yaml(config_string: &str) -> Result<JsonValue, Error> {
+    let documents = match YamlLoader::load_from_str(config_string) {
+        Ok(documents) => documents,
+        Err(why) => return Err(Error::InvalidConfigFile(why.to_string())),
+    };
+    match documents.first() {
+        Some(document) => match yaml_to_json(document) {
+            Ok(config) => Ok(config),
+            Err(_) => Err(Error::InvalidConfigFile("yaml aliases and complex keys aren't supported".to_string())),
+        },
+        None => Ok(JsonValue::new_object()),
+    }
+}
+
+fn parse_toml(config_string: &str) -> Result<JsonValue, Error> {
+    match config_string.parse::<toml::Value>() {
+        Ok(config) => Ok(toml_to_json(&config)),
+        Err(why) => Err(Error::InvalidConfigFile(why.to_string())),
+    }
+}
+
+fn toml_to_json(toml: &toml::Value) -> JsonValue {
+    match toml {
+        toml::Value::String(value) => JsonValue::from(value.as_str()),
+        toml::Value::Integer(value) => JsonValue::from(*value),
+        toml::Value::Float(value) => JsonValue::from(*value),
+        toml::Value::Boolean(value) => JsonValue::from(*value),
+        toml::Value::Datetime(value) => JsonValue::from(value.to_string()),
+        toml::Value::Array(array) => JsonValue::Array(array.iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => {
+            let mut object = JsonValue::new_object();
+            for (key, value) in table {
+                object[key.as_str()] = toml_to_json(value);
+            }
+            object
+        }
+    }
+}
+
+fn parse_config(config: &JsonValue) -> Result<FileConfig, Error> {
+    if !config.is_object() {
+        return Err(Error::InvalidConfigFile("the configuration has to be a mapping of options".to_string()));
+    }
+
+    let mut file_config = FileConfig::default();
+    for (key, value) in config.entries() {
+        match key {
+            KEY_RUNTIMES => file_config.runtimes = Some(parse_runtimes(value)?),
+            KEY_GROUPINGS => file_config.groupings = Some(parse_groupings(value)?),
+            _ => parse_option(key, value, &mut file_config.values)?,
+        }
+    }
+    Ok(file_config)
+}
+
+fn parse_option(key: &str, value: &JsonValue, values: &mut HashMap<String, String>) -> Result<(), Error> {
+    // sections like "thresholds" only group options
+    if value.is_object() {
+        for (nested_key, nested_value) in value.entries() {
+            parse_option(&format!("{}.{}", key, nested_key), nested_value, values)?;
+        }
+        return Ok(());
+    }
+
+    let (env_var, kind) = match OPTIONS.iter().find(|(option, _, _)| *option == key) {
+        Some((_, env_var, kind)) => (env_var, kind),
+        None => return Err(invalid_key(key, "unknown option")),
+    };
+    values.insert(env_var.to_string(), parse_option_value(key, value, kind)?);
+    Ok(())
+}
+
+// values are converted to the format of the environment variables and checked with the same parsers
+fn parse_option_value(key: &str, value: &JsonValue, kind: &OptionKind) -> Result<String, Error> {
+    let value_str = match (kind, value) {
+        (OptionKind::Bool, JsonValue::Boolean(value)) => value.to_string(),
+        (OptionKind::Bool, _) => return Err(invalid_key(key, "expected true or false")),
+        (OptionKind::List, JsonValue::Array(items)) => {
+            let items: Option<Vec<&str>> = items.iter().map(|item| item.as_str()).collect();
+            match items {
+                Some(items) => items.join(","),
+                None => return Err(invalid_key(key, "expected a list of strings")),
+            }
+        }
+        (_, JsonValue::Short(_)) | (_, JsonValue::String(_)) | (_, JsonValue::Number(_)) => value.to_string(),
+        _ => return Err(invalid_key(key, "expected a string or number")),
+    };
+
+    let result = match kind {
+        OptionKind::Coverage => translate_test_coverage(&value_str).map(|_| ()),
+        OptionKind::OptionalCoverage => parse_optional_coverage(&value_str).map(|_| ()),
+        OptionKind::Limit => parse_undocumented_limit(&value_str).map(|_| ()),
+        OptionKind::ProxyBackend => parse_proxy_backend(&value_str).map(|_| ()),
+        OptionKind::DefaultResponsePolicy => parse_default_response_policy(&value_str).map(|_| ()),
+        OptionKind::PathMatching => parse_path_matching(&value_str).map(|_| ()),
+        OptionKind::RejectedLinesLimit => parse_rejected_lines_limit(&value_str).map(|_| ()),
+        OptionKind::Bool | OptionKind::Path | OptionKind::List => Ok(()),
+    };
+    match result {
+        Ok(_) => Ok(value_str),
+        Err(why) => Err(invalid_key(key, &why.get_error_msg())),
+    }
+}
+
+fn parse_runtimes(value: &JsonValue) -> Result<Vec<Arc<Runtime>>, Error> {
+    if !value.is_array() || value.is_empty() {
+        return Err(invalid_key(KEY_RUNTIMES, "expected a list with at least one runtime"));
+    }
+
+    let mut runtimes = vec![];
+    for (index, runtime) in value.members().enumerate() {
+        let key = format!("{}[{}]", KEY_RUNTIMES, index);
+        check_known_keys(&key, runtime, &["openapi-source", "app-base-url", "port"])?;
+        let openapi_source = get_required_string(&key, runtime, "openapi-source")?;
+        let app_base_url = get_required_string(&key, runtime, "app-base-url")?;
+        let port = match &runtime["port"] {
+            JsonValue::Null => None,
+            JsonValue::Number(_) => Some(runtime["port"].to_string()),
+            _ => return Err(invalid_key(&format!("{}.port", key), "expected a port number")),
+        };
+
+        match parse_runtime(&openapi_source, &app_base_url, port.as_deref()) {
+            Ok(runtime) => runtimes.push(runtime),
+            Err(why @ Error::InvalidApplicationURL(_)) => {
+                return Err(invalid_key(&format!("{}.app-base-url", key), &why.get_error_msg()))
+            }
+            Err(why) => return Err(invalid_key(&format!("{}.port", key), &why.get_error_msg())),
+        }
+    }
+
+    if !check_runtime_compatability(&runtimes) {
+        return Err(invalid_key(KEY_RUNTIMES, &Error::MappingDuplicatePorts.get_error_msg()));
+    }
+    Ok(runtimes)
+}
+
+fn parse_groupings(value: &JsonValue) -> Result<HashSet<Grouping>, Error> {
+    if !value.is_array() {
+        return Err(invalid_key(KEY_GROUPINGS, "expected a list of groupings"));
+    }
+
+    let mut groupings = HashSet::new();
+    for (index, grouping) in value.members().enumerate() {
+        let key = format!("{}[{}]", KEY_GROUPINGS, index);
+        check_known_keys(&key, grouping, &["path", "methods", "status-codes", "ignore"])?;
+        let path = get_required_string(&key, grouping, "path")?;
+        let methods = get_list(&key, grouping, "methods")?;
+        let status_codes = get_list(&key, grouping, "status-codes")?;
+        let ignore = match &grouping["ignore"] {
+            JsonValue::Null => false,
+            JsonValue::Boolean(ignore) => *ignore,
+            _ => return Err(invalid_key(&format!("{}.ignore", key), "expected true or false")),
+        };
+
+        match parse_grouping_strings(&path, &methods, &status_codes, &ignore.to_string()) {
+            Ok(grouping) => groupings.insert(grouping),
+            Err(why @ Error::InvalidMethodString(_)) => return Err(invalid_key(&format!("{}.methods", key), &why.get_error_msg())),
+            Err(why @ Error::InvalidStatusCode(_)) => {
+                return Err(invalid_key(&format!("{}.status-codes", key), &why.get_error_msg()))
+            }
+            Err(why) => return Err(invalid_key(&format!("{}.path", key), &why.get_error_msg())),
+        };
+    }
+    Ok(groupings)
+}
+
+fn check_known_keys(key: &str, value: &JsonValue, known_keys: &[&str]) -> Result<(), Error> {
+    if !value.is_object() {
+        return Err(invalid_key(key, &format!("expected a mapping with {}", known_keys.join(", "))));
+    }
+    match value.entries().find(|(entry_key, _)| !known_keys.contains(entry_key)) {
+        Some((entry_key, _)) => Err(invalid_key(&format!("{}.{}", key, entry_key), "unknown option")),
+        None => Ok(()),
+    }
+}
+
+fn get_required_string(key: &str, value: &JsonValue, name: &str) -> Result<String, Error> {
+    match value[name].as_str() {
+        Some(string) => Ok(string.to_string()),
+        None => Err(invalid_key(&format!("{}.{}", key, name), "expected a string")),
+    }
+}
+
+// lists can also be written as a comma separated string, like in the environment variables
+fn get_list(key: &str, value: &JsonValue, name: &str) -> Result<String, Error> {
+    let items = match &value[name] {
+        JsonValue::Array(items) => items.iter().map(|item| item.to_string()).collect::<Vec<String>>().join(","),
+        JsonValue::Short(_) | JsonValue::String(_) | JsonValue::Number(_) => value[name].to_string(),
+        _ => return Err(invalid_key(&format!("{}.{}", key, name), "expected a list")),
+    };
+    Ok(items)
+}
+
+fn invalid_key(key: &str, why: &str) -> Error {
+    Error::InvalidConfigFileKey(key.to_string(), why.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{
+        config::environment::{ENV_VAR_DEBUG, ENV_VAR_JSON_REPORT, ENV_VAR_REDACTED_HEADERS, ENV_VAR_TEST_COVERAGE},
+        models::Method,
+        utils::Error,
+    };
+
+    use super::{find_config_file, parse_toml, parse_yaml, parse_config};
+
+    const YAML_CONFIG: &str = "
+debug: true
+redacted-headers: [X-Api-Key, X-Session]
+runtimes:
+  - openapi-source: docs/openapi.yaml
+    app-base-url: http://localhost:8080
+  - openapi-source: https://example.com/openapi.json
+    app-base-url: http://localhost:8081
+    port: 13751
+groupings:
+  - path: /pets/{id}
+    methods: [get, delete]
+    status-codes: [200, 404]
+    ignore: true
+thresholds:
+  test-coverage: 75%
+reports:
+  json: rudra-report.json
+";
+
+    fn get_error_key(error: Error) -> String {
+        match error {
+            Error::InvalidConfigFileKey(key, _) => key,
+            _ => panic!("expected an invalid key, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn parses_yaml_config() {
+        let file_config = parse_config(&parse_yaml(YAML_CONFIG).unwrap()).unwrap();
+
+        assert_eq!(file_config.values[ENV_VAR_DEBUG], "true");
+        assert_eq!(file_config.values[ENV_VAR_REDACTED_HEADERS], "X-Api-Key,X-Session");
+        assert_eq!(file_config.values[ENV_VAR_TEST_COVERAGE], "75%");
+        assert_eq!(file_config.values[ENV_VAR_JSON_REPORT], "rudra-report.json");
+
+        let runtimes = file_config.runtimes.unwrap();
+        assert_eq!(runtimes.len(), 2);
+        assert_eq!(runtimes[0].port, 13750);
+        assert_eq!(runtimes[1].port, 13751);
+
+        let groupings = file_config.groupings.unwrap();
+        let grouping = groupings.iter().next().unwrap();
+        assert_eq!(grouping.methods(), &vec![Method::GET, Method::DELETE]);
+        assert!(grouping.is_ignore_group);
+    }
+
+    #[test]
+    fn parses_toml_config() {
+        let toml_config = "
+debug = false
+
+[thresholds]
+test-coverage = 0.8
+undocumented-limit = 0
+
+[[runtimes]]
+openapi-source = \"docs/openapi.yaml\"
+app-base-url = \"http://localhost:8080\"
+port = 8000
+";
+        let file_config = parse_config(&parse_toml(toml_config).unwrap()).unwrap();
+
+        assert_eq!(file_config.values[ENV_VAR_DEBUG], "false");
+        assert_eq!(file_config.values[ENV_VAR_TEST_COVERAGE], "0.8");
+        assert_eq!(file_config.runtimes.unwrap()[0].port, 8000);
+        assert!(file_config.groupings.is_none());
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_key() {
+        let parse = |config: &str| parse_config(&parse_yaml(config).unwrap());
+
+        assert_eq!(get_error_key(parse("thresholds:\n  test-coverage: 150%").unwrap_err()), "thresholds.test-coverage");
+        assert_eq!(get_error_key(parse("tresholds:\n  test-coverage: 50%").unwrap_err()), "tresholds.test-coverage");
+        assert_eq!(get_error_key(parse("debug: yes please").unwrap_err()), "debug");
+        assert_eq!(get_error_key(parse("proxy-backend: apache").unwrap_err()), "proxy-backend");
+        assert_eq!(
+            get_error_key(parse("runtimes:\n  - openapi-source: a.yaml\n    app-base-url: http://a\n    port: 99999").unwrap_err()),
+            "runtimes[0].port"
+        );
+        assert_eq!(get_error_key(parse("runtimes:\n  - openapi-source: a.yaml").unwrap_err()), "runtimes[0].app-base-url");
+        assert_eq!(
+            get_error_key(parse("groupings:\n  - path: /pets\n    methods: [fetch]\n    status-codes: [200]").unwrap_err()),
+            "groupings[0].methods"
+        );
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        assert!(matches!(parse_yaml("debug: [true"), Err(Error::InvalidConfigFile(_))));
+        assert!(matches!(parse_toml("debug = "), Err(Error::InvalidConfigFile(_))));
+    }
+
+    #[test]
+    fn finds_configured_or_default_config_file() {
+        let mount_point = Path::new("./test/resource");
+
+        assert_eq!(find_config_file(None, mount_point).unwrap(), None);
+        assert_eq!(
+            find_config_file(Some(&"swagger.yaml".to_string()), mount_point).unwrap(),
+            Some(mount_point.join("swagger.yaml"))
+        );
+        assert!(find_config_file(Some(&"rudra.toml".to_string()), mount_point).is_err());
+    }
+}
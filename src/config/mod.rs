@@ -2,13 +2,19 @@ use std::{path::Path, sync::Arc, collections::HashSet};
 
 use url::Url;
 
+mod cli;
 mod environment;
+mod file;
 mod nginx;
 
+pub use cli::{get_usage, parse_cli_arguments, CliArguments, Command};
 pub use nginx::configure_nginx;
 
 use crate::models::Grouping;
 
+// the repository is mounted here when running rudra via docker
+pub const REPOSITORY_MOUNT_POINT: &str = "/repo";
+
 #[derive(Debug)]
 pub struct RudraConfig {
     pub debug: bool,
@@ -19,6 +25,19 @@ pub struct RudraConfig {
     pub is_merge: bool,
     pub only_account_for_merge: bool,
     pub groupings: HashSet<Grouping>,
+    pub proxy_backend: ProxyBackend,
+    pub default_response_policy: DefaultResponsePolicy,
+    pub path_matching: PathMatching,
+    pub undocumented_limit: Option<usize>,
+    pub rejected_lines_limit: f32,
+    pub query_parameter_coverage: Option<f32>,
+    pub media_type_coverage: bool,
+    pub redacted_headers: Vec<String>,
+    // relative paths of specs, reports and the access log are resolved against the repository
+    pub repository: Box<Path>,
+    pub access_log: Option<Box<Path>>,
+    pub json_report: Option<Box<Path>>,
+    pub junit_report: Option<Box<Path>>,
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -34,6 +53,25 @@ pub enum OpenapiSource {
     Url(Url),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyBackend {
+    Native,
+    Nginx,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultResponsePolicy {
+    Ignore,
+    MatchUndocumented,
+}
+
+// which operations an observation counts towards if several paths of the spec match it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathMatching {
+    MostSpecific,
+    All,
+}
+
 impl RudraConfig {
     pub fn print(&self) {
         println!("Configuration for Rudra:");
@@ -52,13 +90,35 @@ impl RudraConfig {
             );
             println!(" - port: {}", self.runtimes[runtime_index].port);
         }
-        println!(
-            " - account_for_security: {}",
-            self.security_accounts_for_forbidden
-        );
+        println!(" - account_for_forbidden: {}", self.security_accounts_for_forbidden);
+        println!(" - account_for_unautorized: {}", self.security_accounts_for_unautorized);
         println!(" - test_coverage: {}", self.test_coverage);
         println!(" - is_merge: {}", self.is_merge);
         println!(" - only_account_for_merge: {}", self.only_account_for_merge);
+        println!(" - proxy_backend: {:?}", self.proxy_backend);
+        println!(" - default_response_policy: {:?}", self.default_response_policy);
+        println!(" - path_matching: {:?}", self.path_matching);
+        match self.undocumented_limit {
+            Some(undocumented_limit) => println!(" - undocumented_limit: {}", undocumented_limit),
+            None => println!(" - undocumented_limit: none"),
+        }
+        println!(" - rejected_lines_limit: {}", self.rejected_lines_limit);
+        match self.query_parameter_coverage {
+            Some(query_parameter_coverage) => println!(" - query_parameter_coverage: {}", query_parameter_coverage),
+            None => println!(" - query_parameter_coverage: none"),
+        }
+        println!(" - media_type_coverage: {}", self.media_type_coverage);
+        println!(" - redacted_headers: {}", self.redacted_headers.join(", "));
+        println!(" - repository: {:?}", self.repository);
+        if let Some(access_log) = &self.access_log {
+            println!(" - access_log: {:?}", access_log);
+        }
+        if let Some(json_report) = &self.json_report {
+            println!(" - json_report: {:?}", json_report);
+        }
+        if let Some(junit_report) = &self.junit_report {
+            println!(" - junit_report: {:?}", junit_report);
+        }
     }
 
     pub fn all_openapi_sources_are_paths(&self) -> bool {
@@ -5,10 +5,13 @@ use std::{
 };
 
 use super::{RudraConfig, Runtime};
-use crate::utils::Error;
+use crate::{
+    models::{EndpointConfiguration, ParameterLocation, REDACTED_HEADER_VALUE},
+    utils::{print_debug_message, Error},
+};
 
-pub fn configure_nginx(config: &RudraConfig) -> Result<(), Error> {
-    configure_nginx_file(config, Path::new("/etc/nginx/nginx.conf"))
+pub fn configure_nginx(config: &RudraConfig, openapi_endpoints: &[EndpointConfiguration]) -> Result<(), Error> {
+    configure_nginx_file(config, openapi_endpoints, Path::new("/etc/nginx/nginx.conf"))
 }
 
 fn replace_url(base: &String, url: &str) -> String {
@@ -34,6 +37,44 @@ fn replace_runtime_configurations(base: &String, runtimes: &Vec<Arc<Runtime>>) -
     base.replace("INSERT_CONFIGURATIONS_HERE", &config_string)
 }
 
+// nginx has no variable containing all request headers, so only the declared header parameters are logged
+fn replace_logged_headers(base: &str, openapi_endpoints: &[EndpointConfiguration], redacted_headers: &[String]) -> String {
+    let mut header_names: Vec<String> = vec![];
+    let header_parameters = openapi_endpoints
+        .iter()
+        .flat_map(|endpoint| &endpoint.parameters)
+        .filter(|parameter| parameter.location == ParameterLocation::Header);
+    for parameter in header_parameters {
+        let header_name = parameter.name.to_lowercase();
+        if !header_names.contains(&header_name) {
+            header_names.push(header_name);
+        }
+    }
+
+    let mut redactions = String::new();
+    let mut logged_headers = String::new();
+    for header_name in header_names {
+        if !header_name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            print_debug_message(format!("Header {} can't be logged by nginx", header_name));
+            continue;
+        }
+        let variable = format!("http_{}", header_name.replace('-', "_"));
+        // redacted values are replaced before logging, empty ones are still logged as "-"
+        if redacted_headers.contains(&header_name) {
+            redactions.push_str(&format!(
+                "map ${} $rudra_redacted_{} {{ \"\" \"\"; default \"{}\"; }}\n    ",
+                variable, variable, REDACTED_HEADER_VALUE
+            ));
+            logged_headers.push_str(&format!(" \"{}: $rudra_redacted_{}\"", header_name, variable));
+        } else {
+            logged_headers.push_str(&format!(" \"{}: ${}\"", header_name, variable));
+        }
+    }
+
+    base.replace("INSERT_HEADER_REDACTIONS_HERE", &redactions)
+        .replace("INSERT_LOGGED_HEADERS_HERE", &logged_headers)
+}
+
 fn build_runtime_config(runtime: &Runtime) -> String {
     const BASE_CONFIGURATION_STRUCTURE: &str = "
     server {
@@ -69,7 +110,7 @@ fn open_config_file(path: &Path, for_writing: bool) -> Result<File, Error> {
     }
 }
 
-fn configure_nginx_file(config: &RudraConfig, path: &Path) -> Result<(), Error> {
+fn configure_nginx_file(config: &RudraConfig, openapi_endpoints: &[EndpointConfiguration], path: &Path) -> Result<(), Error> {
     let mut file = open_config_file(path, false)?;
 
     let mut config_string = String::new();
@@ -87,6 +128,7 @@ fn configure_nginx_file(config: &RudraConfig, path: &Path) -> Result<(), Error>
         config_string = replace_error_log(&config_string);
     }
     config_string = replace_runtime_configurations(&config_string, &config.runtimes);
+    config_string = replace_logged_headers(&config_string, openapi_endpoints, &config.redacted_headers);
 
     let mut file = open_config_file(path, true)?;
     match file.write_all(config_string.as_bytes()) {
@@ -116,12 +158,13 @@ mod tests {
     use crate::{
         config::{
             nginx::{
-                configure_nginx_file, replace_error_log, replace_port_number,
+                configure_nginx_file, replace_error_log, replace_logged_headers, replace_port_number,
                 replace_runtime_configurations, replace_url,
             },
             OpenapiSource, Runtime,
         },
-        utils::test::create_mock_config,
+        models::{EndpointConfiguration, Method, Parameter, ParameterLocation},
+        utils::test::{create_mock_config, create_mock_runtime},
     };
 
     use super::open_config_file;
@@ -141,7 +184,7 @@ mod tests {
 
         let nginx_path = Path::new("./test/resource/nginx.conf");
         let config = create_mock_config();
-        configure_nginx_file(&config, &nginx_path).unwrap();
+        configure_nginx_file(&config, &[], nginx_path).unwrap();
         let mut conf_string = String::from("");
         File::open(&nginx_path)
             .unwrap()
@@ -201,4 +244,26 @@ mod tests {
             "... stuff ... 13567 ... stuff ..."
         );
     }
+
+    #[test]
+    fn logs_declared_header_parameters_and_redacts_secrets() {
+        let runtime = Arc::new(create_mock_runtime());
+        let openapi_endpoints = vec![
+            EndpointConfiguration::new(Method::GET, "/pets", 200, runtime.clone(), false).unwrap().with_parameters(vec![
+                Parameter::new("X-Tenant-Id", ParameterLocation::Header, true),
+                Parameter::new("X-Api-Key", ParameterLocation::Header, true),
+                Parameter::new("limit", ParameterLocation::Query, false),
+            ]),
+            EndpointConfiguration::new(Method::GET, "/pets", 404, runtime, false)
+                .unwrap()
+                .with_parameters(vec![Parameter::new("x-tenant-id", ParameterLocation::Header, true)]),
+        ];
+        let test_string = String::from("INSERT_HEADER_REDACTIONS_HERE'\"$http_accept\"INSERT_LOGGED_HEADERS_HERE'");
+
+        assert_eq!(
+            replace_logged_headers(&test_string, &openapi_endpoints, &["x-api-key".to_string()]),
+            "map $http_x_api_key $rudra_redacted_http_x_api_key { \"\" \"\"; default \"<redacted>\"; }\n    \
+             '\"$http_accept\" \"x-tenant-id: $http_x_tenant_id\" \"x-api-key: $rudra_redacted_http_x_api_key\"'"
+        );
+    }
 }
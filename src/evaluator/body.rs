@@ -0,0 +1,340 @@
+use std::collections::HashSet;
+
+use json::JsonValue;
+
+use crate::{
+    config::PathMatching,
+    models::{json_to_enum_value, EndpointConfiguration, Grouping, Observation, Schema},
+};
+
+use super::{parameters::get_operations, routes::RouteIndex};
+
+const ROOT_PATH: &str = "$";
+
+// the shapes a request body schema allows, with the information which of them were sent
+pub struct BodyCoverage<'a> {
+    pub operation: &'a EndpointConfiguration,
+    pub observed_bodies: usize,
+    pub optional_properties: Vec<BodyCoverageItem>,
+    pub branches: Vec<BodyCoverageItem>,
+    pub discriminator_values: Vec<BodyCoverageItem>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct BodyCoverageItem {
+    pub name: String,
+    pub is_exercised: bool,
+}
+
+// every declared case is identified by a json path like name, so declared and sent cases can be compared
+#[derive(Default)]
+struct BodyCases {
+    optional_properties: Vec<String>,
+    branches: Vec<String>,
+    discriminator_values: Vec<String>,
+}
+
+pub fn evaluate_request_bodies<'a>(
+    openapi_endpoints: &'a [EndpointConfiguration],
+    observations: &[Observation],
+    groupings: &HashSet<Grouping>,
+    path_matching: &PathMatching,
+) -> Vec<BodyCoverage<'a>> {
+    let mut body_coverages = vec![];
+    let observations_by_operation = RouteIndex::new(openapi_endpoints, path_matching).group_by_operation(observations);
+    for operation in get_operations(openapi_endpoints, groupings) {
+        let schema = match &operation.request_body {
+            Some(schema) => schema,
+            None => continue,
+        };
+
+        let mut declared_cases = BodyCases::default();
+        collect_cases(schema, ROOT_PATH, &mut declared_cases);
+
+        let mut sent_cases = BodyCases::default();
+        let mut observed_bodies = 0;
+        // bodies beyond the kept variants are counted, but their cases are unknown
+        for observation in observations_by_operation.get(operation) {
+            observed_bodies += observation.request_bodies.count();
+            for (body, _) in observation.request_bodies.iter() {
+                collect_sent_cases(schema, body, ROOT_PATH, &mut sent_cases);
+            }
+        }
+
+        body_coverages.push(BodyCoverage {
+            operation,
+            observed_bodies,
+            optional_properties: to_coverage_items(declared_cases.optional_properties, &sent_cases.optional_properties),
+            branches: to_coverage_items(declared_cases.branches, &sent_cases.branches),
+            discriminator_values: to_coverage_items(declared_cases.discriminator_values, &sent_cases.discriminator_values),
+        });
+    }
+    body_coverages
+}
+
+impl BodyCoverage<'_> {
+    pub fn unexercised_items(&self) -> Vec<&BodyCoverageItem> {
+        self.optional_properties
+            .iter()
+            .chain(self.branches.iter())
+            .chain(self.discriminator_values.iter())
+            .filter(|item| !item.is_exercised)
+            .collect()
+    }
+}
+
+fn to_coverage_items(declared_cases: Vec<String>, sent_cases: &[String]) -> Vec<BodyCoverageItem> {
+    declared_cases
+        .into_iter()
+        .map(|name| {
+            let is_exercised = sent_cases.contains(&name);
+            BodyCoverageItem { name, is_exercised }
+        })
+        .collect()
+}
+
+fn collect_cases(schema: &Schema, path: &str, cases: &mut BodyCases) {
+    for (property_name, property_schema) in &schema.properties {
+        let property_path = get_property_path(path, property_name);
+        if !schema.is_required(property_name) {
+            push_unique(&mut cases.optional_properties, property_path.clone());
+        }
+        collect_cases(property_schema, &property_path, cases);
+    }
+    if let Some(items) = &schema.items {
+        collect_cases(items, &get_items_path(path), cases);
+    }
+    for all_of_schema in &schema.all_of {
+        collect_cases(all_of_schema, path, cases);
+    }
+    for (keyword, branches) in [("oneOf", &schema.one_of), ("anyOf", &schema.any_of)] {
+        for (index, branch) in branches.iter().enumerate() {
+            push_unique(&mut cases.branches, get_branch_name(path, keyword, branch, index));
+            collect_cases(branch, path, cases);
+        }
+    }
+    if let Some(discriminator) = &schema.discriminator {
+        for value in get_discriminator_values(schema) {
+            push_unique(&mut cases.discriminator_values, get_discriminator_name(path, &discriminator.property_name, &value));
+        }
+    }
+}
+
+fn collect_sent_cases(schema: &Schema, value: &JsonValue, path: &str, cases: &mut BodyCases) {
+    if value.is_object() {
+        for (property_name, property_schema) in &schema.properties {
+            if !value.has_key(property_name) {
+                continue;
+            }
+            let property_path = get_property_path(path, property_name);
+            if !schema.is_required(property_name) {
+                push_unique(&mut cases.optional_properties, property_path.clone());
+            }
+            collect_sent_cases(property_schema, &value[property_name.as_str()], &property_path, cases);
+        }
+    }
+    if let (JsonValue::Array(elements), Some(items)) = (value, &schema.items) {
+        for element in elements {
+            collect_sent_cases(items, element, &get_items_path(path), cases);
+        }
+    }
+    for all_of_schema in &schema.all_of {
+        collect_sent_cases(all_of_schema, value, path, cases);
+    }
+
+    let discriminator_value = schema
+        .discriminator
+        .as_ref()
+        .and_then(|discriminator| value[discriminator.property_name.as_str()].as_str());
+    for (keyword, branches) in [("oneOf", &schema.one_of), ("anyOf", &schema.any_of)] {
+        for (index, branch) in branches.iter().enumerate() {
+            // a discriminator decides the branch by itself, otherwise every branch the value matches is taken
+            let is_selected = match discriminator_value {
+                Some(discriminator_value) => Some(get_discriminated_schema_name(schema, discriminator_value)) == branch.name,
+                None => branch.matches(value),
+            };
+            if is_selected {
+                push_unique(&mut cases.branches, get_branch_name(path, keyword, branch, index));
+                collect_sent_cases(branch, value, path, cases);
+            }
+        }
+    }
+    if let Some(discriminator) = &schema.discriminator {
+        let sent_value = &value[discriminator.property_name.as_str()];
+        if !sent_value.is_null() {
+            let sent_value = json_to_enum_value(sent_value);
+            push_unique(&mut cases.discriminator_values, get_discriminator_name(path, &discriminator.property_name, &sent_value));
+        }
+    }
+}
+
+// without a mapping, the names of the branches are used as values
+fn get_discriminator_values(schema: &Schema) -> Vec<String> {
+    let mut values: Vec<String> = match &schema.discriminator {
+        Some(discriminator) => discriminator.mapping.iter().map(|(value, _)| value.clone()).collect(),
+        None => vec![],
+    };
+    for branch in schema.one_of.iter().chain(schema.any_of.iter()) {
+        let is_mapped = schema
+            .discriminator
+            .iter()
+            .flat_map(|discriminator| discriminator.mapping.iter())
+            .any(|(_, name)| Some(name) == branch.name.as_ref());
+        if let (false, Some(name)) = (is_mapped, &branch.name) {
+            values.push(name.clone());
+        }
+    }
+    values
+}
+
+// unmapped values name the schema directly
+fn get_discriminated_schema_name(schema: &Schema, discriminator_value: &str) -> String {
+    schema
+        .discriminator
+        .iter()
+        .flat_map(|discriminator| discriminator.mapping.iter())
+        .find(|(value, _)| value == discriminator_value)
+        .map(|(_, name)| name.clone())
+        .unwrap_or_else(|| discriminator_value.to_string())
+}
+
+fn get_property_path(path: &str, property_name: &str) -> String {
+    format!("{}.{}", path, property_name)
+}
+
+fn get_items_path(path: &str) -> String {
+    format!("{}[]", path)
+}
+
+fn get_branch_name(path: &str, keyword: &str, branch: &Schema, index: usize) -> String {
+    match &branch.name {
+        Some(name) => format!("{} {} {}", path, keyword, name),
+        None => format!("{} {} #{}", path, keyword, index),
+    }
+}
+
+fn get_discriminator_name(path: &str, property_name: &str, value: &str) -> String {
+    format!("{}.{}={}", path, property_name, value)
+}
+
+fn push_unique(cases: &mut Vec<String>, case: String) {
+    if !cases.contains(&case) {
+        cases.push(case);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Arc};
+
+    use crate::{
+        config::PathMatching,
+        models::{Discriminator, EndpointConfiguration, Method, Observation, Schema},
+        utils::test::create_mock_runtime,
+    };
+
+    use super::{evaluate_request_bodies, BodyCoverageItem};
+
+    fn create_named_schema(name: &str, property_name: &str) -> Schema {
+        Schema {
+            name: Some(name.to_string()),
+            schema_types: vec!["object".to_string()],
+            properties: vec![(property_name.to_string(), Schema::default())],
+            required: vec![property_name.to_string()],
+            ..Schema::default()
+        }
+    }
+
+    fn create_pet_endpoint() -> EndpointConfiguration {
+        let pet_schema = Schema {
+            schema_types: vec!["object".to_string()],
+            properties: vec![
+                ("name".to_string(), Schema::default()),
+                ("nickname".to_string(), Schema::default()),
+                ("tags".to_string(), Schema {
+                    items: Some(Box::new(Schema {
+                        properties: vec![("label".to_string(), Schema::default())],
+                        ..Schema::default()
+                    })),
+                    ..Schema::default()
+                }),
+            ],
+            required: vec!["name".to_string()],
+            one_of: vec![create_named_schema("Cat", "indoor"), create_named_schema("Dog", "breed")],
+            discriminator: Some(Discriminator {
+                property_name: "kind".to_string(),
+                mapping: vec![("cat".to_string(), "Cat".to_string()), ("dog".to_string(), "Dog".to_string())],
+            }),
+            ..Schema::default()
+        };
+        EndpointConfiguration::new(Method::POST, "/pets", 201, Arc::new(create_mock_runtime()), false)
+            .unwrap()
+            .with_request_body(Some(Arc::new(pet_schema)))
+    }
+
+    fn create_observation(body: &str) -> Observation {
+        Observation::new(Method::POST, "/pets", 201, Arc::new(create_mock_runtime()))
+            .unwrap()
+            .with_request_body(body.as_bytes())
+    }
+
+    fn create_item(name: &str, is_exercised: bool) -> BodyCoverageItem {
+        BodyCoverageItem {
+            name: name.to_string(),
+            is_exercised,
+        }
+    }
+
+    #[test]
+    fn reports_exercised_optional_properties() {
+        let openapi_endpoints = vec![create_pet_endpoint()];
+        let observations = vec![create_observation(r#"{"name": "Rex", "tags": [{"label": "good"}], "kind": "dog", "breed": "pug"}"#)];
+
+        let body_coverages = evaluate_request_bodies(&openapi_endpoints, &observations, &HashSet::new(), &PathMatching::MostSpecific);
+
+        assert_eq!(body_coverages[0].observed_bodies, 1);
+        assert_eq!(
+            body_coverages[0].optional_properties,
+            vec![create_item("$.nickname", false), create_item("$.tags", true), create_item("$.tags[].label", true)]
+        );
+    }
+
+    #[test]
+    fn reports_branches_and_discriminator_values() {
+        let openapi_endpoints = vec![create_pet_endpoint()];
+        let observations = vec![create_observation(r#"{"name": "Rex", "kind": "dog", "breed": "pug"}"#)];
+
+        let body_coverages = evaluate_request_bodies(&openapi_endpoints, &observations, &HashSet::new(), &PathMatching::MostSpecific);
+
+        assert_eq!(body_coverages[0].branches, vec![create_item("$ oneOf Cat", false), create_item("$ oneOf Dog", true)]);
+        assert_eq!(
+            body_coverages[0].discriminator_values,
+            vec![create_item("$.kind=cat", false), create_item("$.kind=dog", true)]
+        );
+        assert_eq!(body_coverages[0].unexercised_items().len(), 5);
+    }
+
+    #[test]
+    fn matches_branches_without_discriminator() {
+        let mut endpoint = create_pet_endpoint();
+        let mut schema = endpoint.request_body.unwrap().as_ref().clone();
+        schema.discriminator = None;
+        endpoint.request_body = Some(Arc::new(schema));
+        let openapi_endpoints = vec![endpoint];
+        let observations = vec![create_observation(r#"{"name": "Tom", "indoor": true}"#), create_observation("not json")];
+
+        let body_coverages = evaluate_request_bodies(&openapi_endpoints, &observations, &HashSet::new(), &PathMatching::MostSpecific);
+
+        assert_eq!(body_coverages[0].observed_bodies, 1);
+        assert_eq!(body_coverages[0].branches, vec![create_item("$ oneOf Cat", true), create_item("$ oneOf Dog", false)]);
+        assert!(body_coverages[0].discriminator_values.is_empty());
+    }
+
+    #[test]
+    fn skips_operations_without_request_body() {
+        let openapi_endpoints =
+            vec![EndpointConfiguration::new(Method::GET, "/pets", 200, Arc::new(create_mock_runtime()), false).unwrap()];
+
+        assert!(evaluate_request_bodies(&openapi_endpoints, &[], &HashSet::new(), &PathMatching::MostSpecific).is_empty());
+    }
+}
@@ -1,23 +1,58 @@
 use std::{
     cell::RefCell,
     collections::{HashMap, HashSet},
+    rc::Rc,
 };
 
-use crate::models::{EndpointConfiguration, Grouping};
+use float_eq::float_eq;
+
+use crate::{
+    config::{DefaultResponsePolicy, PathMatching},
+    models::{EndpointConfiguration, Grouping, Hits, Observation, StatusMatcher},
+};
+
+use super::routes::RouteIndex;
+
+// an endpoint of the spec with the information if it has been covered, shared between groupings
+type CoveredEndpoint<'a> = Rc<RefCell<(&'a EndpointConfiguration, bool)>>;
 
 pub fn evaluate<'a>(
     openapi_endpoints: &'a Vec<EndpointConfiguration>,
     pre_merge_endpoints: &Option<Vec<EndpointConfiguration>>,
-    nginx_endpoints: &Vec<EndpointConfiguration>,
+    observations: &[Observation],
     groupings: &HashSet<Grouping>,
+    path_matching: &PathMatching,
 ) -> Evaluation<'a> {
-    let mut grouping_endpoints: HashMap<&Grouping, Vec<RefCell<(&EndpointConfiguration, bool)>>> =
+    let mut grouping_endpoints: HashMap<&Grouping, Vec<CoveredEndpoint>> =
         HashMap::new();
     for grouping in groupings {
         grouping_endpoints.insert(grouping, vec![]);
     }
 
-    let mut unmatched_endpoints: Vec<RefCell<(&EndpointConfiguration, bool)>> = vec![];
+    // observations only differing in their query or bodies share the same endpoint
+    let mut observed_hits: HashMap<&EndpointConfiguration, Hits> = HashMap::new();
+    for observation in observations {
+        observed_hits
+            .entry(&observation.endpoint)
+            .and_modify(|hits| hits.add(&observation.hits))
+            .or_insert(observation.hits);
+    }
+
+    // each distinct request is looked up once, it only hits the endpoints it is attributed to
+    let route_index = RouteIndex::new(openapi_endpoints, path_matching);
+    let mut endpoint_hits: HashMap<&EndpointConfiguration, usize> = HashMap::new();
+    let mut undocumented_hits = vec![];
+    for (observed_endpoint, hits) in observed_hits {
+        let endpoints = route_index.find_endpoints(observed_endpoint);
+        if endpoints.is_empty() {
+            undocumented_hits.push((observed_endpoint, hits));
+        }
+        for endpoint in endpoints {
+            *endpoint_hits.entry(endpoint).or_insert(0) += hits.count;
+        }
+    }
+
+    let mut unmatched_endpoints: Vec<CoveredEndpoint> = vec![];
     let relevant_endpoints = get_endpoints_for_diff(pre_merge_endpoints, openapi_endpoints);
 
     for openapi_endpoint in &relevant_endpoints {
@@ -26,16 +61,16 @@ pub fn evaluate<'a>(
             if grouping.0.incompases_endpoint_config(openapi_endpoint) {
                 has_group = true;
                 if grouping.1.len() >= 1 && grouping.1[0].borrow().1 {
-                    grouping.1.push(RefCell::new((openapi_endpoint, true)));
+                    grouping.1.push(Rc::new(RefCell::new((openapi_endpoint, true))));
                 } else {
                     if grouping.0.is_ignore_group {
-                        grouping.1.push(RefCell::new((openapi_endpoint, true)));
-                    } else if endpoint_incompases_any(openapi_endpoint, nginx_endpoints) {
+                        grouping.1.push(Rc::new(RefCell::new((openapi_endpoint, true))));
+                    } else if endpoint_hits.contains_key(openapi_endpoint) {
                         for endpoint in grouping.1.iter_mut() {
                             let mut endpoint = endpoint.borrow_mut();
                             endpoint.1 = true;
                         }
-                        grouping.1.push(RefCell::new((openapi_endpoint, true)));
+                        grouping.1.push(Rc::new(RefCell::new((openapi_endpoint, true))));
                     } else {
                         add_endpoint_as_missed(
                             openapi_endpoint,
@@ -48,18 +83,14 @@ pub fn evaluate<'a>(
         }
 
         if !has_group {
-            if !endpoint_incompases_any(openapi_endpoint, nginx_endpoints) {
-                unmatched_endpoints.push(RefCell::new((openapi_endpoint, false)))
+            if !endpoint_hits.contains_key(openapi_endpoint) {
+                unmatched_endpoints.push(Rc::new(RefCell::new((openapi_endpoint, false))))
             }
         }
     }
 
     // filter for met endpoints
-    unmatched_endpoints = unmatched_endpoints
-        .iter()
-        .map(|x| x.clone())
-        .filter(|x| !x.borrow().1)
-        .collect();
+    unmatched_endpoints.retain(|x| !x.borrow().1);
 
     let test_coverage = if relevant_endpoints.len() == 0 {
         1.0
@@ -68,73 +99,157 @@ pub fn evaluate<'a>(
             / relevant_endpoints.len() as f32
     };
 
-    let has_gateway_issues = has_gateway_issues(nginx_endpoints);
+    let observed_requests = observations.iter().map(|observation| observation.hits.count).sum();
+    let has_gateway_issues = has_gateway_issues(observations, observed_requests);
 
-    let endpoints_not_covered = unmatched_endpoints.iter().map(|x| x.borrow().0).collect();
+    let endpoints_not_covered: Vec<&EndpointConfiguration> = unmatched_endpoints.iter().map(|x| x.borrow().0).collect();
+    let not_covered_lookup: HashSet<&EndpointConfiguration> = endpoints_not_covered.iter().copied().collect();
+    let endpoints_covered = relevant_endpoints
+        .iter()
+        .filter(|x| !not_covered_lookup.contains(*x))
+        .copied()
+        .collect();
+
+    let endpoints_missing_in_spec = find_endpoints_missing_in_spec(undocumented_hits, groupings);
 
     Evaluation {
         has_gateway_issues,
         test_coverage,
+        endpoints_covered,
         endpoints_not_covered,
+        endpoints_missing_in_spec,
+        endpoint_hits,
+        observed_requests,
     }
 }
 
-fn endpoint_incompases_any(
-    endpoint: &EndpointConfiguration,
-    possibly_incompased_endpoints: &Vec<EndpointConfiguration>,
-) -> bool {
-    // possible optimisation: remove incompased endpoint configuration from list after finding it
-    for possible_endpoint in possibly_incompased_endpoints {
-        if endpoint.incompases_endpoint(possible_endpoint) {
-            return true;
-        }
-    }
-    false
-}
-
+// the endpoints keep the order of the spec, so the evaluation doesn't depend on the iteration order of a hash set
 fn get_endpoints_for_diff<'a>(
     pre_merge_endpoints: &Option<Vec<EndpointConfiguration>>,
-    post_merge_endpoints: &'a Vec<EndpointConfiguration>,
-) -> HashSet<&'a EndpointConfiguration> {
-    let mut relevant_endpoints = HashSet::new();
-    for post_endpoint in post_merge_endpoints {
-        relevant_endpoints.insert(post_endpoint);
-    }
-    match pre_merge_endpoints {
-        Some(pre_merge_endpoints) => {
-            for pre_endpoint in pre_merge_endpoints {
-                relevant_endpoints.take(pre_endpoint);
-            }
-        }
-        _ => (),
-    }
-    relevant_endpoints
+    post_merge_endpoints: &'a [EndpointConfiguration],
+) -> Vec<&'a EndpointConfiguration> {
+    let mut known_endpoints: HashSet<&EndpointConfiguration> = pre_merge_endpoints.iter().flatten().collect();
+    post_merge_endpoints
+        .iter()
+        .filter(|post_endpoint| known_endpoints.insert(post_endpoint))
+        .collect()
 }
 
 fn add_endpoint_as_missed<'a>(
     endpoint: &'a EndpointConfiguration,
-    grouping_endpoints: &mut Vec<RefCell<(&'a EndpointConfiguration, bool)>>,
-    unmatched_endpoints: &mut Vec<RefCell<(&'a EndpointConfiguration, bool)>>,
+    grouping_endpoints: &mut Vec<CoveredEndpoint<'a>>,
+    unmatched_endpoints: &mut Vec<CoveredEndpoint<'a>>,
 ) {
-    let endpoint = RefCell::new((endpoint, false));
+    // both lists share the endpoint, so covering the group later also covers the endpoint
+    let endpoint = Rc::new(RefCell::new((endpoint, false)));
     grouping_endpoints.push(endpoint.clone());
-    unmatched_endpoints.push(endpoint.clone());
+    unmatched_endpoints.push(endpoint);
 }
 
-fn has_gateway_issues(nginx_endpoints: &Vec<EndpointConfiguration>) -> bool {
-    let gateway_issues = nginx_endpoints
+fn find_endpoints_missing_in_spec(
+    undocumented_hits: Vec<(&EndpointConfiguration, Hits)>,
+    groupings: &HashSet<Grouping>,
+) -> Vec<UndocumentedEndpoint> {
+    let mut endpoints_missing_in_spec: Vec<UndocumentedEndpoint> = undocumented_hits
+        .into_iter()
+        .filter(|(nginx_endpoint, _)| {
+            !groupings.iter().any(|grouping| grouping.is_ignore_group && grouping.incompases_endpoint_config(nginx_endpoint))
+        })
+        .map(|(endpoint, hits)| UndocumentedEndpoint {
+            endpoint: endpoint.clone(),
+            hits: hits.count,
+            first_seen: hits.first_seen,
+            last_seen: hits.last_seen,
+        })
+        .collect();
+
+    endpoints_missing_in_spec.sort_by(|a, b| {
+        b.hits
+            .cmp(&a.hits)
+            .then_with(|| a.endpoint.runtime.port.cmp(&b.endpoint.runtime.port))
+            .then_with(|| a.endpoint.path.to_string().cmp(&b.endpoint.path.to_string()))
+            .then_with(|| a.endpoint.method.cmp(&b.endpoint.method))
+            .then_with(|| a.endpoint.status_code.cmp(&b.endpoint.status_code))
+    });
+    endpoints_missing_in_spec
+}
+
+fn has_gateway_issues(observations: &[Observation], observed_requests: usize) -> bool {
+    let gateway_issues: usize = observations
         .iter()
-        .filter(|x| x.status_code == 502)
-        .count();
-    gateway_issues > 40 || gateway_issues > nginx_endpoints.len() / 4
+        .filter(|x| x.endpoint.status_code == 502)
+        .map(|x| x.hits.count)
+        .sum();
+    gateway_issues > 40 || gateway_issues > observed_requests / 4
+}
+
+// ignoring default responses removes them from the spec, otherwise they are covered by undocumented status codes
+pub fn apply_default_response_policy(
+    openapi_endpoints: Vec<EndpointConfiguration>,
+    default_response_policy: &DefaultResponsePolicy,
+) -> Vec<EndpointConfiguration> {
+    match default_response_policy {
+        DefaultResponsePolicy::Ignore => openapi_endpoints
+            .into_iter()
+            .filter(|endpoint| endpoint.status_code != StatusMatcher::Default)
+            .collect(),
+        DefaultResponsePolicy::MatchUndocumented => openapi_endpoints,
+    }
+}
+
+// generated endpoints for unauthorized and forbidden requests only count if configured, documented ones are always kept
+pub fn apply_security_policy(
+    openapi_endpoints: Vec<EndpointConfiguration>,
+    accounts_for_unautorized: bool,
+    accounts_for_forbidden: bool,
+) -> Vec<EndpointConfiguration> {
+    openapi_endpoints
+        .into_iter()
+        .filter(|endpoint| {
+            !endpoint.is_generated
+                || (endpoint.status_code == 401 && accounts_for_unautorized)
+                || (endpoint.status_code == 403 && accounts_for_forbidden)
+        })
+        .collect()
 }
 
 pub struct Evaluation<'a> {
     pub has_gateway_issues: bool,
     pub test_coverage: f32,
+    pub endpoints_covered: Vec<&'a EndpointConfiguration>,
     pub endpoints_not_covered: Vec<&'a EndpointConfiguration>,
-    // TODO add the following field
-    //endpoints_missing_in_spec: Vec<EndpointConfiguration>,
+    pub endpoints_missing_in_spec: Vec<UndocumentedEndpoint>,
+    pub endpoint_hits: HashMap<&'a EndpointConfiguration, usize>,
+    pub observed_requests: usize,
+}
+
+pub struct UndocumentedEndpoint {
+    pub endpoint: EndpointConfiguration,
+    pub hits: usize,
+    pub first_seen: Option<u64>,
+    pub last_seen: Option<u64>,
+}
+
+impl Evaluation<'_> {
+    pub fn meets_test_coverage(&self, required_test_coverage: f32) -> bool {
+        self.test_coverage > required_test_coverage
+            || float_eq!(self.test_coverage, required_test_coverage, abs <= 0.0001)
+    }
+
+    pub fn hits(&self, endpoint: &EndpointConfiguration) -> usize {
+        self.endpoint_hits.get(endpoint).copied().unwrap_or(0)
+    }
+
+    pub fn undocumented_requests(&self) -> usize {
+        self.endpoints_missing_in_spec.iter().map(|x| x.hits).sum()
+    }
+
+    pub fn exceeds_undocumented_limit(&self, undocumented_limit: Option<usize>) -> bool {
+        match undocumented_limit {
+            Some(undocumented_limit) => self.undocumented_requests() > undocumented_limit,
+            None => false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -144,11 +259,12 @@ mod tests {
     use float_eq::assert_float_eq;
 
     use crate::{
-        models::{EndpointConfiguration, Grouping, Method, OpenapiPath},
-        utils::test::create_mock_runtime,
+        config::{DefaultResponsePolicy, PathMatching},
+        models::{EndpointConfiguration, Grouping, Method, OpenapiPath, Parameter, ParameterLocation, Schema, StatusMatcher},
+        utils::test::{create_mock_runtime, create_observations},
     };
 
-    use super::{endpoint_incompases_any, evaluate, has_gateway_issues};
+    use super::{apply_default_response_policy, apply_security_policy, evaluate, get_endpoints_for_diff, has_gateway_issues};
 
     fn create_endpoint_a() -> EndpointConfiguration {
         EndpointConfiguration::new(
@@ -192,9 +308,11 @@ mod tests {
         ];
         let nginx_endpoints = vec![create_endpoint_a(), create_endpoint_b()];
 
-        let evaluation = evaluate(&openapi_endpoints, &None, &nginx_endpoints, &HashSet::new());
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&nginx_endpoints), &HashSet::new(), &PathMatching::MostSpecific);
 
         assert_float_eq!(evaluation.test_coverage, 2.0 / 3.0, abs <= 0.001);
+        assert_eq!(evaluation.endpoints_covered.len(), 2);
+        assert_eq!(evaluation.endpoints_not_covered, vec![&openapi_endpoints[2]]);
     }
 
     #[test]
@@ -202,7 +320,7 @@ mod tests {
         let openapi_endpoints = vec![];
         let nginx_endpoints = vec![];
 
-        let evaluation = evaluate(&openapi_endpoints, &None, &nginx_endpoints, &HashSet::new());
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&nginx_endpoints), &HashSet::new(), &PathMatching::MostSpecific);
         assert_float_eq!(evaluation.test_coverage, 1.0, abs <= 0.001);
     }
 
@@ -211,7 +329,7 @@ mod tests {
         let openapi_endpoints = vec![create_endpoint_a()];
         let nginx_endpoints = vec![];
 
-        let evaluation = evaluate(&openapi_endpoints, &None, &nginx_endpoints, &HashSet::new());
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&nginx_endpoints), &HashSet::new(), &PathMatching::MostSpecific);
         assert_float_eq!(evaluation.test_coverage, 0.0, abs <= 0.001);
     }
 
@@ -232,39 +350,105 @@ mod tests {
         let mut groupings = HashSet::new();
         groupings.insert(grouping);
 
-        let evaluation = evaluate(&openapi_endpoints, &None, &nginx_endpoints, &groupings);
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&nginx_endpoints), &groupings, &PathMatching::MostSpecific);
+
+        assert_float_eq!(evaluation.test_coverage, 1.0, abs <= 0.001);
+    }
+
+    #[test]
+    fn coverage_below_threshold_is_not_met() {
+        let openapi_endpoints = vec![create_endpoint_a(), create_endpoint_b()];
+        let nginx_endpoints = vec![create_endpoint_a()];
+
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&nginx_endpoints), &HashSet::new(), &PathMatching::MostSpecific);
+        assert!(!evaluation.meets_test_coverage(0.7));
+        assert!(evaluation.meets_test_coverage(0.5));
+    }
+
+    #[test]
+    fn full_coverage_meets_full_threshold() {
+        let openapi_endpoints = vec![create_endpoint_a(), create_endpoint_b(), create_endpoint_c()];
+        let nginx_endpoints = vec![create_endpoint_a(), create_endpoint_b(), create_endpoint_c()];
 
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&nginx_endpoints), &HashSet::new(), &PathMatching::MostSpecific);
+        assert!(evaluation.meets_test_coverage(1.0));
+    }
+
+    #[test]
+    fn evaluate_groups_endpoints_regardless_of_order() {
+        // the uncovered endpoint of the group is evaluated before the covered one
+        let openapi_endpoints = vec![create_endpoint_c(), create_endpoint_a()];
+        let nginx_endpoints = vec![create_endpoint_a()];
+        let mut groupings = HashSet::new();
+        groupings.insert(Grouping::new(
+            vec![Method::GET, Method::POST],
+            vec![200],
+            OpenapiPath::from_str("/{foo}").unwrap(),
+            false,
+        ));
+
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&nginx_endpoints), &groupings, &PathMatching::MostSpecific);
+
+        assert!(evaluation.endpoints_not_covered.is_empty());
         assert_float_eq!(evaluation.test_coverage, 1.0, abs <= 0.001);
     }
 
     #[test]
-    fn internal_incompases_all_check_matches_base_case() {
-        let endpoint = create_endpoint_a();
-        let possibly_incompased_endpoints = vec![create_endpoint_a()];
-        assert!(endpoint_incompases_any(
-            &endpoint,
-            &possibly_incompased_endpoints
-        ))
+    fn lists_requests_missing_in_spec_with_hits() {
+        let openapi_endpoints = vec![create_endpoint_a()];
+        let nginx_endpoints = vec![
+            create_endpoint_a(),
+            create_endpoint_b(),
+            create_endpoint_b(),
+            create_endpoint_c(),
+        ];
+
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&nginx_endpoints), &HashSet::new(), &PathMatching::MostSpecific);
+
+        assert_eq!(evaluation.endpoints_missing_in_spec.len(), 2);
+        assert_eq!(evaluation.endpoints_missing_in_spec[0].endpoint, create_endpoint_b());
+        assert_eq!(evaluation.endpoints_missing_in_spec[0].hits, 2);
+        assert_eq!(evaluation.endpoints_missing_in_spec[1].endpoint, create_endpoint_c());
+        assert_eq!(evaluation.endpoints_missing_in_spec[1].hits, 1);
+        assert_eq!(evaluation.undocumented_requests(), 3);
+    }
+
+    #[test]
+    fn undocumented_status_codes_are_missing_in_spec() {
+        let openapi_endpoints = vec![create_endpoint_a()];
+        let nginx_endpoints = vec![EndpointConfiguration::new(
+            Method::GET,
+            "/a",
+            500,
+            Arc::new(create_mock_runtime()),
+            false,
+        )
+        .unwrap()];
+
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&nginx_endpoints), &HashSet::new(), &PathMatching::MostSpecific);
+        assert_eq!(evaluation.endpoints_missing_in_spec.len(), 1);
     }
 
     #[test]
-    fn internal_incompases_all_check_functions_for_sized_arrays() {
-        let endpoint = create_endpoint_c();
-        let possibly_incompased_endpoints = vec![create_endpoint_a(), create_endpoint_b()];
-        assert!(!endpoint_incompases_any(
-            &endpoint,
-            &possibly_incompased_endpoints
-        ))
+    fn ignored_requests_are_not_missing_in_spec() {
+        let nginx_endpoints = vec![create_endpoint_b()];
+        let mut groupings = HashSet::new();
+        groupings.insert(Grouping::new(vec![Method::GET], vec![200], OpenapiPath::from_str("/b").unwrap(), true));
+
+        let openapi_endpoints = vec![];
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&nginx_endpoints), &groupings, &PathMatching::MostSpecific);
+        assert!(evaluation.endpoints_missing_in_spec.is_empty());
     }
 
     #[test]
-    fn internal_incompases_all_check_returns_false_for_empty_possibilities() {
-        let endpoint = create_endpoint_a();
-        let possibly_incompased_endpoints = vec![];
-        assert!(!endpoint_incompases_any(
-            &endpoint,
-            &possibly_incompased_endpoints
-        ))
+    fn detects_exceeded_undocumented_limit() {
+        let nginx_endpoints = vec![create_endpoint_a(), create_endpoint_b()];
+
+        let openapi_endpoints = vec![];
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&nginx_endpoints), &HashSet::new(), &PathMatching::MostSpecific);
+        assert!(!evaluation.exceeds_undocumented_limit(None));
+        assert!(!evaluation.exceeds_undocumented_limit(Some(2)));
+        assert!(evaluation.exceeds_undocumented_limit(Some(1)));
     }
 
     #[test]
@@ -278,6 +462,129 @@ mod tests {
         )
         .unwrap()];
 
-        assert!(has_gateway_issues(&nginx_endpoints));
+        assert!(has_gateway_issues(&create_observations(&nginx_endpoints), 1));
+    }
+
+    fn create_matcher_endpoint(status_code: StatusMatcher) -> EndpointConfiguration {
+        EndpointConfiguration::new_with_status_matcher(Method::GET, "/a", status_code, Arc::new(create_mock_runtime()), false)
+            .unwrap()
+    }
+
+    fn create_observed_endpoint(status_code: u16) -> EndpointConfiguration {
+        EndpointConfiguration::new(Method::GET, "/a", status_code, Arc::new(create_mock_runtime()), false).unwrap()
+    }
+
+    #[test]
+    fn range_is_covered_by_any_status_of_its_class() {
+        let openapi_endpoints = vec![create_matcher_endpoint(StatusMatcher::Range(4))];
+        let observed_endpoints = vec![create_observed_endpoint(418)];
+
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&observed_endpoints), &HashSet::new(), &PathMatching::MostSpecific);
+
+        assert_float_eq!(evaluation.test_coverage, 1.0, abs <= 0.0001);
+        assert!(evaluation.endpoints_missing_in_spec.is_empty());
+    }
+
+    #[test]
+    fn default_is_covered_by_undocumented_status() {
+        let openapi_endpoints = vec![
+            create_observed_endpoint(200),
+            create_matcher_endpoint(StatusMatcher::Range(4)),
+            create_matcher_endpoint(StatusMatcher::Default),
+        ];
+
+        let documented_observations = vec![create_observed_endpoint(200), create_observed_endpoint(404)];
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&documented_observations), &HashSet::new(), &PathMatching::MostSpecific);
+        assert_eq!(evaluation.endpoints_not_covered.len(), 1);
+        assert_eq!(evaluation.endpoints_not_covered[0].status_code, StatusMatcher::Default);
+
+        let undocumented_observations = vec![create_observed_endpoint(500)];
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&undocumented_observations), &HashSet::new(), &PathMatching::MostSpecific);
+        assert_eq!(evaluation.endpoints_covered.len(), 1);
+        assert_eq!(evaluation.endpoints_covered[0].status_code, StatusMatcher::Default);
+        assert!(evaluation.endpoints_missing_in_spec.is_empty());
+    }
+
+    #[test]
+    fn observations_only_cover_the_most_specific_path() {
+        let runtime = Arc::new(create_mock_runtime());
+        let openapi_endpoints = vec![
+            EndpointConfiguration::new(Method::GET, "/users/{id}", 200, runtime.clone(), false).unwrap(),
+            EndpointConfiguration::new(Method::GET, "/users/{id}", 404, runtime.clone(), false).unwrap(),
+            EndpointConfiguration::new(Method::GET, "/users/me", 200, runtime.clone(), false).unwrap(),
+        ];
+        let observed_endpoints = vec![
+            EndpointConfiguration::new(Method::GET, "/users/me", 200, runtime.clone(), false).unwrap(),
+            EndpointConfiguration::new(Method::GET, "/users/me", 404, runtime, false).unwrap(),
+        ];
+
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&observed_endpoints), &HashSet::new(), &PathMatching::MostSpecific);
+        assert_eq!(evaluation.endpoints_covered, vec![&openapi_endpoints[2]]);
+        assert_eq!(evaluation.hits(&openapi_endpoints[0]), 0);
+        assert_eq!(evaluation.hits(&openapi_endpoints[2]), 1);
+        assert_eq!(evaluation.endpoints_missing_in_spec.len(), 1);
+        assert_eq!(evaluation.endpoints_missing_in_spec[0].endpoint, observed_endpoints[1]);
+
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&observed_endpoints), &HashSet::new(), &PathMatching::All);
+        assert_eq!(evaluation.endpoints_covered.len(), 3);
+        assert!(evaluation.endpoints_missing_in_spec.is_empty());
+    }
+
+    #[test]
+    fn requests_under_any_server_cover_the_endpoint() {
+        let runtime = Arc::new(create_mock_runtime());
+        let openapi_endpoints = vec![EndpointConfiguration::new(Method::GET, "/v1/weather", 200, runtime.clone(), false)
+            .unwrap()
+            .with_alternative_paths(&["/v2/weather".to_string()])
+            .unwrap()];
+        let observed_endpoints = vec![EndpointConfiguration::new(Method::GET, "/v2/weather", 200, runtime, false).unwrap()];
+
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&observed_endpoints), &HashSet::new(), &PathMatching::MostSpecific);
+        assert_float_eq!(evaluation.test_coverage, 1.0, abs <= 0.001);
+        assert!(evaluation.endpoints_missing_in_spec.is_empty());
+    }
+
+    #[test]
+    fn ignore_policy_removes_default_responses() {
+        let openapi_endpoints = vec![create_observed_endpoint(200), create_matcher_endpoint(StatusMatcher::Default)];
+
+        assert_eq!(apply_default_response_policy(openapi_endpoints.clone(), &DefaultResponsePolicy::Ignore).len(), 1);
+        assert_eq!(apply_default_response_policy(openapi_endpoints, &DefaultResponsePolicy::MatchUndocumented).len(), 2);
+    }
+
+    #[test]
+    fn security_policy_only_removes_generated_endpoints() {
+        let runtime = Arc::new(create_mock_runtime());
+        let openapi_endpoints = vec![
+            EndpointConfiguration::new(Method::GET, "/foo", 401, runtime.clone(), true).unwrap(),
+            EndpointConfiguration::new(Method::GET, "/foo", 403, runtime.clone(), true).unwrap(),
+            EndpointConfiguration::new(Method::POST, "/foo", 401, runtime, false).unwrap(),
+        ];
+
+        let endpoints = apply_security_policy(openapi_endpoints.clone(), false, false);
+        assert_eq!(endpoints.len(), 1);
+        assert!(!endpoints[0].is_generated);
+
+        let endpoints = apply_security_policy(openapi_endpoints.clone(), true, false);
+        assert!(endpoints.iter().any(|x| x.is_generated && x.status_code == 401));
+        assert!(!endpoints.iter().any(|x| x.status_code == 403));
+
+        assert_eq!(apply_security_policy(openapi_endpoints, true, true).len(), 3);
+    }
+
+    #[test]
+    fn changed_details_dont_make_endpoints_new_in_merge() {
+        let pre_merge_endpoints = Some(vec![create_endpoint_a(), create_endpoint_b()]);
+        let openapi_endpoints = vec![
+            create_endpoint_a()
+                .with_parameters(vec![Parameter::new("page", ParameterLocation::Query, false)])
+                .with_request_body(Some(Arc::new(Schema::default()))),
+            create_endpoint_b().with_request_media_types(vec!["text/csv".to_string()]),
+            create_endpoint_c(),
+        ];
+
+        let relevant_endpoints = get_endpoints_for_diff(&pre_merge_endpoints, &openapi_endpoints);
+
+        assert_eq!(relevant_endpoints, vec![&openapi_endpoints[2]]);
     }
 }
@@ -0,0 +1,218 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    config::PathMatching,
+    models::{EndpointConfiguration, Grouping, Observation},
+};
+
+use super::{parameters::is_ignored, routes::RouteIndex};
+
+// responses of an endpoint which were checked against the spec, with everything that didn't conform
+pub struct ResponseConformance<'a> {
+    pub endpoint: &'a EndpointConfiguration,
+    pub validated_responses: usize,
+    pub violations: Vec<ResponseViolation>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ResponseViolation {
+    pub description: String,
+    pub occurrences: usize,
+}
+
+pub fn evaluate_response_conformance<'a>(
+    openapi_endpoints: &'a [EndpointConfiguration],
+    observations: &[Observation],
+    groupings: &HashSet<Grouping>,
+    path_matching: &PathMatching,
+) -> Vec<ResponseConformance<'a>> {
+    let mut conformances: Vec<ResponseConformance> = vec![];
+    let mut indices: HashMap<&EndpointConfiguration, usize> = HashMap::new();
+    let route_index = RouteIndex::new(openapi_endpoints, path_matching);
+    for observation in observations {
+        let endpoint = match find_response_endpoint(&route_index, observation, groupings) {
+            Some(endpoint) => endpoint,
+            None => continue,
+        };
+
+        let index = *indices.entry(endpoint).or_insert_with(|| {
+            conformances.push(ResponseConformance {
+                endpoint,
+                validated_responses: 0,
+                violations: vec![],
+            });
+            conformances.len() - 1
+        });
+        let conformance = &mut conformances[index];
+        conformance.validated_responses += observation.hits.count;
+        for (description, occurrences) in validate_responses(endpoint, observation) {
+            match conformance.violations.iter_mut().find(|violation| violation.description == description) {
+                Some(violation) => violation.occurrences += occurrences,
+                None => conformance.violations.push(ResponseViolation { description, occurrences }),
+            }
+        }
+    }
+    conformances
+}
+
+impl ResponseConformance<'_> {
+    pub fn conforms(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+// the most specific response describes the observation, exact status codes before ranges and default responses,
+// responses without declared content have nothing to check
+pub(super) fn find_response_endpoint<'a>(
+    route_index: &RouteIndex<'a>,
+    observation: &Observation,
+    groupings: &HashSet<Grouping>,
+) -> Option<&'a EndpointConfiguration> {
+    route_index
+        .find_endpoints(&observation.endpoint)
+        .into_iter()
+        .filter(|endpoint| !is_ignored(endpoint, groupings))
+        .min_by_key(|endpoint| endpoint.status_code)
+        .filter(|endpoint| !endpoint.response_media_types.is_empty())
+}
+
+// the violations with the number of responses they occurred in, responses without a content type can't be checked,
+// bodies are only validated if they were recorded and are among the kept variants
+fn validate_responses(endpoint: &EndpointConfiguration, observation: &Observation) -> Vec<(String, usize)> {
+    let mut violations = vec![];
+    for (content_types, count) in observation.content_types.iter() {
+        if let Some(content_type) = &content_types.response {
+            if !endpoint.response_media_types.iter().any(|media_type| media_type.matches(content_type)) {
+                violations.push((format!("undeclared content type {}", content_type), count));
+            }
+        }
+    }
+    for (response_body, count) in observation.response_bodies.iter() {
+        let media_type = endpoint.response_media_types.iter().find(|media_type| media_type.matches(&response_body.content_type));
+        if let Some(schema) = media_type.and_then(|media_type| media_type.schema.as_ref()) {
+            violations.extend(schema.validate(&response_body.body).into_iter().map(|description| (description, count)));
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Arc};
+
+    use crate::{
+        config::PathMatching,
+        models::{EndpointConfiguration, MediaType, Method, Observation, Schema, StatusMatcher},
+        utils::test::create_mock_runtime,
+    };
+
+    use super::{evaluate_response_conformance, ResponseViolation};
+
+    fn create_pet_endpoint(status_code: StatusMatcher) -> EndpointConfiguration {
+        let schema = Schema {
+            schema_types: vec!["object".to_string()],
+            properties: vec![(
+                "id".to_string(),
+                Schema {
+                    schema_types: vec!["integer".to_string()],
+                    ..Schema::default()
+                },
+            )],
+            required: vec!["id".to_string()],
+            ..Schema::default()
+        };
+        EndpointConfiguration::new_with_status_matcher(Method::GET, "/pets/{id}", status_code, Arc::new(create_mock_runtime()), false)
+            .unwrap()
+            .with_response_media_types(vec![MediaType::new("application/json", Some(Arc::new(schema)))])
+    }
+
+    fn create_observation(status_code: u16, content_type: Option<&str>, body: &str) -> Observation {
+        Observation::new(Method::GET, "/pets/1", status_code, Arc::new(create_mock_runtime()))
+            .unwrap()
+            .with_response(content_type, body.as_bytes())
+    }
+
+    #[test]
+    fn reports_violations_per_endpoint_with_occurrences() {
+        let openapi_endpoints = vec![create_pet_endpoint(StatusMatcher::Exact(200))];
+        let observations = vec![
+            create_observation(200, Some("application/json"), r#"{"id": 1}"#),
+            create_observation(200, Some("application/json"), r#"{"id": "1"}"#),
+            create_observation(200, Some("application/json"), r#"{"id": "2"}"#),
+            create_observation(200, Some("text/html"), "<html></html>"),
+            create_observation(200, None, ""),
+        ];
+
+        let conformances = evaluate_response_conformance(&openapi_endpoints, &observations, &HashSet::new(), &PathMatching::MostSpecific);
+
+        assert_eq!(conformances.len(), 1);
+        assert_eq!(conformances[0].validated_responses, 5);
+        assert!(!conformances[0].conforms());
+        assert_eq!(
+            conformances[0].violations,
+            vec![
+                ResponseViolation {
+                    description: "$.id: expected integer, found string".to_string(),
+                    occurrences: 2,
+                },
+                ResponseViolation {
+                    description: "undeclared content type text/html".to_string(),
+                    occurrences: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn weights_violations_of_aggregated_responses() {
+        let openapi_endpoints = vec![create_pet_endpoint(StatusMatcher::Exact(200))];
+        let mut observation = create_observation(200, Some("application/json"), r#"{"id": "1"}"#);
+        observation.add(create_observation(200, Some("application/json"), r#"{"id": "1"}"#));
+        observation.add(create_observation(200, Some("application/json"), r#"{"id": 2}"#));
+        observation.add(create_observation(200, Some("text/html"), "<html></html>"));
+
+        let conformances = evaluate_response_conformance(&openapi_endpoints, &[observation], &HashSet::new(), &PathMatching::MostSpecific);
+
+        assert_eq!(conformances[0].validated_responses, 4);
+        assert_eq!(
+            conformances[0].violations,
+            vec![
+                ResponseViolation {
+                    description: "undeclared content type text/html".to_string(),
+                    occurrences: 1,
+                },
+                ResponseViolation {
+                    description: "$.id: expected integer, found string".to_string(),
+                    occurrences: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_against_most_specific_response() {
+        let mut default_endpoint = create_pet_endpoint(StatusMatcher::Default);
+        default_endpoint.response_media_types = vec![MediaType::new("text/plain", None)];
+        let openapi_endpoints = vec![default_endpoint, create_pet_endpoint(StatusMatcher::Range(2))];
+        let observations = vec![
+            create_observation(201, Some("application/json"), r#"{"id": 1}"#),
+            create_observation(500, Some("text/plain"), "error"),
+        ];
+
+        let conformances = evaluate_response_conformance(&openapi_endpoints, &observations, &HashSet::new(), &PathMatching::MostSpecific);
+
+        assert_eq!(conformances.len(), 2);
+        assert_eq!(conformances[0].endpoint.status_code, StatusMatcher::Range(2));
+        assert_eq!(conformances[1].endpoint.status_code, StatusMatcher::Default);
+        assert!(conformances.iter().all(|conformance| conformance.conforms()));
+    }
+
+    #[test]
+    fn skips_responses_without_declared_content() {
+        let openapi_endpoints =
+            vec![EndpointConfiguration::new(Method::GET, "/pets/{id}", 200, Arc::new(create_mock_runtime()), false).unwrap()];
+        let observations = vec![create_observation(200, Some("text/html"), "")];
+
+        assert!(evaluate_response_conformance(&openapi_endpoints, &observations, &HashSet::new(), &PathMatching::MostSpecific).is_empty());
+    }
+}
@@ -0,0 +1,44 @@
+use std::collections::HashSet;
+
+use crate::{
+    config::PathMatching,
+    models::{EndpointConfiguration, Grouping, Observation, ParameterLocation},
+};
+
+use super::{
+    body::{evaluate_request_bodies, BodyCoverage},
+    conformance::{evaluate_response_conformance, ResponseConformance},
+    media_types::{evaluate_media_types, MediaTypeEvaluation},
+    parameters::{evaluate_enum_values, evaluate_parameters, EnumValueCoverage, ParameterEvaluation},
+};
+
+// everything evaluated from the recorded requests and responses beyond which endpoints were hit
+pub struct DetailedEvaluation<'a> {
+    pub query_parameters: ParameterEvaluation<'a>,
+    pub header_parameters: ParameterEvaluation<'a>,
+    pub enum_values: Vec<EnumValueCoverage<'a>>,
+    pub request_bodies: Vec<BodyCoverage<'a>>,
+    pub response_conformances: Vec<ResponseConformance<'a>>,
+    // only evaluated if media type coverage was opted into
+    pub media_types: Option<MediaTypeEvaluation<'a>>,
+}
+
+pub fn evaluate_details<'a>(
+    openapi_endpoints: &'a [EndpointConfiguration],
+    observations: &[Observation],
+    groupings: &HashSet<Grouping>,
+    media_type_coverage: bool,
+    path_matching: &PathMatching,
+) -> DetailedEvaluation<'a> {
+    DetailedEvaluation {
+        query_parameters: evaluate_parameters(openapi_endpoints, observations, groupings, ParameterLocation::Query, path_matching),
+        header_parameters: evaluate_parameters(openapi_endpoints, observations, groupings, ParameterLocation::Header, path_matching),
+        enum_values: evaluate_enum_values(openapi_endpoints, observations, groupings, path_matching),
+        request_bodies: evaluate_request_bodies(openapi_endpoints, observations, groupings, path_matching),
+        response_conformances: evaluate_response_conformance(openapi_endpoints, observations, groupings, path_matching),
+        media_types: match media_type_coverage {
+            true => Some(evaluate_media_types(openapi_endpoints, observations, groupings, path_matching)),
+            false => None,
+        },
+    }
+}
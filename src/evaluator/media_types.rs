@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    config::PathMatching,
+    models::{media_type_matches, ContentTypes, EndpointConfiguration, Grouping, Observation},
+};
+
+use super::{
+    conformance::find_response_endpoint,
+    parameters::{get_operations, is_ignored},
+    routes::RouteIndex,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaTypeDirection {
+    Request,
+    Response,
+}
+
+impl MediaTypeDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MediaTypeDirection::Request => "request",
+            MediaTypeDirection::Response => "response",
+        }
+    }
+}
+
+pub struct MediaTypeEvaluation<'a> {
+    pub media_type_coverage: f32,
+    pub variants: Vec<MediaTypeVariant<'a>>,
+}
+
+// a media type declared for a request body of an operation or for a response, request variants don't depend on the status code
+pub struct MediaTypeVariant<'a> {
+    pub endpoint: &'a EndpointConfiguration,
+    pub direction: MediaTypeDirection,
+    pub media_type: &'a str,
+    pub hits: usize,
+}
+
+// only bodies with more than one representation are evaluated, otherwise the endpoint coverage already tells the same
+pub fn evaluate_media_types<'a>(
+    openapi_endpoints: &'a [EndpointConfiguration],
+    observations: &[Observation],
+    groupings: &HashSet<Grouping>,
+    path_matching: &PathMatching,
+) -> MediaTypeEvaluation<'a> {
+    let mut variants = vec![];
+    let route_index = RouteIndex::new(openapi_endpoints, path_matching);
+    let observations_by_operation = route_index.group_by_operation(observations);
+
+    for operation in get_operations(openapi_endpoints, groupings) {
+        if operation.request_media_types.len() < 2 {
+            continue;
+        }
+        for media_type in &operation.request_media_types {
+            let hits = observations_by_operation
+                .get(operation)
+                .iter()
+                .flat_map(|observation| observation.content_types.iter())
+                .filter(|(content_types, _)| match &content_types.request {
+                    Some(content_type) => media_type_matches(media_type, content_type),
+                    None => false,
+                })
+                .map(|(_, count)| count)
+                .sum();
+            variants.push(MediaTypeVariant {
+                endpoint: operation,
+                direction: MediaTypeDirection::Request,
+                media_type,
+                hits,
+            });
+        }
+    }
+
+    // every observation is attributed to its response once, instead of being searched for each endpoint
+    let mut responses: HashMap<&EndpointConfiguration, Vec<&Observation>> = HashMap::new();
+    for observation in observations {
+        if let Some(endpoint) = find_response_endpoint(&route_index, observation, groupings) {
+            responses.entry(endpoint).or_default().push(observation);
+        }
+    }
+
+    let response_endpoints = openapi_endpoints
+        .iter()
+        .filter(|endpoint| endpoint.response_media_types.len() > 1 && !is_ignored(endpoint, groupings));
+    for endpoint in response_endpoints {
+        for media_type in &endpoint.response_media_types {
+            let hits = responses
+                .get(endpoint)
+                .into_iter()
+                .flatten()
+                .flat_map(|observation| observation.content_types.iter())
+                .filter(|(content_types, _)| returned_media_type(content_types, &media_type.name))
+                .map(|(_, count)| count)
+                .sum();
+            variants.push(MediaTypeVariant {
+                endpoint,
+                direction: MediaTypeDirection::Response,
+                media_type: &media_type.name,
+                hits,
+            });
+        }
+    }
+
+    let covered_variants = variants.iter().filter(|variant| variant.hits > 0).count();
+    let media_type_coverage = if variants.is_empty() {
+        1.0
+    } else {
+        covered_variants as f32 / variants.len() as f32
+    };
+
+    MediaTypeEvaluation {
+        media_type_coverage,
+        variants,
+    }
+}
+
+impl MediaTypeEvaluation<'_> {
+    pub fn uncovered_variants(&self) -> Vec<&MediaTypeVariant<'_>> {
+        self.variants.iter().filter(|variant| variant.hits == 0).collect()
+    }
+}
+
+// responses without a content type fall back to the media types the request explicitly accepted
+fn returned_media_type(content_types: &ContentTypes, media_type: &str) -> bool {
+    match (&content_types.response, &content_types.accept) {
+        (Some(content_type), _) => media_type_matches(media_type, content_type),
+        (None, Some(accept)) => accept
+            .split(',')
+            .any(|accepted| media_type_matches(media_type, accepted)),
+        (None, None) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Arc};
+
+    use float_eq::assert_float_eq;
+
+    use crate::{
+        config::PathMatching,
+        models::{EndpointConfiguration, MediaType, Method, Observation},
+        utils::test::create_mock_runtime,
+    };
+
+    use super::{evaluate_media_types, MediaTypeDirection};
+
+    fn create_report_endpoints() -> Vec<EndpointConfiguration> {
+        let runtime = Arc::new(create_mock_runtime());
+        vec![
+            EndpointConfiguration::new(Method::POST, "/reports", 201, runtime.clone(), false)
+                .unwrap()
+                .with_request_media_types(vec!["application/json".to_string(), "text/csv".to_string()])
+                .with_response_media_types(vec![MediaType::new("application/json", None)]),
+            EndpointConfiguration::new(Method::GET, "/reports/{id}", 200, runtime, false)
+                .unwrap()
+                .with_response_media_types(vec![
+                    MediaType::new("application/json", None),
+                    MediaType::new("text/csv", None),
+                    MediaType::new("application/xml", None),
+                ]),
+        ]
+    }
+
+    fn create_observation(method: Method, request_target: &str, status_code: u16) -> Observation {
+        Observation::new(method, request_target, status_code, Arc::new(create_mock_runtime())).unwrap()
+    }
+
+    #[test]
+    fn counts_hits_per_declared_media_type() {
+        let openapi_endpoints = create_report_endpoints();
+        let observations = vec![
+            create_observation(Method::POST, "/reports", 201).with_request_headers(Some("text/csv; charset=utf-8"), None),
+            create_observation(Method::GET, "/reports/1", 200).with_response(Some("application/json"), b"{}"),
+            create_observation(Method::GET, "/reports/2", 200).with_response(Some("application/json"), b"{}"),
+            create_observation(Method::GET, "/reports/3", 200).with_request_headers(None, Some("text/csv, */*;q=0.1")),
+        ];
+
+        let evaluation = evaluate_media_types(&openapi_endpoints, &observations, &HashSet::new(), &PathMatching::MostSpecific);
+
+        let hits: Vec<(MediaTypeDirection, &str, usize)> =
+            evaluation.variants.iter().map(|x| (x.direction, x.media_type, x.hits)).collect();
+        assert_eq!(
+            hits,
+            vec![
+                (MediaTypeDirection::Request, "application/json", 0),
+                (MediaTypeDirection::Request, "text/csv", 1),
+                (MediaTypeDirection::Response, "application/json", 2),
+                (MediaTypeDirection::Response, "text/csv", 1),
+                (MediaTypeDirection::Response, "application/xml", 0),
+            ]
+        );
+        assert_float_eq!(evaluation.media_type_coverage, 0.6, abs <= 0.0001);
+        assert_eq!(evaluation.uncovered_variants().len(), 2);
+    }
+
+    #[test]
+    fn full_coverage_without_multiple_representations() {
+        let openapi_endpoints = vec![create_report_endpoints().remove(0).with_request_media_types(vec![])];
+
+        let evaluation = evaluate_media_types(&openapi_endpoints, &[], &HashSet::new(), &PathMatching::MostSpecific);
+
+        assert!(evaluation.variants.is_empty());
+        assert_float_eq!(evaluation.media_type_coverage, 1.0, abs <= 0.0001);
+    }
+}
@@ -1,3 +1,19 @@
+mod body;
 mod compare;
+mod conformance;
+mod details;
+mod media_types;
+mod parameters;
+mod routes;
 pub use compare::Evaluation;
 pub use compare::evaluate;
+pub use compare::apply_default_response_policy;
+pub use compare::apply_security_policy;
+pub use body::{evaluate_request_bodies, BodyCoverage, BodyCoverageItem};
+pub use conformance::{evaluate_response_conformance, ResponseConformance, ResponseViolation};
+pub use details::{evaluate_details, DetailedEvaluation};
+pub use media_types::{evaluate_media_types, MediaTypeDirection, MediaTypeEvaluation, MediaTypeVariant};
+pub use routes::{OperationObservations, RouteIndex};
+pub use parameters::{
+    evaluate_enum_values, evaluate_parameters, EnumValueCoverage, OperationParameters, ParameterEvaluation, UnenforcedParameter,
+};
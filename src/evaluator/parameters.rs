@@ -0,0 +1,346 @@
+use std::collections::HashSet;
+
+use float_eq::float_eq;
+
+use crate::{
+    config::PathMatching,
+    models::{EndpointConfiguration, Grouping, Observation, Parameter, ParameterLocation, StatusMatcher},
+};
+
+use super::routes::RouteIndex;
+
+pub struct ParameterEvaluation<'a> {
+    pub parameter_coverage: f32,
+    pub operations: Vec<OperationParameters<'a>>,
+}
+
+// the declared parameters of an operation, split by whether any request ever sent them
+pub struct OperationParameters<'a> {
+    pub operation: &'a EndpointConfiguration,
+    pub sent_parameters: Vec<&'a Parameter>,
+    pub unsent_parameters: Vec<&'a Parameter>,
+    pub unenforced_parameters: Vec<UnenforcedParameter<'a>>,
+}
+
+// a required parameter which was missing in requests the service still answered successfully
+pub struct UnenforcedParameter<'a> {
+    pub parameter: &'a Parameter,
+    pub successful_requests: usize,
+}
+
+// the enum values of a path or query parameter, split by whether any request used them
+pub struct EnumValueCoverage<'a> {
+    pub operation: &'a EndpointConfiguration,
+    pub parameter: &'a Parameter,
+    pub observed_values: Vec<&'a str>,
+    pub unobserved_values: Vec<&'a str>,
+}
+
+pub fn evaluate_parameters<'a>(
+    openapi_endpoints: &'a [EndpointConfiguration],
+    observations: &[Observation],
+    groupings: &HashSet<Grouping>,
+    location: ParameterLocation,
+    path_matching: &PathMatching,
+) -> ParameterEvaluation<'a> {
+    let mut operations = vec![];
+    let observations_by_operation = RouteIndex::new(openapi_endpoints, path_matching).group_by_operation(observations);
+    for operation in get_operations(openapi_endpoints, groupings) {
+        let declared_parameters: Vec<&Parameter> = operation
+            .parameters
+            .iter()
+            .filter(|parameter| parameter.location == location)
+            .collect();
+        if declared_parameters.is_empty() {
+            continue;
+        }
+
+        let operation_observations = observations_by_operation.get(operation);
+        let unenforced_parameters = declared_parameters
+            .iter()
+            .filter(|parameter| parameter.required)
+            .map(|parameter| UnenforcedParameter {
+                parameter,
+                successful_requests: operation_observations
+                    .iter()
+                    .filter(|observation| StatusMatcher::Range(2).matches(&observation.endpoint.status_code))
+                    .map(|observation| observation.hits.count.saturating_sub(observation.count_parameter(parameter)))
+                    .sum(),
+            })
+            .filter(|unenforced_parameter| unenforced_parameter.successful_requests > 0)
+            .collect();
+        let (sent_parameters, unsent_parameters) = declared_parameters
+            .into_iter()
+            .partition(|parameter| operation_observations.iter().any(|observation| observation.has_parameter(parameter)));
+
+        operations.push(OperationParameters {
+            operation,
+            sent_parameters,
+            unsent_parameters,
+            unenforced_parameters,
+        });
+    }
+
+    let declared_parameters: usize = operations.iter().map(|x| x.sent_parameters.len() + x.unsent_parameters.len()).sum();
+    let sent_parameters: usize = operations.iter().map(|x| x.sent_parameters.len()).sum();
+    let parameter_coverage = if declared_parameters == 0 {
+        1.0
+    } else {
+        sent_parameters as f32 / declared_parameters as f32
+    };
+
+    ParameterEvaluation {
+        parameter_coverage,
+        operations,
+    }
+}
+
+pub fn evaluate_enum_values<'a>(
+    openapi_endpoints: &'a [EndpointConfiguration],
+    observations: &[Observation],
+    groupings: &HashSet<Grouping>,
+    path_matching: &PathMatching,
+) -> Vec<EnumValueCoverage<'a>> {
+    let mut enum_value_coverages = vec![];
+    let observations_by_operation = RouteIndex::new(openapi_endpoints, path_matching).group_by_operation(observations);
+    for operation in get_operations(openapi_endpoints, groupings) {
+        let enum_parameters = operation.parameters.iter().filter(|parameter| {
+            !parameter.enum_values.is_empty()
+                && (parameter.location == ParameterLocation::Path || parameter.location == ParameterLocation::Query)
+        });
+        for parameter in enum_parameters {
+            let sent_values: HashSet<String> = observations_by_operation
+                .get(operation)
+                .iter()
+                .flat_map(|observation| get_sent_values(operation, parameter, observation))
+                .collect();
+            let (observed_values, unobserved_values) = parameter
+                .enum_values
+                .iter()
+                .map(|value| value.as_str())
+                .partition(|value| sent_values.contains(*value));
+
+            enum_value_coverages.push(EnumValueCoverage {
+                operation,
+                parameter,
+                observed_values,
+                unobserved_values,
+            });
+        }
+    }
+    enum_value_coverages
+}
+
+fn get_sent_values(operation: &EndpointConfiguration, parameter: &Parameter, observation: &Observation) -> Vec<String> {
+    match parameter.location {
+        ParameterLocation::Query => observation
+            .query_parameters
+            .iter()
+            .filter(|observed_parameter| observed_parameter.name == parameter.name)
+            .flat_map(|observed_parameter| observed_parameter.values.iter().map(|(value, _)| value.clone()))
+            .collect(),
+        ParameterLocation::Path => operation
+            .match_path_variables(&observation.endpoint.path)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(name, _)| name == &parameter.name)
+            .map(|(_, value)| value)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+// every status code of an operation shares the same parameters, operations fully in ignored groupings are skipped
+pub(super) fn get_operations<'a>(
+    openapi_endpoints: &'a [EndpointConfiguration],
+    groupings: &HashSet<Grouping>,
+) -> Vec<&'a EndpointConfiguration> {
+    let mut operations: Vec<&EndpointConfiguration> = vec![];
+    for endpoint in openapi_endpoints {
+        if operations.iter().any(|operation| operation.is_same_operation(endpoint)) {
+            continue;
+        }
+        let is_operation_ignored = openapi_endpoints
+            .iter()
+            .filter(|other| other.is_same_operation(endpoint))
+            .all(|endpoint| is_ignored(endpoint, groupings));
+        if !is_operation_ignored {
+            operations.push(endpoint);
+        }
+    }
+    operations
+}
+
+pub(super) fn is_ignored(endpoint: &EndpointConfiguration, groupings: &HashSet<Grouping>) -> bool {
+    groupings
+        .iter()
+        .any(|grouping| grouping.is_ignore_group && grouping.incompases_endpoint_config(endpoint))
+}
+
+impl ParameterEvaluation<'_> {
+    pub fn meets_parameter_coverage(&self, required_parameter_coverage: Option<f32>) -> bool {
+        match required_parameter_coverage {
+            Some(required_parameter_coverage) => {
+                self.parameter_coverage > required_parameter_coverage
+                    || float_eq!(self.parameter_coverage, required_parameter_coverage, abs <= 0.0001)
+            }
+            None => true,
+        }
+    }
+
+    pub fn has_declared_parameters(&self) -> bool {
+        !self.operations.is_empty()
+    }
+}
+
+impl OperationParameters<'_> {
+    pub fn unsent_required_parameters(&self) -> Vec<&Parameter> {
+        self.unsent_parameters.iter().filter(|parameter| parameter.required).copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, str::FromStr, sync::Arc};
+
+    use float_eq::assert_float_eq;
+
+    use crate::{
+        config::PathMatching,
+        models::{EndpointConfiguration, Grouping, Method, Observation, OpenapiPath, Parameter, ParameterLocation},
+        utils::test::create_mock_runtime,
+    };
+
+    use super::{evaluate_enum_values, evaluate_parameters};
+
+    fn create_search_endpoints() -> Vec<EndpointConfiguration> {
+        let runtime = Arc::new(create_mock_runtime());
+        let parameters = vec![
+            Parameter::new("filter", ParameterLocation::Query, true),
+            Parameter::new("sort", ParameterLocation::Query, false),
+            Parameter::new("page", ParameterLocation::Query, false),
+            Parameter::new("X-Request-Id", ParameterLocation::Header, false),
+        ];
+        vec![
+            EndpointConfiguration::new(Method::GET, "/search/{index}", 200, runtime.clone(), false)
+                .unwrap()
+                .with_parameters(parameters.clone()),
+            EndpointConfiguration::new(Method::GET, "/search/{index}", 400, runtime.clone(), false)
+                .unwrap()
+                .with_parameters(parameters),
+            EndpointConfiguration::new(Method::GET, "/health", 200, runtime, false).unwrap(),
+        ]
+    }
+
+    fn create_observation(request_target: &str) -> Observation {
+        Observation::new(Method::GET, request_target, 200, Arc::new(create_mock_runtime())).unwrap()
+    }
+
+    #[test]
+    fn reports_sent_and_unsent_query_parameters_per_operation() {
+        let openapi_endpoints = create_search_endpoints();
+        let observations = vec![
+            create_observation("/search/books?sort=title"),
+            create_observation("/search/books?page=2&unknown=1"),
+            create_observation("/health?filter=x"),
+        ];
+
+        let evaluation = evaluate_parameters(&openapi_endpoints, &observations, &HashSet::new(), ParameterLocation::Query, &PathMatching::MostSpecific);
+
+        assert_eq!(evaluation.operations.len(), 1);
+        let operation = &evaluation.operations[0];
+        assert_eq!(operation.operation.path.to_string(), "/search/{index}");
+        assert_eq!(operation.sent_parameters.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(), vec!["sort", "page"]);
+        assert_eq!(operation.unsent_parameters.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(), vec!["filter"]);
+        assert_eq!(operation.unsent_required_parameters().len(), 1);
+        assert_float_eq!(evaluation.parameter_coverage, 2.0 / 3.0, abs <= 0.0001);
+    }
+
+    #[test]
+    fn reports_header_parameters_and_successful_requests_without_required_ones() {
+        let mut openapi_endpoints = create_search_endpoints();
+        openapi_endpoints[0].parameters.push(Parameter::new("X-Tenant-Id", ParameterLocation::Header, true));
+        let observations = vec![
+            create_observation("/search/books").with_request_header_values(vec![("x-request-id".to_string(), "1".to_string())]),
+            create_observation("/search/books"),
+            Observation::new(Method::GET, "/search/books", 400, Arc::new(create_mock_runtime())).unwrap(),
+        ];
+
+        let evaluation = evaluate_parameters(&openapi_endpoints, &observations, &HashSet::new(), ParameterLocation::Header, &PathMatching::MostSpecific);
+
+        let operation = &evaluation.operations[0];
+        assert_eq!(operation.sent_parameters.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(), vec!["X-Request-Id"]);
+        assert_eq!(operation.unsent_parameters.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(), vec!["X-Tenant-Id"]);
+        assert_eq!(operation.unenforced_parameters.len(), 1);
+        assert_eq!(operation.unenforced_parameters[0].parameter.name, "X-Tenant-Id");
+        assert_eq!(operation.unenforced_parameters[0].successful_requests, 2);
+        assert_float_eq!(evaluation.parameter_coverage, 0.5, abs <= 0.0001);
+    }
+
+    #[test]
+    fn full_coverage_without_declared_parameters() {
+        let openapi_endpoints = vec![create_search_endpoints().remove(2)];
+
+        let evaluation = evaluate_parameters(&openapi_endpoints, &[], &HashSet::new(), ParameterLocation::Query, &PathMatching::MostSpecific);
+
+        assert!(!evaluation.has_declared_parameters());
+        assert_float_eq!(evaluation.parameter_coverage, 1.0, abs <= 0.0001);
+        assert!(evaluation.meets_parameter_coverage(Some(1.0)));
+    }
+
+    #[test]
+    fn threshold_is_only_checked_if_configured() {
+        let openapi_endpoints = create_search_endpoints();
+        let observations = vec![create_observation("/search/books?filter=a")];
+
+        let evaluation = evaluate_parameters(&openapi_endpoints, &observations, &HashSet::new(), ParameterLocation::Query, &PathMatching::MostSpecific);
+
+        assert!(evaluation.meets_parameter_coverage(None));
+        assert!(evaluation.meets_parameter_coverage(Some(0.3)));
+        assert!(!evaluation.meets_parameter_coverage(Some(0.5)));
+    }
+
+    #[test]
+    fn skips_ignored_operations() {
+        let openapi_endpoints = create_search_endpoints();
+        let mut groupings = HashSet::new();
+        groupings.insert(Grouping::new(
+            vec![Method::GET],
+            vec![200, 400],
+            OpenapiPath::from_str("/search/{index}").unwrap(),
+            true,
+        ));
+
+        let evaluation = evaluate_parameters(&openapi_endpoints, &[], &groupings, ParameterLocation::Query, &PathMatching::MostSpecific);
+
+        assert!(evaluation.operations.is_empty());
+    }
+
+    #[test]
+    fn reports_observed_enum_values_of_path_and_query_parameters() {
+        let runtime = Arc::new(create_mock_runtime());
+        let openapi_endpoints = vec![EndpointConfiguration::new(Method::GET, "/tickets/{priority}", 200, runtime, false)
+            .unwrap()
+            .with_parameters(vec![
+                Parameter::new("priority", ParameterLocation::Path, true)
+                    .with_enum_values(vec!["low".to_string(), "high".to_string()]),
+                Parameter::new("status", ParameterLocation::Query, false)
+                    .with_enum_values(vec!["open".to_string(), "closed".to_string(), "archived".to_string()]),
+                Parameter::new("limit", ParameterLocation::Query, false),
+            ])];
+        let observations = vec![
+            create_observation("/tickets/low?status=open"),
+            create_observation("/tickets/high?status=closed&status=open"),
+            create_observation("/tickets?status=archived"),
+        ];
+
+        let enum_value_coverages = evaluate_enum_values(&openapi_endpoints, &observations, &HashSet::new(), &PathMatching::MostSpecific);
+
+        assert_eq!(enum_value_coverages.len(), 2);
+        assert_eq!(enum_value_coverages[0].parameter.name, "priority");
+        assert_eq!(enum_value_coverages[0].observed_values, vec!["low", "high"]);
+        assert!(enum_value_coverages[0].unobserved_values.is_empty());
+        assert_eq!(enum_value_coverages[1].observed_values, vec!["open", "closed"]);
+        assert_eq!(enum_value_coverages[1].unobserved_values, vec!["archived"]);
+    }
+}
@@ -0,0 +1,268 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{
+    config::PathMatching,
+    models::{EndpointConfiguration, Method, Observation, OpenapiPath, StatusMatcher},
+};
+
+// the paths of the spec as a trie of their segments per port and method, so looking up a request doesn't depend on the size of the spec
+pub struct RouteIndex<'a> {
+    routes: HashMap<(u16, Method), RouteNode<'a>>,
+    path_matching: PathMatching,
+}
+
+#[derive(Default)]
+struct RouteNode<'a> {
+    fixed: HashMap<&'a str, RouteNode<'a>>,
+    templated: Vec<(OpenapiPath, RouteNode<'a>)>,
+    // every status code of the path ending in this node
+    endpoints: Vec<&'a EndpointConfiguration>,
+    specificity: Vec<(u8, usize)>,
+}
+
+// observations of each operation, operations are identified by their port, method and path
+pub struct OperationObservations<'a, 'b> {
+    observations: HashMap<(u16, &'a Method, &'a OpenapiPath), Vec<&'b Observation>>,
+}
+
+impl<'a> RouteIndex<'a> {
+    pub fn new(openapi_endpoints: &'a [EndpointConfiguration], path_matching: &PathMatching) -> RouteIndex<'a> {
+        let mut routes: HashMap<(u16, Method), RouteNode> = HashMap::new();
+        for endpoint in openapi_endpoints {
+            // an endpoint is reachable under the base path of every server, without being duplicated for each of them
+            for path in std::iter::once(&endpoint.path).chain(&endpoint.alternative_paths) {
+                let mut node = routes.entry((endpoint.runtime.port, endpoint.method.clone())).or_default();
+                for segment in path.segments() {
+                    node = node.get_or_insert_child(segment);
+                }
+                if node.endpoints.is_empty() {
+                    node.specificity = path.specificity();
+                }
+                node.endpoints.push(endpoint);
+            }
+        }
+        RouteIndex {
+            routes,
+            path_matching: *path_matching,
+        }
+    }
+
+    // every endpoint of the operations a request is attributed to, regardless of their status code
+    pub fn find_operation_endpoints(&self, observed_endpoint: &EndpointConfiguration) -> Vec<&'a EndpointConfiguration> {
+        let root = match self.routes.get(&(observed_endpoint.runtime.port, observed_endpoint.method.clone())) {
+            Some(root) => root,
+            None => return vec![],
+        };
+        let segments: Vec<&str> = observed_endpoint.path.segments().collect();
+        let mut matching_nodes = vec![];
+        root.collect_matching_nodes(&segments, &mut matching_nodes);
+
+        matching_nodes.retain(|node| node.endpoints.iter().any(|endpoint| endpoint.runtime == observed_endpoint.runtime));
+        if self.path_matching == PathMatching::MostSpecific {
+            if let Some(highest_specificity) = matching_nodes.iter().map(|node| &node.specificity).max().cloned() {
+                matching_nodes.retain(|node| node.specificity == highest_specificity);
+            }
+        }
+        let mut endpoints: Vec<&'a EndpointConfiguration> = vec![];
+        for endpoint in matching_nodes.into_iter().flat_map(|node| node.endpoints.iter()) {
+            // a request can match several base paths of the same endpoint
+            if endpoint.runtime == observed_endpoint.runtime && !endpoints.iter().any(|known| std::ptr::eq(*known, *endpoint)) {
+                endpoints.push(endpoint);
+            }
+        }
+        endpoints
+    }
+
+    // explicit status codes take precedence over ranges and both over default responses,
+    // default responses cover every status code, which no other response of the same operation describes
+    pub fn find_endpoints(&self, observed_endpoint: &EndpointConfiguration) -> Vec<&'a EndpointConfiguration> {
+        let operation_endpoints = self.find_operation_endpoints(observed_endpoint);
+        operation_endpoints
+            .iter()
+            .filter(|endpoint| {
+                let matches = match endpoint.status_code {
+                    StatusMatcher::Default => true,
+                    status_code => status_code.matches(&observed_endpoint.status_code),
+                };
+                matches
+                    && !operation_endpoints.iter().any(|other| {
+                        other.is_same_operation(endpoint)
+                            && other.status_code < endpoint.status_code
+                            && other.status_code.matches(&observed_endpoint.status_code)
+                    })
+            })
+            .copied()
+            .collect()
+    }
+
+    pub fn group_by_operation<'b>(&self, observations: &'b [Observation]) -> OperationObservations<'a, 'b> {
+        let mut grouped_observations: HashMap<(u16, &'a Method, &'a OpenapiPath), Vec<&'b Observation>> = HashMap::new();
+        for observation in observations {
+            let mut operations: Vec<(u16, &Method, &OpenapiPath)> = self
+                .find_operation_endpoints(&observation.endpoint)
+                .into_iter()
+                .map(|endpoint| (endpoint.runtime.port, &endpoint.method, &endpoint.path))
+                .collect();
+            operations.dedup();
+            for operation in operations {
+                grouped_observations.entry(operation).or_default().push(observation);
+            }
+        }
+        OperationObservations {
+            observations: grouped_observations,
+        }
+    }
+}
+
+impl<'a> RouteNode<'a> {
+    // segments without variables are looked up directly, templated ones have to be matched one by one
+    fn get_or_insert_child(&mut self, segment: &'a str) -> &mut RouteNode<'a> {
+        let pattern = match OpenapiPath::from_str(segment) {
+            Ok(pattern) if !pattern.is_concrete() => pattern,
+            _ => return self.fixed.entry(segment).or_default(),
+        };
+        let index = match self.templated.iter().position(|(existing_pattern, _)| existing_pattern == &pattern) {
+            Some(index) => index,
+            None => {
+                self.templated.push((pattern, RouteNode::default()));
+                self.templated.len() - 1
+            }
+        };
+        &mut self.templated[index].1
+    }
+
+    fn collect_matching_nodes<'s>(&'s self, segments: &[&str], matching_nodes: &mut Vec<&'s RouteNode<'a>>) {
+        let (segment, remaining_segments) = match segments.split_first() {
+            Some(split) => split,
+            None => {
+                if !self.endpoints.is_empty() {
+                    matching_nodes.push(self);
+                }
+                return;
+            }
+        };
+        if let Some(child) = self.fixed.get(*segment) {
+            child.collect_matching_nodes(remaining_segments, matching_nodes);
+        }
+        for (pattern, child) in &self.templated {
+            if pattern.incompases_str(segment) {
+                child.collect_matching_nodes(remaining_segments, matching_nodes);
+            }
+        }
+    }
+}
+
+impl<'a, 'b> OperationObservations<'a, 'b> {
+    pub fn get(&self, operation: &'a EndpointConfiguration) -> &[&'b Observation] {
+        match self.observations.get(&(operation.runtime.port, &operation.method, &operation.path)) {
+            Some(observations) => observations,
+            None => &[],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        config::PathMatching,
+        models::{EndpointConfiguration, Method, Observation, StatusMatcher},
+        utils::test::create_mock_runtime,
+    };
+
+    use super::RouteIndex;
+
+    fn create_endpoint(method: Method, path: &str, status_code: u16) -> EndpointConfiguration {
+        EndpointConfiguration::new(method, path, status_code, Arc::new(create_mock_runtime()), false).unwrap()
+    }
+
+    #[test]
+    fn finds_endpoints_by_method_path_and_status() {
+        let openapi_endpoints = vec![
+            create_endpoint(Method::GET, "/a", 200),
+            create_endpoint(Method::GET, "/b", 200),
+            create_endpoint(Method::POST, "/c", 200),
+            create_endpoint(Method::GET, "/files/{name}.json", 200),
+        ];
+        let route_index = RouteIndex::new(&openapi_endpoints, &PathMatching::MostSpecific);
+
+        assert_eq!(route_index.find_endpoints(&create_endpoint(Method::GET, "/a", 200)), vec![&openapi_endpoints[0]]);
+        assert_eq!(route_index.find_endpoints(&create_endpoint(Method::GET, "/files/a.b.json", 200)), vec![&openapi_endpoints[3]]);
+        assert!(route_index.find_endpoints(&create_endpoint(Method::GET, "/a", 404)).is_empty());
+        assert!(route_index.find_endpoints(&create_endpoint(Method::GET, "/c", 200)).is_empty());
+        assert!(route_index.find_endpoints(&create_endpoint(Method::GET, "/a/b", 200)).is_empty());
+        assert!(route_index.find_endpoints(&create_endpoint(Method::GET, "/files/a.yaml", 200)).is_empty());
+        assert!(RouteIndex::new(&[], &PathMatching::MostSpecific).find_endpoints(&create_endpoint(Method::GET, "/a", 200)).is_empty());
+    }
+
+    #[test]
+    fn only_finds_most_specific_path_if_configured() {
+        let openapi_endpoints = vec![
+            create_endpoint(Method::GET, "/{tenant}/users/{id}", 200),
+            create_endpoint(Method::GET, "/acme/users/{id}", 200),
+            create_endpoint(Method::GET, "/acme/users/{id}", 404),
+            create_endpoint(Method::GET, "/acme/{collection}/me", 200),
+        ];
+        let observed_endpoint = create_endpoint(Method::GET, "/acme/users/me", 200);
+
+        let route_index = RouteIndex::new(&openapi_endpoints, &PathMatching::MostSpecific);
+        assert_eq!(route_index.find_endpoints(&observed_endpoint), vec![&openapi_endpoints[1]]);
+        assert_eq!(route_index.find_operation_endpoints(&observed_endpoint).len(), 2);
+
+        let route_index = RouteIndex::new(&openapi_endpoints, &PathMatching::All);
+        assert_eq!(route_index.find_endpoints(&observed_endpoint).len(), 3);
+    }
+
+    #[test]
+    fn finds_endpoints_under_any_base_path() {
+        let openapi_endpoints = vec![create_endpoint(Method::GET, "/v1/weather", 200)
+            .with_alternative_paths(&["/v2/weather".to_string()])
+            .unwrap()];
+        let route_index = RouteIndex::new(&openapi_endpoints, &PathMatching::All);
+
+        assert_eq!(route_index.find_endpoints(&create_endpoint(Method::GET, "/v1/weather", 200)), vec![&openapi_endpoints[0]]);
+        assert_eq!(route_index.find_endpoints(&create_endpoint(Method::GET, "/v2/weather", 200)), vec![&openapi_endpoints[0]]);
+        assert!(route_index.find_endpoints(&create_endpoint(Method::GET, "/v3/weather", 200)).is_empty());
+    }
+
+    #[test]
+    fn default_responses_only_find_undocumented_status_codes() {
+        let runtime = Arc::new(create_mock_runtime());
+        let openapi_endpoints = vec![
+            create_endpoint(Method::GET, "/a", 200),
+            EndpointConfiguration::new_with_status_matcher(Method::GET, "/a", StatusMatcher::Range(4), runtime.clone(), false).unwrap(),
+            EndpointConfiguration::new_with_status_matcher(Method::GET, "/a", StatusMatcher::Default, runtime, false).unwrap(),
+        ];
+        let route_index = RouteIndex::new(&openapi_endpoints, &PathMatching::MostSpecific);
+
+        assert_eq!(route_index.find_endpoints(&create_endpoint(Method::GET, "/a", 404)), vec![&openapi_endpoints[1]]);
+        assert_eq!(route_index.find_endpoints(&create_endpoint(Method::GET, "/a", 500)), vec![&openapi_endpoints[2]]);
+
+        // explicit status codes take precedence over their range
+        let openapi_endpoints = vec![
+            create_endpoint(Method::GET, "/a", 404),
+            EndpointConfiguration::new_with_status_matcher(Method::GET, "/a", StatusMatcher::Range(4), Arc::new(create_mock_runtime()), false).unwrap(),
+        ];
+        let route_index = RouteIndex::new(&openapi_endpoints, &PathMatching::MostSpecific);
+
+        assert_eq!(route_index.find_endpoints(&create_endpoint(Method::GET, "/a", 404)), vec![&openapi_endpoints[0]]);
+        assert_eq!(route_index.find_endpoints(&create_endpoint(Method::GET, "/a", 400)), vec![&openapi_endpoints[1]]);
+    }
+
+    #[test]
+    fn groups_observations_by_operation() {
+        let openapi_endpoints = vec![create_endpoint(Method::GET, "/users/{id}", 200), create_endpoint(Method::GET, "/users/{id}", 404)];
+        let runtime = Arc::new(create_mock_runtime());
+        let observations = vec![
+            Observation::new(Method::GET, "/users/1", 200, runtime.clone()).unwrap(),
+            Observation::new(Method::GET, "/users/2", 404, runtime.clone()).unwrap(),
+            Observation::new(Method::GET, "/moo", 200, runtime).unwrap(),
+        ];
+
+        let observations_by_operation = RouteIndex::new(&openapi_endpoints, &PathMatching::MostSpecific).group_by_operation(&observations);
+
+        assert_eq!(observations_by_operation.get(&openapi_endpoints[0]).len(), 2);
+        assert_eq!(observations_by_operation.get(&openapi_endpoints[1]).len(), 2);
+    }
+}
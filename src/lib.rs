@@ -1,22 +1,73 @@
-use std::process::{Command, Stdio};
+use std::{collections::HashMap, fs, path::Path, process::{Command, Stdio}};
 
-use config::{configure_nginx, RudraConfig};
-use evaluator::evaluate;
-use models::EndpointConfiguration;
-use parser::{get_pre_merge_openapi_endpoints, get_openapi_endpoint_configs};
-use utils::print_debug_message;
+use config::{configure_nginx, ProxyBackend, RudraConfig};
+use evaluator::{apply_default_response_policy, apply_security_policy, evaluate, evaluate_details};
+use models::{EndpointConfiguration, Observation};
+use parser::{get_pre_merge_openapi_endpoints, get_openapi_endpoint_configs, parse_access_log, write_observation_log, NGINX_ACCESS_LOG};
+use proxy::run_native_proxy;
+use report::{build_json_report, build_text_report, get_json_report_exit_code, read_json_report, write_json_report, write_junit_report};
+use utils::{print_debug_message, print_error_and_exit_with_code, Error, ExitCode};
 
-use crate::{parser::parse_nginx_access_log, utils::print_error_and_exit};
+use crate::utils::print_error_and_exit;
 
 pub mod config;
 pub mod evaluator;
 pub mod models;
 pub mod parser;
+pub mod proxy;
+pub mod report;
 pub mod utils;
 
-pub fn run_nginx(config: &RudraConfig) {
-    // insert application URL to nginx file
-    match configure_nginx(config) {
+// the recorded requests are kept in the configured access log, so they can be evaluated again later on
+pub fn run_proxy(config: &RudraConfig, openapi_endpoints: &[EndpointConfiguration]) -> Vec<Observation> {
+    let access_log = config.access_log.as_ref().map(|access_log| config.repository.join(access_log));
+    match config.proxy_backend {
+        ProxyBackend::Native => {
+            let observations = match run_native_proxy(&config.runtimes, &config.redacted_headers) {
+                Ok(observations) => observations,
+                Err(error) => error.display_error_and_exit(),
+            };
+            if let Some(access_log) = access_log {
+                print_debug_message(format!("Writing access log to {:?}", access_log));
+                if let Err(why) = write_observation_log(&observations, &access_log) {
+                    why.display_error();
+                }
+            }
+            observations
+        }
+        ProxyBackend::Nginx => {
+            run_nginx(config, openapi_endpoints);
+            if let Some(access_log) = access_log {
+                print_debug_message(format!("Copying access log to {:?}", access_log));
+                if let Err(why) = fs::copy(NGINX_ACCESS_LOG, &access_log) {
+                    Error::UnexpectedIOIssue(format!("issue copying access log to {:?} due to: {}", access_log, why)).display_error();
+                }
+            }
+            read_access_log(config, Path::new(NGINX_ACCESS_LOG))
+        }
+    }
+}
+
+// logs of nginx as well as those written by the native proxy can be read
+pub fn read_access_log(config: &RudraConfig, path: &Path) -> Vec<Observation> {
+    match parse_access_log(&config.runtimes, path, config.rejected_lines_limit) {
+        Ok(observations) => observations,
+        Err(err @ Error::TooManyRejectedLines(..)) => err.display_error_and_exit(),
+        Err(_) => print_error_and_exit(format!("An unexpected error occured while parsing the access log {:?}", path)),
+    }
+}
+
+// without a configured access log, the one nginx left behind is evaluated
+pub fn get_access_log_path(config: &RudraConfig) -> Box<Path> {
+    match &config.access_log {
+        Some(access_log) => Box::from(config.repository.join(access_log)),
+        None => Box::from(Path::new(NGINX_ACCESS_LOG)),
+    }
+}
+
+pub fn run_nginx(config: &RudraConfig, openapi_endpoints: &[EndpointConfiguration]) {
+    // insert application URL and logged headers to nginx file
+    match configure_nginx(config, openapi_endpoints) {
         Ok(_) => (),
         Err(error) => error.display_error_and_exit(),
     }
@@ -42,14 +93,14 @@ pub fn run_nginx(config: &RudraConfig) {
     }
 }
 
-pub fn initialize_rudra() -> (RudraConfig, Vec<EndpointConfiguration>, Option<Vec<EndpointConfiguration>>) {
-    let config = match RudraConfig::from_env() {
+pub fn initialize_rudra(cli_options: &HashMap<String, String>) -> (RudraConfig, Vec<EndpointConfiguration>, Option<Vec<EndpointConfiguration>>) {
+    let config = match RudraConfig::from_env_and_cli(cli_options) {
         Ok(config) => config,
         Err(error) => error.display_error_and_exit(),
     };
 
     let openapi_endpoints = match get_openapi_endpoint_configs(&config) {
-        Ok(openapi_endpoints) => openapi_endpoints,
+        Ok(openapi_endpoints) => apply_policies(&config, openapi_endpoints),
         Err(error) => error.display_error_and_exit(),
     };
 
@@ -59,9 +110,9 @@ pub fn initialize_rudra() -> (RudraConfig, Vec<EndpointConfiguration>, Option<Ve
     // be compared
     if config.only_account_for_merge && !config.all_openapi_sources_are_paths() {
         if config.is_merge {
-            print_error_and_exit("Your configuration contains a dynamically loaded openapi spec. Rudra needs it to be a local file when only accounting for the difference between commits.");
+            print_error_and_exit_with_code("Your configuration contains a dynamically loaded openapi spec. Rudra needs it to be a local file when only accounting for the difference between commits.", ExitCode::ConfigurationError);
         } else {
-            print_error_and_exit("You need to have two commits to compare (ex. pull/merge request) when only accounting for the difference between commits.");
+            print_error_and_exit_with_code("You need to have two commits to compare (ex. pull/merge request) when only accounting for the difference between commits.", ExitCode::ConfigurationError);
         }
     } 
 
@@ -70,38 +121,96 @@ pub fn initialize_rudra() -> (RudraConfig, Vec<EndpointConfiguration>, Option<Ve
         let mut endpoints = vec![];
 
         for runtime in &config.runtimes {
-            let mut pre_merge_endpoints_of_runtime = match get_pre_merge_openapi_endpoints(runtime.clone()) {
+            let mut pre_merge_endpoints_of_runtime = match get_pre_merge_openapi_endpoints(runtime.clone(), &config.repository) {
                 Ok(endpoints) => endpoints,
                 Err(err) => err.display_error_and_exit(),
             };
             endpoints.append(&mut pre_merge_endpoints_of_runtime);
         }
-        pre_merge_endpoints = Some(endpoints);
+        pre_merge_endpoints = Some(apply_policies(&config, endpoints));
     }     
     (config, openapi_endpoints, pre_merge_endpoints)
 }
 
-pub fn run_eval(config: &RudraConfig, openapi_endpoints: Vec<EndpointConfiguration>, pre_merge_endpoints: Option<Vec<EndpointConfiguration>>) {
-    print_debug_message("Evaluating endpoint coverage");
+fn apply_policies(config: &RudraConfig, openapi_endpoints: Vec<EndpointConfiguration>) -> Vec<EndpointConfiguration> {
+    let openapi_endpoints = apply_default_response_policy(openapi_endpoints, &config.default_response_policy);
+    apply_security_policy(
+        openapi_endpoints,
+        config.security_accounts_for_unautorized,
+        config.security_accounts_for_forbidden,
+    )
+}
 
-    let nginx_endpoints = match parse_nginx_access_log(&config.runtimes) {
-        Ok(nginx_endpoints) => nginx_endpoints,
-        Err(_) => print_error_and_exit("An unexpected error occured while parsing the nginx logs"),
-    };
+pub fn run_eval(config: &RudraConfig, openapi_endpoints: Vec<EndpointConfiguration>, pre_merge_endpoints: Option<Vec<EndpointConfiguration>>, observations: Vec<Observation>) -> ExitCode {
+    print_debug_message("Evaluating endpoint coverage");
 
-    let evaluation = evaluate(&openapi_endpoints, &pre_merge_endpoints, &nginx_endpoints, &config.groupings);
+    let evaluation = evaluate(&openapi_endpoints, &pre_merge_endpoints, &observations, &config.groupings, &config.path_matching);
+    let detailed_evaluation = evaluate_details(&openapi_endpoints, &observations, &config.groupings, config.media_type_coverage, &config.path_matching);
+    // the results are printed from the json report, so they read the same as when rendering a saved report
+    let report = build_json_report(config, &evaluation, &detailed_evaluation);
+    print!("{}", build_text_report(&report));
+
+    if let Some(json_report) = &config.json_report {
+        let report_path = config.repository.join(json_report);
+        print_debug_message(format!("Writing json report to {:?}", report_path));
+        if let Err(why) = write_json_report(&report, &report_path) {
+            why.display_error();
+        }
+    }
 
-    if evaluation.has_gateway_issues {
-        println!("WARNING: an unusual amount of 502 status codes were found, your setup might have gateway issues.");
+    if let Some(junit_report) = &config.junit_report {
+        let report_path = config.repository.join(junit_report);
+        print_debug_message(format!("Writing junit report to {:?}", report_path));
+        if let Err(why) = write_junit_report(config, &evaluation, &report_path) {
+            why.display_error();
+        }
     }
 
-    println!("Test Coverage: {}%", evaluation.test_coverage * 100.0);
-    
-    if evaluation.endpoints_not_covered.len() > 0 {
-        println!("The following endpoints were missed:");
-        for endpoint in evaluation.endpoints_not_covered {
-            println!("- {} {} {}", endpoint.path, endpoint.method, endpoint.status_code);
+    get_json_report_exit_code(&report)
+}
+
+pub fn run_check_config(config: &RudraConfig, openapi_endpoints: &[EndpointConfiguration]) -> ExitCode {
+    config.print();
+    println!(
+        "The configuration is valid, the openapi specs of {} services contain {} endpoints",
+        config.runtimes.len(),
+        openapi_endpoints.len()
+    );
+    ExitCode::Success
+}
+
+pub fn run_list_endpoints(config: &RudraConfig, openapi_endpoints: &[EndpointConfiguration]) -> ExitCode {
+    for runtime in &config.runtimes {
+        println!("Endpoints of {} (port {}):", runtime.app_base_url, runtime.port);
+        let mut endpoints: Vec<&EndpointConfiguration> = openapi_endpoints.iter().filter(|x| &x.runtime == runtime).collect();
+        endpoints.sort_by_cached_key(|endpoint| (endpoint.path.to_string(), endpoint.method.clone(), endpoint.status_code));
+        for endpoint in endpoints {
+            let is_ignored = config
+                .groupings
+                .iter()
+                .any(|grouping| grouping.is_ignore_group && grouping.incompases_endpoint_config(endpoint));
+            let mut annotations = vec![];
+            if endpoint.is_generated {
+                annotations.push("generated");
+            }
+            if is_ignored {
+                annotations.push("ignored");
+            }
+            match annotations.is_empty() {
+                true => println!("- {} {} {}", endpoint.path, endpoint.method, endpoint.status_code),
+                false => println!("- {} {} {} ({})", endpoint.path, endpoint.method, endpoint.status_code, annotations.join(", ")),
+            }
         }
     }
+    ExitCode::Success
 }
 
+// renders a json report of an earlier evaluation, exiting like the evaluation did
+pub fn run_report(path: &Path) -> ExitCode {
+    let report = match read_json_report(path) {
+        Ok(report) => report,
+        Err(error) => error.display_error_and_exit(),
+    };
+    print!("{}", build_text_report(&report));
+    get_json_report_exit_code(&report)
+}
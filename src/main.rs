@@ -1,12 +1,48 @@
-use rudra::{initialize_rudra, run_eval, run_nginx};
+use std::{env, process};
 
-fn main() {
-    let (config, openapi_endpoints, pre_merge_endpoints) = initialize_rudra();
+use rudra::{
+    config::{get_usage, parse_cli_arguments, Command},
+    get_access_log_path, initialize_rudra, read_access_log, run_check_config, run_eval, run_list_endpoints, run_proxy, run_report,
+    utils::ExitCode,
+};
 
-    if config.debug {
-        config.print();
-    }
-    run_nginx(&config);
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let cli_arguments = match parse_cli_arguments(&args) {
+        Ok(cli_arguments) => cli_arguments,
+        Err(error) => error.display_error_and_exit(),
+    };
 
-    run_eval(&config, openapi_endpoints, pre_merge_endpoints);
+    let exit_code = match cli_arguments.command {
+        Command::Proxy => {
+            let (config, openapi_endpoints, pre_merge_endpoints) = initialize_rudra(&cli_arguments.options);
+            if config.debug {
+                config.print();
+            }
+            let observations = run_proxy(&config, &openapi_endpoints);
+            run_eval(&config, openapi_endpoints, pre_merge_endpoints, observations)
+        }
+        Command::Evaluate => {
+            let (config, openapi_endpoints, pre_merge_endpoints) = initialize_rudra(&cli_arguments.options);
+            if config.debug {
+                config.print();
+            }
+            let observations = read_access_log(&config, &get_access_log_path(&config));
+            run_eval(&config, openapi_endpoints, pre_merge_endpoints, observations)
+        }
+        Command::CheckConfig => {
+            let (config, openapi_endpoints, _) = initialize_rudra(&cli_arguments.options);
+            run_check_config(&config, &openapi_endpoints)
+        }
+        Command::ListEndpoints => {
+            let (config, openapi_endpoints, _) = initialize_rudra(&cli_arguments.options);
+            run_list_endpoints(&config, &openapi_endpoints)
+        }
+        Command::Report(path) => run_report(&path),
+        Command::Help => {
+            print!("{}", get_usage());
+            ExitCode::Success
+        }
+    };
+    process::exit(exit_code.as_i32());
 }
@@ -1,16 +1,24 @@
-use std::{fmt::Display, str::FromStr, sync::Arc};
+use std::{cmp::Ordering, fmt::Display, hash::{Hash, Hasher}, str::{FromStr, Split}, sync::Arc};
 
 use crate::{config::Runtime, utils::Error};
 
-use super::misc::Method;
+use super::{misc::Method, MediaType, Parameter, Schema, StatusMatcher};
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+// endpoints are identified by method, path, status and runtime, the parameters, bodies and media types describe them,
+// so a changed schema or description doesn't make them a different endpoint
+#[derive(Debug, Clone)]
 pub struct EndpointConfiguration {
     pub method: Method,
     pub path: OpenapiPath,
-    pub status_code: u16,
+    // the same path under the base paths of the further servers of the spec, requests to them cover this endpoint too
+    pub alternative_paths: Vec<OpenapiPath>,
+    pub status_code: StatusMatcher,
     pub runtime: Arc<Runtime>,
     pub is_generated: bool,
+    pub parameters: Vec<Parameter>,
+    pub request_body: Option<Arc<Schema>>,
+    pub request_media_types: Vec<String>,
+    pub response_media_types: Vec<MediaType>,
 }
 
 impl EndpointConfiguration {
@@ -20,22 +28,102 @@ impl EndpointConfiguration {
         status_code: u16,
         runtime: Arc<Runtime>,
         is_generated: bool,
+    ) -> Result<EndpointConfiguration, Error> {
+        EndpointConfiguration::new_with_status_matcher(
+            method,
+            openapi_path,
+            StatusMatcher::Exact(status_code),
+            runtime,
+            is_generated,
+        )
+    }
+
+    pub fn new_with_status_matcher(
+        method: Method,
+        openapi_path: &str,
+        status_code: StatusMatcher,
+        runtime: Arc<Runtime>,
+        is_generated: bool,
     ) -> Result<EndpointConfiguration, Error> {
         Ok(EndpointConfiguration {
             method,
             path: OpenapiPath::from_str(openapi_path)?,
+            alternative_paths: vec![],
             status_code,
             runtime,
             is_generated,
+            parameters: vec![],
+            request_body: None,
+            request_media_types: vec![],
+            response_media_types: vec![],
         })
     }
 
+    pub fn with_alternative_paths(mut self, alternative_paths: &[String]) -> Result<EndpointConfiguration, Error> {
+        self.alternative_paths = alternative_paths
+            .iter()
+            .map(|path| OpenapiPath::from_str(path))
+            .collect::<Result<_, _>>()?;
+        Ok(self)
+    }
+
+    pub fn with_parameters(mut self, parameters: Vec<Parameter>) -> EndpointConfiguration {
+        self.parameters = parameters;
+        self
+    }
+
+    pub fn with_request_body(mut self, request_body: Option<Arc<Schema>>) -> EndpointConfiguration {
+        self.request_body = request_body;
+        self
+    }
+
+    pub fn with_request_media_types(mut self, request_media_types: Vec<String>) -> EndpointConfiguration {
+        self.request_media_types = request_media_types;
+        self
+    }
+
+    pub fn with_response_media_types(mut self, response_media_types: Vec<MediaType>) -> EndpointConfiguration {
+        self.response_media_types = response_media_types;
+        self
+    }
+
+    // names and values of the path variables, if the other path is the path of this endpoint under any of its base paths
+    pub fn match_path_variables(&self, other: &OpenapiPath) -> Option<Vec<(&str, String)>> {
+        std::iter::once(&self.path)
+            .chain(&self.alternative_paths)
+            .find_map(|path| path.match_variables(other))
+    }
+
     pub fn incompases_endpoint(&self, other: &EndpointConfiguration) -> bool {
+        self.status_code.matches(&other.status_code) && self.incompases_operation(other)
+    }
+
+    // same method and path, regardless of the status code
+    pub fn incompases_operation(&self, other: &EndpointConfiguration) -> bool {
         self.method == other.method
-            && self.status_code == other.status_code
             && self.runtime == other.runtime
             && self.path.incompases_openapi_path(&other.path)
+    }
+
+    pub fn is_same_operation(&self, other: &EndpointConfiguration) -> bool {
+        self.method == other.method && self.runtime == other.runtime && self.path == other.path
+    }
+}
+
+impl PartialEq for EndpointConfiguration {
+    fn eq(&self, other: &Self) -> bool {
+        self.method == other.method && self.path == other.path && self.status_code == other.status_code && self.runtime == other.runtime
+    }
+}
 
+impl Eq for EndpointConfiguration {}
+
+impl Hash for EndpointConfiguration {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.method.hash(state);
+        self.path.hash(state);
+        self.status_code.hash(state);
+        self.runtime.hash(state);
     }
 }
 
@@ -47,45 +135,102 @@ pub struct OpenapiPath {
 
 impl OpenapiPath {
     pub fn incompases_openapi_path(&self, other: &OpenapiPath) -> bool {
+        self.match_variables(other).is_some()
+    }
+
+    pub fn incompases_str(&self, other: &str) -> bool {
+        self.match_str_variables(other).is_some()
+    }
+
+    // names and values of the variables, if the other path is incompased by this one
+    pub fn match_variables(&self, other: &OpenapiPath) -> Option<Vec<(&str, String)>> {
+        self.match_str_variables(&other.original_source)
+    }
+
+    fn match_str_variables(&self, other: &str) -> Option<Vec<(&str, String)>> {
+        let mut variables = vec![];
         let mut parse_index = 0;
-        let other_as_str = &other.original_source;
+        // compared as bytes, since decoded paths can contain multibyte characters
+        let other_bytes = other.as_bytes();
 
         for component_index in 0..self.components.len() {
             let component = &self.components[component_index];
             match component {
                 OpenapiPathComponent::Fixed(fixed) => {
-                    if fixed.len() + parse_index > other_as_str.len()
-                        || fixed != &other_as_str[parse_index..parse_index + fixed.len()]
-                    {
-                        return false;
+                    if !other_bytes[parse_index..].starts_with(fixed.as_bytes()) {
+                        return None;
                     }
                     parse_index += fixed.len();
                 }
-                OpenapiPathComponent::Variable => {
+                OpenapiPathComponent::Variable(name) => {
                     const EMPTY_NEXT_STRING: &str = "";
                     let next_string = match self.components.get(component_index + 1) {
                         Some(next_component) => match next_component {
                             OpenapiPathComponent::Fixed(original_source) => &original_source,
-                            OpenapiPathComponent::Variable => EMPTY_NEXT_STRING,
+                            OpenapiPathComponent::Variable(_) => EMPTY_NEXT_STRING,
                         },
                         None => EMPTY_NEXT_STRING,
                     };
 
-                    while parse_index < other_as_str.len() {
-                        if &other_as_str[parse_index..parse_index + 1] == "/"
-                            ||(next_string != EMPTY_NEXT_STRING && other_as_str.len() > next_string.len() + parse_index
-                                && &other_as_str[parse_index..parse_index + next_string.len()]
-                                    == next_string)
+                    let variable_start = parse_index;
+                    while parse_index < other_bytes.len() {
+                        if other_bytes[parse_index] == b'/'
+                            || (next_string != EMPTY_NEXT_STRING && other_bytes[parse_index..].starts_with(next_string.as_bytes()))
                         {
                             break;
                         }
                         parse_index += 1;
                     }
+                    let value = String::from_utf8_lossy(&other_bytes[variable_start..parse_index]).to_string();
+                    variables.push((name.as_str(), value));
                 }
             }
         }
 
-        parse_index == other_as_str.len()
+        if parse_index == other_bytes.len() {
+            Some(variables)
+        } else {
+            None
+        }
+    }
+
+    // concrete segments beat partially templated ones, which beat plain variables, compared from the first segment on
+    pub fn compare_specificity(&self, other: &OpenapiPath) -> Ordering {
+        self.specificity().cmp(&other.specificity())
+    }
+
+    pub fn specificity(&self) -> Vec<(u8, usize)> {
+        self.segments().map(get_segment_specificity).collect()
+    }
+
+    // variables never contain a "/", so paths can be matched segment by segment
+    pub fn segments(&self) -> Split<'_, char> {
+        self.original_source.split('/')
+    }
+
+    pub fn is_concrete(&self) -> bool {
+        self.components.iter().all(|component| matches!(component, OpenapiPathComponent::Fixed(_)))
+    }
+}
+
+// rank of the segment and the number of its fixed characters
+fn get_segment_specificity(segment: &str) -> (u8, usize) {
+    let components = match OpenapiPath::from_str(segment) {
+        Ok(path) => path.components,
+        Err(_) => return (0, 0),
+    };
+    let mut has_variables = false;
+    let mut fixed_length = 0;
+    for component in components {
+        match component {
+            OpenapiPathComponent::Fixed(fixed) => fixed_length += fixed.len(),
+            OpenapiPathComponent::Variable(_) => has_variables = true,
+        }
+    }
+    match (has_variables, fixed_length) {
+        (false, _) => (2, fixed_length),
+        (true, 0) => (0, 0),
+        (true, _) => (1, fixed_length),
     }
 }
 
@@ -111,7 +256,7 @@ impl FromStr for OpenapiPath {
                     path.push(OpenapiPathComponent::Fixed(cached_component.to_string()));
                     cached_component = String::new();
                 }
-                path.push(OpenapiPathComponent::Variable);
+                path.push(OpenapiPathComponent::Variable(current_component));
                 current_component = String::new();
             } else if !is_in_variable && character.to_string() == "{" {
                 is_in_variable = true;
@@ -144,12 +289,12 @@ impl FromStr for OpenapiPath {
 #[derive(PartialEq, Eq, Debug, Clone, Hash)]
 pub enum OpenapiPathComponent {
     Fixed(String),
-    Variable,
+    Variable(String),
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{str::FromStr, sync::Arc};
+    use std::{cmp::Ordering, str::FromStr, sync::Arc};
 
     use crate::{models::Method, utils::test::create_mock_runtime};
 
@@ -171,7 +316,7 @@ mod tests {
         let expected = OpenapiPath {
             components: vec![
                 OpenapiPathComponent::Fixed("/foo/".to_string()),
-                OpenapiPathComponent::Variable,
+                OpenapiPathComponent::Variable("bar".to_string()),
                 OpenapiPathComponent::Fixed("/moo".to_string()),
             ],
             original_source: "/foo/{bar}/moo".to_string(),
@@ -207,7 +352,7 @@ mod tests {
         let expected = OpenapiPath {
             components: vec![
                 OpenapiPathComponent::Fixed("/foo/".to_string()),
-                OpenapiPathComponent::Variable,
+                OpenapiPathComponent::Variable("bar".to_string()),
             ],
             original_source: "/foo/{bar}".to_string(),
         };
@@ -325,4 +470,35 @@ mod tests {
     fn matches_numerics_as_vairable_in_path() {
         test_incompas_path_with_string("/foo/{bar}", "/foo/69", true);
     }
+
+    #[test]
+    fn matches_variable_values() {
+        let path = OpenapiPath::from_str("/tickets/{id}/comments/{comment}.json").unwrap();
+        let other = OpenapiPath::from_str("/tickets/42/comments/7.json").unwrap();
+
+        assert_eq!(
+            path.match_variables(&other),
+            Some(vec![("id", "42".to_string()), ("comment", "7".to_string())])
+        );
+        assert_eq!(path.match_variables(&OpenapiPath::from_str("/tickets/42").unwrap()), None);
+    }
+
+    #[test]
+    fn matches_paths_with_multibyte_characters() {
+        test_incompas_path_with_string("/cities/{name}/weather", "/cities/São Paulo/weather", true);
+        test_incompas_path_with_string("/cafés", "/café", false);
+        test_incompas_path_with_string("/café", "/cafés", false);
+    }
+
+    #[test]
+    fn concrete_segments_are_more_specific_than_templated_ones() {
+        let compare = |a: &str, b: &str| OpenapiPath::from_str(a).unwrap().compare_specificity(&OpenapiPath::from_str(b).unwrap());
+
+        assert_eq!(compare("/users/me", "/users/{id}"), Ordering::Greater);
+        assert_eq!(compare("/users/{id}", "/users/me"), Ordering::Less);
+        assert_eq!(compare("/files/{name}.json", "/files/{file}"), Ordering::Greater);
+        assert_eq!(compare("/files/{name}.json", "/files/{name}.{extension}"), Ordering::Greater);
+        assert_eq!(compare("/users/{id}/posts", "/{tenant}/{id}/posts"), Ordering::Greater);
+        assert_eq!(compare("/users/{id}", "/users/{name}"), Ordering::Equal);
+    }
 }
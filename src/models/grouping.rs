@@ -11,13 +11,25 @@ pub struct Grouping {
 impl Grouping {
     pub fn incompases_endpoint_config(&self, endpoint: &EndpointConfiguration) -> bool {
         self.methods.contains(&endpoint.method)
-            && self.status.contains(&endpoint.status_code)
+            && self.status.iter().any(|status| endpoint.status_code == *status)
             && self.path.incompases_openapi_path(&endpoint.path)
     }
 
     pub fn new(methods: Vec<Method>, status: Vec<u16>, path: OpenapiPath, is_ignore_group: bool) -> Grouping {
         Grouping { methods, status, path, is_ignore_group }
     }
+
+    pub fn methods(&self) -> &Vec<Method> {
+        &self.methods
+    }
+
+    pub fn status(&self) -> &Vec<u16> {
+        &self.status
+    }
+
+    pub fn path(&self) -> &OpenapiPath {
+        &self.path
+    }
 }
 
 #[cfg(test)]
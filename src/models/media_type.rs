@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use super::Schema;
+
+const WILDCARD: &str = "*";
+
+// a media type declared for a response, with the schema of its body if there is one
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MediaType {
+    pub name: String,
+    pub schema: Option<Arc<Schema>>,
+}
+
+impl MediaType {
+    pub fn new(name: &str, schema: Option<Arc<Schema>>) -> MediaType {
+        MediaType {
+            name: name.to_string(),
+            schema,
+        }
+    }
+
+    pub fn matches(&self, content_type: &str) -> bool {
+        media_type_matches(&self.name, content_type)
+    }
+}
+
+// declared media types can contain wildcards like "application/*" or "*/*"
+pub fn media_type_matches(declared_media_type: &str, content_type: &str) -> bool {
+    let declared = get_essence(declared_media_type);
+    let observed = get_essence(content_type);
+    match (declared.split_once('/'), observed.split_once('/')) {
+        (Some((declared_type, declared_subtype)), Some((observed_type, observed_subtype))) => {
+            (declared_type == WILDCARD || declared_type == observed_type)
+                && (declared_subtype == WILDCARD || declared_subtype == observed_subtype)
+        }
+        _ => declared == observed,
+    }
+}
+
+// covers "application/json" as well as suffixes like "application/merge-patch+json"
+pub fn is_json_media_type(media_type: &str) -> bool {
+    let media_type = get_essence(media_type);
+    media_type == "application/json" || media_type.ends_with("+json")
+}
+
+// parameters like "; charset=utf-8" don't change the media type
+fn get_essence(media_type: &str) -> String {
+    media_type.split(';').next().unwrap_or("").trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_json_media_type, MediaType};
+
+    #[test]
+    fn matches_content_types_with_parameters_and_wildcards() {
+        assert!(MediaType::new("application/json", None).matches("application/json; charset=utf-8"));
+        assert!(MediaType::new("application/JSON", None).matches("application/json"));
+        assert!(MediaType::new("image/*", None).matches("image/png"));
+        assert!(MediaType::new("*/*", None).matches("text/html"));
+        assert!(!MediaType::new("application/json", None).matches("text/html"));
+        assert!(!MediaType::new("image/*", None).matches("text/plain"));
+    }
+
+    #[test]
+    fn detects_json_media_types() {
+        assert!(is_json_media_type("application/json"));
+        assert!(is_json_media_type("application/problem+json; charset=utf-8"));
+        assert!(!is_json_media_type("application/xml"));
+    }
+}
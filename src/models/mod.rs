@@ -1,8 +1,19 @@
 mod endpoint;
 mod misc;
+mod observation;
 mod grouping;
+mod media_type;
+mod parameter;
+mod schema;
+mod status;
 
 pub use endpoint::EndpointConfiguration;
 pub use endpoint::OpenapiPath;
 pub use misc::Method;
 pub use grouping::Grouping;
+pub use media_type::{is_json_media_type, media_type_matches, MediaType};
+pub use parameter::Parameter;
+pub use parameter::ParameterLocation;
+pub use schema::{json_to_enum_value, Discriminator, Schema};
+pub use status::StatusMatcher;
+pub use observation::{ContentTypes, Hits, Observation, ObservedParameter, ResponseBody, Variants, MAX_RECORDED_BODY_SIZE, REDACTED_HEADER_VALUE};
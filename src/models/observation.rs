@@ -0,0 +1,472 @@
+use std::sync::Arc;
+
+use json::JsonValue;
+
+use crate::{config::Runtime, utils::{percent_decode, percent_decode_path, Error}};
+
+use super::{is_json_media_type, EndpointConfiguration, Method, Parameter, ParameterLocation};
+
+// larger bodies aren't recorded, nginx writes them to a temporary file and doesn't log them either
+pub const MAX_RECORDED_BODY_SIZE: usize = 64 * 1024;
+// replaces the values of headers configured to be redacted, their presence is still recorded
+pub const REDACTED_HEADER_VALUE: &str = "<redacted>";
+// distinct values kept per detail of identical requests, so varying bodies or headers don't grow the memory
+pub const MAX_VARIANTS: usize = 16;
+// clients send plenty of headers which aren't declared, so the distinct names are limited as well
+const MAX_PARAMETERS: usize = 64;
+
+// the requests recorded by the proxy with the same runtime, method, path and status, their endpoint only contains the path,
+// so it can be matched against the spec, the details the evaluators need vary independently and are aggregated separately
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Observation {
+    pub endpoint: EndpointConfiguration,
+    pub hits: Hits,
+    pub query_parameters: Vec<ObservedParameter>,
+    pub request_headers: Vec<ObservedParameter>,
+    pub request_bodies: Variants<JsonValue>,
+    pub content_types: Variants<ContentTypes>,
+    pub response_bodies: Variants<ResponseBody>,
+}
+
+// identical requests are only kept once, timestamps are seconds since the unix epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hits {
+    pub count: usize,
+    pub first_seen: Option<u64>,
+    pub last_seen: Option<u64>,
+}
+
+// the distinct values of a detail with the number of requests they were sent in,
+// requests with further values are only counted as others
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variants<T> {
+    pub values: Vec<(T, usize)>,
+    pub others: usize,
+}
+
+// a query parameter or header with the number of requests which sent it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObservedParameter {
+    pub name: String,
+    pub requests: usize,
+    pub values: Variants<String>,
+}
+
+// the accepted media types are only relevant if the response doesn't declare its content type
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentTypes {
+    pub request: Option<String>,
+    pub accept: Option<String>,
+    pub response: Option<String>,
+}
+
+// the content type tells which schema of the response the body belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseBody {
+    pub content_type: String,
+    pub body: JsonValue,
+}
+
+impl Observation {
+    // the builders describe the details of a single request, identical requests are added afterwards
+    pub fn new(
+        method: Method,
+        request_target: &str,
+        status_code: u16,
+        runtime: Arc<Runtime>,
+    ) -> Result<Observation, Error> {
+        let (path, query) = split_request_target(request_target);
+        let endpoint = EndpointConfiguration::new(method, &percent_decode_path(path), status_code, runtime, false)?;
+        let mut observation = Observation::from_endpoint(endpoint);
+        observation.query_parameters = observe_parameters(parse_query(query));
+        Ok(observation)
+    }
+
+    // a single request without any details
+    pub fn from_endpoint(endpoint: EndpointConfiguration) -> Observation {
+        Observation {
+            endpoint,
+            hits: Hits::once(None),
+            query_parameters: vec![],
+            request_headers: vec![],
+            request_bodies: Variants::default(),
+            content_types: Variants::once(ContentTypes::default()),
+            response_bodies: Variants::default(),
+        }
+    }
+
+    pub fn with_timestamp(mut self, timestamp: Option<u64>) -> Observation {
+        self.hits.first_seen = timestamp;
+        self.hits.last_seen = timestamp;
+        self
+    }
+
+    // only json bodies are recorded, since only they can be compared with the schemas of the spec
+    pub fn with_request_body(mut self, request_body: &[u8]) -> Observation {
+        self.request_bodies = match parse_json_body(request_body) {
+            Some(request_body) => Variants::once(request_body),
+            None => Variants::default(),
+        };
+        self
+    }
+
+    pub fn with_request_headers(mut self, content_type: Option<&str>, accept: Option<&str>) -> Observation {
+        let mut content_types = self.get_content_types();
+        content_types.request = content_type.map(|content_type| content_type.to_string());
+        content_types.accept = accept.map(|accept| accept.to_string());
+        self.content_types = Variants::once(content_types);
+        self
+    }
+
+    // header names are case insensitive, so they are stored in lowercase
+    pub fn with_request_header_values(mut self, headers: Vec<(String, String)>) -> Observation {
+        self.request_headers = observe_parameters(headers.into_iter().map(|(name, value)| (name.to_lowercase(), value)).collect());
+        self
+    }
+
+    // the body is only recorded if the response declares itself as json
+    pub fn with_response(mut self, content_type: Option<&str>, body: &[u8]) -> Observation {
+        self.response_bodies = match (content_type, parse_json_body(body)) {
+            (Some(content_type), Some(body)) if is_json_media_type(content_type) => Variants::once(ResponseBody {
+                content_type: content_type.to_string(),
+                body,
+            }),
+            _ => Variants::default(),
+        };
+        let mut content_types = self.get_content_types();
+        content_types.response = content_type.map(|content_type| content_type.to_string());
+        self.content_types = Variants::once(content_types);
+        self
+    }
+
+    // the details of identical requests are added to the aggregates, so only their distinct values are kept
+    pub fn add(&mut self, other: Observation) {
+        self.hits.add(&other.hits);
+        add_parameters(&mut self.query_parameters, other.query_parameters);
+        add_parameters(&mut self.request_headers, other.request_headers);
+        self.request_bodies.add_all(other.request_bodies);
+        self.content_types.add_all(other.content_types);
+        self.response_bodies.add_all(other.response_bodies);
+    }
+
+    fn get_content_types(&self) -> ContentTypes {
+        match self.content_types.values.first() {
+            Some((content_types, _)) => content_types.clone(),
+            None => ContentTypes::default(),
+        }
+    }
+
+    pub fn get_query_parameter(&self, name: &str) -> Option<&str> {
+        self.query_parameters
+            .iter()
+            .find(|parameter| parameter.name == name)
+            .and_then(ObservedParameter::get_first_value)
+    }
+
+    pub fn get_request_header(&self, name: &str) -> Option<&str> {
+        self.request_headers
+            .iter()
+            .find(|header| header.name.eq_ignore_ascii_case(name))
+            .and_then(ObservedParameter::get_first_value)
+    }
+
+    // the number of requests which sent the parameter, only query parameters and headers are recorded so far
+    pub fn count_parameter(&self, parameter: &Parameter) -> usize {
+        let observed_parameter = match parameter.location {
+            ParameterLocation::Query => self.query_parameters.iter().find(|x| x.name == parameter.name),
+            ParameterLocation::Header => self.request_headers.iter().find(|x| x.name.eq_ignore_ascii_case(&parameter.name)),
+            _ => None,
+        };
+        observed_parameter.map_or(0, |observed_parameter| observed_parameter.requests)
+    }
+
+    pub fn has_parameter(&self, parameter: &Parameter) -> bool {
+        self.count_parameter(parameter) > 0
+    }
+}
+
+impl<T: PartialEq> Variants<T> {
+    pub fn once(value: T) -> Variants<T> {
+        Variants {
+            values: vec![(value, 1)],
+            others: 0,
+        }
+    }
+
+    pub fn add(&mut self, value: T, count: usize) {
+        if let Some((_, value_count)) = self.values.iter_mut().find(|(x, _)| *x == value) {
+            *value_count += count;
+        } else if self.values.len() < MAX_VARIANTS {
+            self.values.push((value, count));
+        } else {
+            self.others += count;
+        }
+    }
+
+    pub fn add_all(&mut self, other: Variants<T>) {
+        for (value, count) in other.values {
+            self.add(value, count);
+        }
+        self.others += other.others;
+    }
+}
+
+impl<T> Variants<T> {
+    // the requests with any value, including the ones which weren't kept
+    pub fn count(&self) -> usize {
+        self.values.iter().map(|(_, count)| count).sum::<usize>() + self.others
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.values.iter().map(|(value, count)| (value, *count))
+    }
+}
+
+impl<T> Default for Variants<T> {
+    fn default() -> Self {
+        Variants { values: vec![], others: 0 }
+    }
+}
+
+impl ObservedParameter {
+    pub fn get_first_value(&self) -> Option<&str> {
+        self.values.values.first().map(|(value, _)| value.as_str())
+    }
+}
+
+impl Hits {
+    pub fn once(timestamp: Option<u64>) -> Hits {
+        Hits {
+            count: 1,
+            first_seen: timestamp,
+            last_seen: timestamp,
+        }
+    }
+
+    pub fn add(&mut self, other: &Hits) {
+        self.count += other.count;
+        self.first_seen = min_timestamp(self.first_seen, other.first_seen);
+        self.last_seen = max_timestamp(self.last_seen, other.last_seen);
+    }
+}
+
+// requests without a timestamp don't change when a request was seen
+fn min_timestamp(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn max_timestamp(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+// a parameter sent several times, like "?city=a&city=b", still counts as one request
+fn observe_parameters(pairs: Vec<(String, String)>) -> Vec<ObservedParameter> {
+    let mut parameters: Vec<ObservedParameter> = vec![];
+    for (name, value) in pairs {
+        match parameters.iter_mut().find(|parameter| parameter.name == name) {
+            Some(parameter) => parameter.values.add(value, 1),
+            None => parameters.push(ObservedParameter {
+                name,
+                requests: 1,
+                values: Variants::once(value),
+            }),
+        }
+    }
+    parameters
+}
+
+fn add_parameters(parameters: &mut Vec<ObservedParameter>, other_parameters: Vec<ObservedParameter>) {
+    for other_parameter in other_parameters {
+        match parameters.iter().position(|parameter| parameter.name == other_parameter.name) {
+            Some(index) => {
+                parameters[index].requests += other_parameter.requests;
+                parameters[index].values.add_all(other_parameter.values);
+            }
+            None if parameters.len() < MAX_PARAMETERS => parameters.push(other_parameter),
+            None => (),
+        }
+    }
+}
+
+fn parse_json_body(body: &[u8]) -> Option<JsonValue> {
+    if body.is_empty() || body.len() > MAX_RECORDED_BODY_SIZE {
+        return None;
+    }
+    match std::str::from_utf8(body) {
+        Ok(body) => json::parse(body).ok(),
+        Err(_) => None,
+    }
+}
+
+// fragments aren't sent by clients, but are removed in case a log contains them nevertheless
+pub fn split_request_target(request_target: &str) -> (&str, &str) {
+    let request_target = match request_target.split_once('#') {
+        Some((request_target, _)) => request_target,
+        None => request_target,
+    };
+    match request_target.split_once('?') {
+        Some((path, query)) => (path, query),
+        None => (request_target, ""),
+    }
+}
+
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|parameter| !parameter.is_empty())
+        .map(|parameter| {
+            let (name, value) = parameter.split_once('=').unwrap_or((parameter, ""));
+            // spaces are encoded as "+" in query strings
+            (percent_decode(&name.replace('+', " ")), percent_decode(&value.replace('+', " ")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        models::{Method, OpenapiPath, Parameter, ParameterLocation},
+        utils::test::create_mock_runtime,
+    };
+
+    use super::{split_request_target, Hits, Observation, Variants, MAX_RECORDED_BODY_SIZE, MAX_VARIANTS};
+
+    #[test]
+    fn splits_path_and_query() {
+        assert_eq!(split_request_target("/weather?city=x"), ("/weather", "city=x"));
+        assert_eq!(split_request_target("/weather"), ("/weather", ""));
+        assert_eq!(split_request_target("/weather?city=x#today"), ("/weather", "city=x"));
+        assert_eq!(split_request_target("/weather?"), ("/weather", ""));
+    }
+
+    #[test]
+    fn uses_decoded_path_for_endpoint() {
+        let observation = Observation::new(Method::GET, "/caf%C3%A9/menu?a=1", 200, Arc::new(create_mock_runtime())).unwrap();
+
+        assert_eq!(observation.endpoint.path.to_string(), "/café/menu");
+    }
+
+    #[test]
+    fn keeps_encoded_slashes_in_their_segment() {
+        let observation = Observation::new(Method::GET, "/files/a%2Fb", 200, Arc::new(create_mock_runtime())).unwrap();
+        let files_path: OpenapiPath = "/files/{name}".parse().unwrap();
+
+        assert_eq!(observation.endpoint.path.to_string(), "/files/a%2Fb");
+        assert!(files_path.incompases_openapi_path(&observation.endpoint.path));
+    }
+
+    #[test]
+    fn decodes_query_parameters() {
+        let observation = Observation::new(
+            Method::GET,
+            "/weather?city=New+York&units=%C2%B0C&flag&city=Berlin",
+            200,
+            Arc::new(create_mock_runtime()),
+        )
+        .unwrap();
+
+        let query_parameters: Vec<(&str, usize, Vec<&str>)> = observation
+            .query_parameters
+            .iter()
+            .map(|parameter| (parameter.name.as_str(), parameter.requests, parameter.values.iter().map(|(value, _)| value.as_str()).collect()))
+            .collect();
+        assert_eq!(
+            query_parameters,
+            vec![("city", 1, vec!["New York", "Berlin"]), ("units", 1, vec!["°C"]), ("flag", 1, vec![""])]
+        );
+        assert_eq!(observation.get_query_parameter("city"), Some("New York"));
+        assert_eq!(observation.get_query_parameter("missing"), None);
+    }
+
+    #[test]
+    fn encoded_plus_stays_plus() {
+        let observation = Observation::new(Method::GET, "/sum?term=1%2B1", 200, Arc::new(create_mock_runtime())).unwrap();
+
+        assert_eq!(observation.get_query_parameter("term"), Some("1+1"));
+    }
+
+    #[test]
+    fn records_json_bodies_within_limit() {
+        let observation = Observation::new(Method::POST, "/pets", 201, Arc::new(create_mock_runtime())).unwrap();
+
+        assert_eq!(observation.clone().with_request_body(b"{\"name\": \"Rex\"}").request_bodies.values[0].0["name"], "Rex");
+        assert_eq!(observation.clone().with_request_body(b"name=Rex").request_bodies, Variants::default());
+        assert_eq!(observation.clone().with_request_body(b"").request_bodies, Variants::default());
+
+        let large_body = format!("\"{}\"", "a".repeat(MAX_RECORDED_BODY_SIZE));
+        assert_eq!(observation.with_request_body(large_body.as_bytes()).request_bodies, Variants::default());
+    }
+
+    #[test]
+    fn records_json_response_bodies_only() {
+        let observation = Observation::new(Method::GET, "/pets", 200, Arc::new(create_mock_runtime())).unwrap();
+
+        let json_response = observation.clone().with_response(Some("application/json; charset=utf-8"), b"[1]");
+        assert_eq!(json_response.content_types.values[0].0.response.as_deref(), Some("application/json; charset=utf-8"));
+        assert_eq!(json_response.response_bodies.values[0].0.body[0], 1);
+
+        let html_response = observation.with_response(Some("text/html"), b"[1]");
+        assert_eq!(html_response.content_types.values[0].0.response.as_deref(), Some("text/html"));
+        assert_eq!(html_response.response_bodies, Variants::default());
+    }
+
+    #[test]
+    fn matches_header_parameters_case_insensitive() {
+        let observation = Observation::new(Method::GET, "/pets", 200, Arc::new(create_mock_runtime()))
+            .unwrap()
+            .with_request_header_values(vec![("X-Tenant-Id".to_string(), "acme".to_string())]);
+
+        assert_eq!(observation.get_request_header("x-tenant-id"), Some("acme"));
+        assert!(observation.has_parameter(&Parameter::new("X-TENANT-ID", ParameterLocation::Header, true)));
+        assert!(!observation.has_parameter(&Parameter::new("If-Match", ParameterLocation::Header, false)));
+        assert!(!observation.has_parameter(&Parameter::new("X-Tenant-Id", ParameterLocation::Query, false)));
+    }
+
+    #[test]
+    fn adds_hits_of_identical_requests() {
+        let mut hits = Hits::once(Some(200));
+        hits.add(&Hits::once(Some(100)));
+        hits.add(&Hits::once(None));
+        hits.add(&Hits {
+            count: 3,
+            first_seen: Some(150),
+            last_seen: Some(300),
+        });
+
+        assert_eq!(hits, Hits { count: 6, first_seen: Some(100), last_seen: Some(300) });
+    }
+
+    #[test]
+    fn aggregates_details_of_identical_requests() {
+        let runtime = Arc::new(create_mock_runtime());
+        let mut observation = Observation::new(Method::POST, "/pets?dry-run", 201, runtime.clone())
+            .unwrap()
+            .with_request_body(b"{\"id\": 0}")
+            .with_request_headers(Some("application/json"), None)
+            .with_timestamp(Some(100));
+        for id in 1..MAX_VARIANTS + 2 {
+            observation.add(
+                Observation::new(Method::POST, "/pets", 201, runtime.clone())
+                    .unwrap()
+                    .with_request_body(format!("{{\"id\": {}}}", id).as_bytes())
+                    .with_request_headers(Some("application/json"), None)
+                    .with_timestamp(Some(100 + id as u64)),
+            );
+        }
+
+        assert_eq!(observation.hits, Hits { count: MAX_VARIANTS + 2, first_seen: Some(100), last_seen: Some(100 + MAX_VARIANTS as u64 + 1) });
+        assert_eq!(observation.request_bodies.values.len(), MAX_VARIANTS);
+        assert_eq!(observation.request_bodies.others, 2);
+        assert_eq!(observation.request_bodies.count(), MAX_VARIANTS + 2);
+        assert_eq!(observation.content_types.values.len(), 1);
+        assert_eq!(observation.count_parameter(&Parameter::new("dry-run", ParameterLocation::Query, false)), 1);
+    }
+}
@@ -0,0 +1,113 @@
+use std::str::FromStr;
+
+use crate::utils::Error;
+
+const LOCATION_QUERY_STR: &str = "query";
+const LOCATION_HEADER_STR: &str = "header";
+const LOCATION_PATH_STR: &str = "path";
+const LOCATION_COOKIE_STR: &str = "cookie";
+const LOCATION_BODY_STR: &str = "body";
+const LOCATION_FORM_DATA_STR: &str = "formData";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParameterLocation {
+    Query,
+    Header,
+    Path,
+    Cookie,
+    // body and formData only exist in swagger 2.0
+    Body,
+    FormData,
+}
+
+impl ParameterLocation {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ParameterLocation::Query => LOCATION_QUERY_STR,
+            ParameterLocation::Header => LOCATION_HEADER_STR,
+            ParameterLocation::Path => LOCATION_PATH_STR,
+            ParameterLocation::Cookie => LOCATION_COOKIE_STR,
+            ParameterLocation::Body => LOCATION_BODY_STR,
+            ParameterLocation::FormData => LOCATION_FORM_DATA_STR,
+        }
+    }
+}
+
+impl FromStr for ParameterLocation {
+    type Err = Error;
+
+    fn from_str(location_str: &str) -> Result<Self, Self::Err> {
+        match location_str {
+            LOCATION_QUERY_STR => Ok(ParameterLocation::Query),
+            LOCATION_HEADER_STR => Ok(ParameterLocation::Header),
+            LOCATION_PATH_STR => Ok(ParameterLocation::Path),
+            LOCATION_COOKIE_STR => Ok(ParameterLocation::Cookie),
+            LOCATION_BODY_STR => Ok(ParameterLocation::Body),
+            LOCATION_FORM_DATA_STR => Ok(ParameterLocation::FormData),
+            _ => Err(Error::InvalidParseParameter(format!("unknown location \"{}\"", location_str))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Parameter {
+    pub name: String,
+    pub location: ParameterLocation,
+    pub required: bool,
+    pub enum_values: Vec<String>,
+}
+
+impl Parameter {
+    pub fn new(name: &str, location: ParameterLocation, required: bool) -> Parameter {
+        Parameter {
+            name: name.to_string(),
+            location,
+            required,
+            enum_values: vec![],
+        }
+    }
+
+    pub fn with_enum_values(mut self, enum_values: Vec<String>) -> Parameter {
+        self.enum_values = enum_values;
+        self
+    }
+
+    // a parameter is identified by its name and location, header names are case insensitive
+    pub fn is_same_parameter(&self, other: &Parameter) -> bool {
+        self.location == other.location
+            && match self.location {
+                ParameterLocation::Header => self.name.eq_ignore_ascii_case(&other.name),
+                _ => self.name == other.name,
+            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::{Parameter, ParameterLocation};
+
+    #[test]
+    fn parameters_are_identified_by_name_and_location() {
+        let parameter = Parameter::new("id", ParameterLocation::Query, true);
+
+        assert!(parameter.is_same_parameter(&Parameter::new("id", ParameterLocation::Query, false)));
+        assert!(!parameter.is_same_parameter(&Parameter::new("id", ParameterLocation::Path, true)));
+        assert!(!parameter.is_same_parameter(&Parameter::new("ID", ParameterLocation::Query, true)));
+    }
+
+    #[test]
+    fn header_parameters_are_case_insensitive() {
+        let parameter = Parameter::new("X-Request-Id", ParameterLocation::Header, false);
+
+        assert!(parameter.is_same_parameter(&Parameter::new("x-request-id", ParameterLocation::Header, false)));
+    }
+
+    #[test]
+    fn parses_locations() {
+        assert_eq!(ParameterLocation::from_str("formData").unwrap(), ParameterLocation::FormData);
+        assert_eq!(ParameterLocation::from_str("query").unwrap().as_str(), "query");
+        assert!(ParameterLocation::from_str("matrix").is_err());
+    }
+}
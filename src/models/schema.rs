@@ -0,0 +1,226 @@
+use json::JsonValue;
+
+const TYPE_OBJECT: &str = "object";
+const TYPE_ARRAY: &str = "array";
+const TYPE_STRING: &str = "string";
+const TYPE_NUMBER: &str = "number";
+const TYPE_INTEGER: &str = "integer";
+const TYPE_BOOLEAN: &str = "boolean";
+const TYPE_NULL: &str = "null";
+
+// the parts of a json schema needed to tell which shapes of a request body were sent
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Schema {
+    // name of the referenced schema or its title, used to label oneOf and anyOf branches
+    pub name: Option<String>,
+    pub schema_types: Vec<String>,
+    pub enum_values: Vec<String>,
+    pub properties: Vec<(String, Schema)>,
+    pub required: Vec<String>,
+    pub items: Option<Box<Schema>>,
+    pub all_of: Vec<Schema>,
+    pub one_of: Vec<Schema>,
+    pub any_of: Vec<Schema>,
+    pub discriminator: Option<Discriminator>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Discriminator {
+    pub property_name: String,
+    // discriminator values and the names of the schemas they select
+    pub mapping: Vec<(String, String)>,
+}
+
+impl Schema {
+    pub fn is_required(&self, property_name: &str) -> bool {
+        self.required.iter().any(|required| required == property_name)
+    }
+
+    // lenient validation, only used to decide which branches of oneOf and anyOf a value belongs to
+    pub fn matches(&self, value: &JsonValue) -> bool {
+        if !self.schema_types.is_empty() && !self.schema_types.iter().any(|schema_type| matches_type(schema_type, value)) {
+            return false;
+        }
+        if !self.enum_values.is_empty() && !self.enum_values.contains(&json_to_enum_value(value)) {
+            return false;
+        }
+
+        if value.is_object() {
+            if !self.required.iter().all(|required| value.has_key(required)) {
+                return false;
+            }
+            let properties_match = self
+                .properties
+                .iter()
+                .filter(|(name, _)| value.has_key(name))
+                .all(|(name, schema)| schema.matches(&value[name.as_str()]));
+            if !properties_match {
+                return false;
+            }
+        }
+        if let (JsonValue::Array(elements), Some(items)) = (value, &self.items) {
+            if !elements.iter().all(|element| items.matches(element)) {
+                return false;
+            }
+        }
+
+        self.all_of.iter().all(|schema| schema.matches(value))
+            && (self.one_of.is_empty() || self.one_of.iter().any(|schema| schema.matches(value)))
+            && (self.any_of.is_empty() || self.any_of.iter().any(|schema| schema.matches(value)))
+    }
+
+    // describes every way the value violates the schema, locations are written like "$.items[].id"
+    pub fn validate(&self, value: &JsonValue) -> Vec<String> {
+        let mut violations = vec![];
+        self.validate_at(value, "$", &mut violations);
+        violations
+    }
+
+    fn validate_at(&self, value: &JsonValue, path: &str, violations: &mut Vec<String>) {
+        if !self.schema_types.is_empty() && !self.schema_types.iter().any(|schema_type| matches_type(schema_type, value)) {
+            violations.push(format!("{}: expected {}, found {}", path, self.schema_types.join(" or "), get_type_name(value)));
+            return;
+        }
+        if !self.enum_values.is_empty() && !self.enum_values.contains(&json_to_enum_value(value)) {
+            violations.push(format!("{}: {} is not one of the enum values", path, value.dump()));
+        }
+
+        if value.is_object() {
+            for required in self.required.iter().filter(|required| !value.has_key(required)) {
+                violations.push(format!("{}: missing required property {}", path, required));
+            }
+            for (name, schema) in self.properties.iter().filter(|(name, _)| value.has_key(name)) {
+                schema.validate_at(&value[name.as_str()], &format!("{}.{}", path, name), violations);
+            }
+        }
+        if let (JsonValue::Array(elements), Some(items)) = (value, &self.items) {
+            for element in elements {
+                items.validate_at(element, &format!("{}[]", path), violations);
+            }
+        }
+
+        for schema in &self.all_of {
+            schema.validate_at(value, path, violations);
+        }
+        if !self.one_of.is_empty() && !self.one_of.iter().any(|schema| schema.matches(value)) {
+            violations.push(format!("{}: matches none of the oneOf schemas", path));
+        }
+        if !self.any_of.is_empty() && !self.any_of.iter().any(|schema| schema.matches(value)) {
+            violations.push(format!("{}: matches none of the anyOf schemas", path));
+        }
+    }
+}
+
+// enum values are stored as they appear in the spec, strings without quotes and everything else as json
+pub fn json_to_enum_value(value: &JsonValue) -> String {
+    match value.as_str() {
+        Some(value) => value.to_string(),
+        None => value.dump(),
+    }
+}
+
+fn matches_type(schema_type: &str, value: &JsonValue) -> bool {
+    match schema_type {
+        TYPE_OBJECT => value.is_object(),
+        TYPE_ARRAY => value.is_array(),
+        TYPE_STRING => value.is_string(),
+        TYPE_NUMBER => value.is_number(),
+        TYPE_INTEGER => value.as_f64().is_some_and(|number| number.fract() == 0.0),
+        TYPE_BOOLEAN => value.is_boolean(),
+        TYPE_NULL => value.is_null(),
+        _ => true,
+    }
+}
+
+fn get_type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => TYPE_NULL,
+        JsonValue::Short(_) | JsonValue::String(_) => TYPE_STRING,
+        JsonValue::Number(_) => TYPE_NUMBER,
+        JsonValue::Boolean(_) => TYPE_BOOLEAN,
+        JsonValue::Object(_) => TYPE_OBJECT,
+        JsonValue::Array(_) => TYPE_ARRAY,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use json::{array, object, JsonValue};
+
+    use super::Schema;
+
+    fn create_typed_schema(schema_type: &str) -> Schema {
+        Schema {
+            schema_types: vec![schema_type.to_string()],
+            ..Schema::default()
+        }
+    }
+
+    #[test]
+    fn matches_types() {
+        assert!(create_typed_schema("integer").matches(&JsonValue::from(3)));
+        assert!(!create_typed_schema("integer").matches(&JsonValue::from(3.5)));
+        assert!(create_typed_schema("number").matches(&JsonValue::from(3)));
+        assert!(!create_typed_schema("string").matches(&JsonValue::from(true)));
+        assert!(Schema::default().matches(&object! { anything: "goes" }));
+    }
+
+    #[test]
+    fn matches_required_and_nested_properties() {
+        let schema = Schema {
+            properties: vec![
+                ("name".to_string(), create_typed_schema("string")),
+                ("tags".to_string(), Schema {
+                    items: Some(Box::new(create_typed_schema("string"))),
+                    ..create_typed_schema("array")
+                }),
+            ],
+            required: vec!["name".to_string()],
+            ..create_typed_schema("object")
+        };
+
+        assert!(schema.matches(&object! { name: "Rex", tags: array!["good"] }));
+        assert!(!schema.matches(&object! { tags: array!["good"] }));
+        assert!(!schema.matches(&object! { name: "Rex", tags: array![1] }));
+    }
+
+    #[test]
+    fn matches_enum_values() {
+        let schema = Schema {
+            enum_values: vec!["cat".to_string(), "1".to_string()],
+            ..Schema::default()
+        };
+
+        assert!(schema.matches(&JsonValue::from("cat")));
+        assert!(schema.matches(&JsonValue::from(1)));
+        assert!(!schema.matches(&JsonValue::from("dog")));
+    }
+
+    #[test]
+    fn describes_violations_with_their_location() {
+        let schema = Schema {
+            properties: vec![
+                ("id".to_string(), create_typed_schema("integer")),
+                ("items".to_string(), Schema {
+                    items: Some(Box::new(Schema {
+                        required: vec!["name".to_string()],
+                        ..create_typed_schema("object")
+                    })),
+                    ..create_typed_schema("array")
+                }),
+            ],
+            required: vec!["id".to_string(), "status".to_string()],
+            ..create_typed_schema("object")
+        };
+
+        assert_eq!(
+            schema.validate(&object! { id: "1", items: array![object! { name: "a" }, object! {}] }),
+            vec![
+                "$: missing required property status".to_string(),
+                "$.id: expected integer, found string".to_string(),
+                "$.items[]: missing required property name".to_string(),
+            ]
+        );
+        assert!(schema.validate(&object! { id: 1, status: "ok", items: array![] }).is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::utils::Error;
+
+const DEFAULT_STATUS_STR: &str = "default";
+const RANGE_SUFFIX: &str = "XX";
+
+// ordered from the most to the least specific, the evaluator relies on it for precedence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StatusMatcher {
+    Exact(u16),
+    // holds the first digit of the status code, "4XX" is stored as 4
+    Range(u16),
+    Default,
+}
+
+impl StatusMatcher {
+    // default responses depend on the other responses of an operation and are matched by the evaluator
+    pub fn matches(&self, other: &StatusMatcher) -> bool {
+        match (self, other) {
+            (StatusMatcher::Exact(status_code), StatusMatcher::Exact(other_status_code)) => status_code == other_status_code,
+            (StatusMatcher::Range(class), StatusMatcher::Exact(other_status_code)) => *class == other_status_code / 100,
+            (StatusMatcher::Range(class), StatusMatcher::Range(other_class)) => class == other_class,
+            _ => false,
+        }
+    }
+}
+
+// only exact status codes equal a plain status code
+impl PartialEq<u16> for StatusMatcher {
+    fn eq(&self, other: &u16) -> bool {
+        *self == StatusMatcher::Exact(*other)
+    }
+}
+
+impl Display for StatusMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatusMatcher::Exact(status_code) => write!(f, "{}", status_code),
+            StatusMatcher::Range(class) => write!(f, "{}{}", class, RANGE_SUFFIX),
+            StatusMatcher::Default => write!(f, "{}", DEFAULT_STATUS_STR),
+        }
+    }
+}
+
+impl FromStr for StatusMatcher {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let status_str = s.trim();
+        if status_str.eq_ignore_ascii_case(DEFAULT_STATUS_STR) {
+            return Ok(StatusMatcher::Default);
+        }
+
+        // the openapi spec requires uppercase ranges, lowercase ones are accepted nevertheless
+        if status_str.is_ascii() && status_str.len() == 3 && status_str[1..].eq_ignore_ascii_case(RANGE_SUFFIX) {
+            return match status_str[..1].parse() {
+                Ok(class @ 1..=5) => Ok(StatusMatcher::Range(class)),
+                _ => Err(Error::InvalidParseStatusCode(s.to_string())),
+            };
+        }
+
+        match status_str.parse() {
+            Ok(status_code) => Ok(StatusMatcher::Exact(status_code)),
+            Err(_) => Err(Error::InvalidParseStatusCode(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::StatusMatcher;
+
+    #[test]
+    fn parses_status_matchers() {
+        assert_eq!(StatusMatcher::from_str("200").unwrap(), StatusMatcher::Exact(200));
+        assert_eq!(StatusMatcher::from_str("4XX").unwrap(), StatusMatcher::Range(4));
+        assert_eq!(StatusMatcher::from_str("5xx").unwrap(), StatusMatcher::Range(5));
+        assert_eq!(StatusMatcher::from_str("default").unwrap(), StatusMatcher::Default);
+        assert!(StatusMatcher::from_str("6XX").is_err());
+        assert!(StatusMatcher::from_str("2X0").is_err());
+        assert!(StatusMatcher::from_str("ok").is_err());
+    }
+
+    #[test]
+    fn rejects_non_ascii_status_keys() {
+        assert!(StatusMatcher::from_str("€").is_err());
+        assert!(StatusMatcher::from_str("2é").is_err());
+    }
+
+    #[test]
+    fn ranges_match_status_codes_of_their_class() {
+        assert!(StatusMatcher::Range(4).matches(&StatusMatcher::Exact(404)));
+        assert!(StatusMatcher::Range(4).matches(&StatusMatcher::Exact(418)));
+        assert!(!StatusMatcher::Range(4).matches(&StatusMatcher::Exact(500)));
+        assert!(!StatusMatcher::Exact(404).matches(&StatusMatcher::Range(4)));
+    }
+
+    #[test]
+    fn default_doesnt_match_on_its_own() {
+        assert!(!StatusMatcher::Default.matches(&StatusMatcher::Exact(200)));
+    }
+
+    #[test]
+    fn displays_like_in_spec() {
+        assert_eq!(StatusMatcher::Exact(200).to_string(), "200");
+        assert_eq!(StatusMatcher::Range(2).to_string(), "2XX");
+        assert_eq!(StatusMatcher::Default.to_string(), "default");
+    }
+}
@@ -0,0 +1,248 @@
+use crate::utils::{decode_nginx_escapes, Error};
+
+// the values logged after the port, the declared header parameters follow them
+const LOGGED_VALUES: usize = 4;
+
+// a line of the access log nginx writes with rudra's log_format:
+// [$time_local] "$request" $status $server_port "$request_body" "$sent_http_content_type" "$content_type" "$http_accept" "name: value"...
+// logs written by older versions end after the port or one of the values
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct AccessLogLine<'a> {
+    pub time: &'a str,
+    pub method: &'a str,
+    // always in origin-form, the path and query of absolute-form targets
+    pub request_target: String,
+    pub status: &'a str,
+    pub port: &'a str,
+    values: Vec<&'a str>,
+}
+
+impl<'a> AccessLogLine<'a> {
+    pub fn parse(line: &'a str) -> Result<AccessLogLine<'a>, Error> {
+        let mut cursor = Cursor { remaining: line };
+
+        let time = cursor.delimited('[', ']')?;
+        cursor.expect(' ')?;
+        // nginx escapes quotes inside of variables as \x22, so the next quote ends the request
+        let request = cursor.delimited('"', '"')?;
+        cursor.expect(' ')?;
+        let status = cursor.digits()?;
+        cursor.expect(' ')?;
+        let port = cursor.digits()?;
+
+        let mut values = vec![];
+        while cursor.remaining.starts_with(" \"") {
+            cursor.expect(' ')?;
+            values.push(cursor.delimited('"', '"')?);
+        }
+
+        let (method, request_target) = parse_request(request)?;
+        Ok(AccessLogLine {
+            time,
+            method,
+            request_target,
+            status,
+            port,
+            values,
+        })
+    }
+
+    // nginx logs missing values as "-"
+    fn get_value(&self, index: usize) -> Option<&'a str> {
+        match self.values.get(index) {
+            Some(value) if *value != "-" => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn request_body(&self) -> Vec<u8> {
+        match self.get_value(0) {
+            Some(request_body) => decode_nginx_escapes(request_body),
+            None => vec![],
+        }
+    }
+
+    pub fn response_content_type(&self) -> Option<&'a str> {
+        self.get_value(1)
+    }
+
+    pub fn request_content_type(&self) -> Option<&'a str> {
+        self.get_value(2)
+    }
+
+    pub fn accept(&self) -> Option<&'a str> {
+        self.get_value(3)
+    }
+
+    // the declared header parameters are logged as "name: value", escaped just like the body
+    pub fn request_headers(&self) -> Vec<(String, String)> {
+        self.values
+            .iter()
+            .skip(LOGGED_VALUES)
+            .filter_map(|header| header.split_once(": "))
+            .filter(|(name, value)| !name.is_empty() && *value != "-")
+            .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(&decode_nginx_escapes(value)).to_string()))
+            .collect()
+    }
+}
+
+struct Cursor<'a> {
+    remaining: &'a str,
+}
+
+impl<'a> Cursor<'a> {
+    fn expect(&mut self, character: char) -> Result<(), Error> {
+        match self.remaining.strip_prefix(character) {
+            Some(remaining) => {
+                self.remaining = remaining;
+                Ok(())
+            }
+            None => Err(Error::InvalidParseSyntax),
+        }
+    }
+
+    fn delimited(&mut self, start: char, end: char) -> Result<&'a str, Error> {
+        self.expect(start)?;
+        match self.remaining.split_once(end) {
+            Some((value, remaining)) => {
+                self.remaining = remaining;
+                Ok(value)
+            }
+            None => Err(Error::InvalidParseSyntax),
+        }
+    }
+
+    fn digits(&mut self) -> Result<&'a str, Error> {
+        let end = self.remaining.find(|character: char| !character.is_ascii_digit()).unwrap_or(self.remaining.len());
+        if end == 0 {
+            return Err(Error::InvalidParseSyntax);
+        }
+        let (digits, remaining) = self.remaining.split_at(end);
+        self.remaining = remaining;
+        Ok(digits)
+    }
+}
+
+// "METHOD target HTTP/x.y", requests of http/0.9 don't have a protocol,
+// garbage like tls handshakes sent to the plain port or "-" for requests nginx couldn't read is rejected
+fn parse_request(request: &str) -> Result<(&str, String), Error> {
+    let mut parts = request.split(' ');
+    let (method, target, protocol) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(target), protocol, None) => (method, target, protocol),
+        _ => return Err(Error::InvalidParseSyntax),
+    };
+    if !is_token(method) || target.is_empty() || !protocol.is_none_or(is_http_version) {
+        return Err(Error::InvalidParseSyntax);
+    }
+
+    let target = String::from_utf8_lossy(&decode_nginx_escapes(target)).to_string();
+    Ok((method, to_origin_form(target)?))
+}
+
+// methods are tokens of rfc 9110, unknown ones are still parsed so they can be reported as such
+fn is_token(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte))
+}
+
+// HTTP/1.0, HTTP/1.1, HTTP/2.0 and HTTP/3 are all logged by nginx
+fn is_http_version(protocol: &str) -> bool {
+    let version = match protocol.strip_prefix("HTTP/") {
+        Some(version) => version,
+        None => return false,
+    };
+    let (major, minor) = match version.split_once('.') {
+        Some((major, minor)) => (major, Some(minor)),
+        None => (version, None),
+    };
+    let is_number = |value: &str| !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit());
+    is_number(major) && minor.is_none_or(is_number)
+}
+
+// proxies send absolute-form targets like "http://host:8080/path?query", only their path and query are relevant
+fn to_origin_form(target: String) -> Result<String, Error> {
+    if target.starts_with('/') {
+        return Ok(target);
+    }
+    let authority_and_path = match target.split_once("://") {
+        Some((scheme, authority_and_path)) if is_scheme(scheme) => authority_and_path,
+        _ => return Err(Error::InvalidParseSyntax),
+    };
+    match authority_and_path.find(['/', '?']) {
+        Some(index) if authority_and_path[index..].starts_with('/') => Ok(authority_and_path[index..].to_string()),
+        Some(index) => Ok(format!("/{}", &authority_and_path[index..])),
+        None => Ok("/".to_string()),
+    }
+}
+
+fn is_scheme(scheme: &str) -> bool {
+    scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AccessLogLine;
+
+    // real world lines with the method, origin-form target, status and port they are parsed to
+    const VALID_LINES: [(&str, &str, &str, &str, &str); 14] = [
+        (r#"[11/Jul/2022:08:50:03 +0000] "GET /weather HTTP/1.1" 200 8080"#, "GET", "/weather", "200", "8080"),
+        (r#"[11/Jul/2022:08:50:03 +0000] "DELETE /pets/1 HTTP/1.1" 204 8080"#, "DELETE", "/pets/1", "204", "8080"),
+        (r#"[11/Jul/2022:08:50:03 +0000] "PATCH /pets/1 HTTP/1.1" 200 8080"#, "PATCH", "/pets/1", "200", "8080"),
+        (r#"[11/Jul/2022:08:50:03 +0000] "OPTIONS /pets HTTP/1.1" 204 8080"#, "OPTIONS", "/pets", "204", "8080"),
+        (r#"[11/Jul/2022:08:50:03 +0000] "PROPFIND /dav HTTP/1.1" 405 8080"#, "PROPFIND", "/dav", "405", "8080"),
+        (r#"[11/Jul/2022:08:50:03 +0000] "GET /weather HTTP/1.0" 200 8080"#, "GET", "/weather", "200", "8080"),
+        (r#"[11/Jul/2022:08:50:03 +0000] "GET /weather HTTP/2.0" 200 8080"#, "GET", "/weather", "200", "8080"),
+        (r#"[11/Jul/2022:08:50:03 +0000] "GET /weather HTTP/3" 200 8080"#, "GET", "/weather", "200", "8080"),
+        (r#"[11/Jul/2022:08:50:03 +0000] "GET /weather" 200 8080"#, "GET", "/weather", "200", "8080"),
+        (r#"[11/Jul/2022:08:50:03 +0000] "GET http://localhost:13750/weather?days=3 HTTP/1.1" 200 13750"#, "GET", "/weather?days=3", "200", "13750"),
+        (r#"[11/Jul/2022:08:50:03 +0000] "GET https://example.com HTTP/1.1" 200 8080"#, "GET", "/", "200", "8080"),
+        (r#"[11/Jul/2022:08:50:03 +0000] "GET http://example.com?q=1 HTTP/1.1" 200 8080"#, "GET", "/?q=1", "200", "8080"),
+        (r#"[11/Jul/2022:08:50:03 +0000] "GET /search?q=\x22rex\x22 HTTP/1.1" 200 8080"#, "GET", "/search?q=\"rex\"", "200", "8080"),
+        (r#"[11/Jul/2022:08:50:03 +0000] "GET /caf\xC3\xA9 HTTP/1.1" 200 8080 "-""#, "GET", "/café", "200", "8080"),
+    ];
+
+    const INVALID_LINES: [&str; 10] = [
+        r#"[11/Jul/2022:08:50:03 +0000] "-" 400 8080"#,
+        r#"[11/Jul/2022:08:50:03 +0000] "\x16\x03\x01\x02\x00\x01\x00\x01\xFC\x03\x03" 400 8080"#,
+        r#"[11/Jul/2022:08:50:03 +0000] "GET /weather HTTP/1.1 extra" 400 8080"#,
+        r#"[11/Jul/2022:08:50:03 +0000] "GET weather HTTP/1.1" 400 8080"#,
+        r#"[11/Jul/2022:08:50:03 +0000] "GET ftp://example.com/weather HTTP/1.1" 400 8080"#,
+        r#"[11/Jul/2022:08:50:03 +0000] "GET /weather SPDY/3" 400 8080"#,
+        r#"[11/Jul/2022:08:50:03 +0000] "GET /weather HTTP/1.1" - 8080"#,
+        r#"[11/Jul/2022:08:50:03 +0000] "GET /weather HTTP/1.1" 200"#,
+        r#"[11/Jul/2022:08:50:03 +0000 "GET /weather HTTP/1.1" 200 8080"#,
+        r#"GET /weather HTTP/1.1 200 8080"#,
+    ];
+
+    #[test]
+    fn parses_real_world_lines() {
+        for (line, method, request_target, status, port) in VALID_LINES {
+            let parsed_line = AccessLogLine::parse(line).unwrap_or_else(|_| panic!("failed to parse {}", line));
+            assert_eq!(parsed_line.time, "11/Jul/2022:08:50:03 +0000", "{}", line);
+            assert_eq!(parsed_line.method, method, "{}", line);
+            assert_eq!(parsed_line.request_target, request_target, "{}", line);
+            assert_eq!(parsed_line.status, status, "{}", line);
+            assert_eq!(parsed_line.port, port, "{}", line);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        for line in INVALID_LINES {
+            assert!(AccessLogLine::parse(line).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn parses_logged_values() {
+        let parsed_line = AccessLogLine::parse(
+            r#"[11/Jul/2022:08:50:03 +0000] "POST /pets HTTP/2.0" 201 8080 "{\x22name\x22: \x22Rex\x22}" "-" "application/json" "*/*" "x-tenant-id: acme" "if-match: -""#,
+        )
+        .unwrap();
+
+        assert_eq!(parsed_line.request_body(), br#"{"name": "Rex"}"#.to_vec());
+        assert_eq!(parsed_line.response_content_type(), None);
+        assert_eq!(parsed_line.request_content_type(), Some("application/json"));
+        assert_eq!(parsed_line.accept(), Some("*/*"));
+        assert_eq!(parsed_line.request_headers(), vec![("x-tenant-id".to_string(), "acme".to_string())]);
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use crate::models::{Method, Observation, StatusMatcher};
+
+// requests are identified by runtime, method, path and status, so memory grows with the distinct requests instead of the log,
+// their bodies and headers are aggregated per request, which keeps only a limited number of distinct values
+#[derive(Default)]
+pub struct ObservationAggregator {
+    indices: HashMap<(u16, Method, String, StatusMatcher), usize>,
+    observations: Vec<Observation>,
+}
+
+impl ObservationAggregator {
+    pub fn add_observation(&mut self, observation: Observation) {
+        let endpoint = &observation.endpoint;
+        let key = (endpoint.runtime.port, endpoint.method.clone(), endpoint.path.to_string(), endpoint.status_code);
+        match self.indices.get(&key) {
+            Some(index) => self.observations[*index].add(observation),
+            None => {
+                self.indices.insert(key, self.observations.len());
+                self.observations.push(observation);
+            }
+        }
+    }
+
+    pub fn into_observations(self) -> Vec<Observation> {
+        self.observations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        models::{Hits, Method, Observation},
+        utils::test::create_mock_runtime,
+    };
+
+    use super::ObservationAggregator;
+
+    #[test]
+    fn aggregates_requests_by_method_path_and_status() {
+        let runtime = Arc::new(create_mock_runtime());
+        let mut aggregator = ObservationAggregator::default();
+        for (method, request_target, status_code, timestamp) in [
+            (Method::GET, "/a?page=1", 200, 20),
+            (Method::GET, "/b", 200, 10),
+            (Method::GET, "/a?page=2", 200, 30),
+            (Method::GET, "/a", 200, 5),
+            (Method::GET, "/a", 404, 5),
+            (Method::POST, "/a", 200, 5),
+        ] {
+            aggregator.add_observation(Observation::new(method, request_target, status_code, runtime.clone()).unwrap().with_timestamp(Some(timestamp)));
+        }
+        let observations = aggregator.into_observations();
+
+        assert_eq!(observations.len(), 4);
+        assert_eq!(observations[0].hits, Hits { count: 3, first_seen: Some(5), last_seen: Some(30) });
+        assert_eq!(observations[0].query_parameters[0].requests, 2);
+        assert_eq!(observations[0].query_parameters[0].values.values, vec![("1".to_string(), 1), ("2".to_string(), 1)]);
+        assert_eq!(observations[1].hits, Hits::once(Some(10)));
+    }
+
+    #[test]
+    fn aggregates_bodies_and_headers_of_identical_requests() {
+        let runtime = Arc::new(create_mock_runtime());
+        let mut aggregator = ObservationAggregator::default();
+        for name in ["Rex", "Rex", "Bo"] {
+            aggregator.add_observation(
+                Observation::new(Method::POST, "/pets", 201, runtime.clone())
+                    .unwrap()
+                    .with_request_body(format!(r#"{{"name": "{}"}}"#, name).as_bytes())
+                    .with_request_header_values(vec![("X-Request-Id".to_string(), name.to_string())]),
+            );
+        }
+
+        let observations = aggregator.into_observations();
+        assert_eq!(observations.len(), 1);
+        assert_eq!(observations[0].hits.count, 3);
+        assert_eq!(observations[0].request_bodies.iter().map(|(_, count)| count).collect::<Vec<usize>>(), vec![2, 1]);
+        assert_eq!(observations[0].request_headers[0].requests, 3);
+    }
+}
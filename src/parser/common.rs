@@ -1,3 +1,23 @@
+use std::str::FromStr;
+
+use url::Url;
+
+use crate::{config::OpenapiSource, utils::Error};
+
+const RELATIVE_SERVER_BASE: &str = "http://localhost/";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OpenapiVersion {
+    V2,
+    V3,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct OpenapiServer {
+    pub url: String,
+    pub variables: Vec<(String, String)>,
+}
+
 pub fn format_basepath(basepath: &str) -> &str {
     if basepath.ends_with("/") {
         &basepath[0..basepath.len()-1]
@@ -6,9 +26,74 @@ pub fn format_basepath(basepath: &str) -> &str {
     }
 }
 
+pub fn parse_openapi_version(openapi_version: Option<&str>) -> Result<OpenapiVersion, Error> {
+    match openapi_version {
+        // swagger 2.0 specs don't contain the openapi field
+        None => Ok(OpenapiVersion::V2),
+        Some(version) if version.trim().starts_with("3.") || version.trim() == "3" => Ok(OpenapiVersion::V3),
+        Some(version) => Err(Error::UnsupportedOpenapiVersion(version.to_string())),
+    }
+}
+
+// relative server urls are relative to where the spec is served, specs read from the repository
+// aren't served from a known location, so they are resolved against the root of the service
+pub fn get_spec_url(openapi_source: &OpenapiSource) -> Url {
+    match openapi_source {
+        OpenapiSource::Url(url) => url.clone(),
+        OpenapiSource::Path(_) => Url::from_str(RELATIVE_SERVER_BASE).unwrap(),
+    }
+}
+
+pub fn get_basepaths_from_servers(servers: &[OpenapiServer], spec_url: &Url) -> Result<Vec<String>, Error> {
+    // according to the openapi spec a missing servers array defaults to "/"
+    if servers.is_empty() {
+        return Ok(vec![String::new()]);
+    }
+
+    let mut basepaths = vec![];
+    for server in servers {
+        let basepath = get_basepath_from_server(server, spec_url)?;
+        if !basepaths.contains(&basepath) {
+            basepaths.push(basepath);
+        }
+    }
+    Ok(basepaths)
+}
+
+fn get_basepath_from_server(server: &OpenapiServer, spec_url: &Url) -> Result<String, Error> {
+    let server_url = replace_server_variables(server)?;
+
+    let url = match Url::from_str(&server_url) {
+        Ok(url) => url,
+        Err(_) => match spec_url.join(&server_url) {
+            Ok(url) => url,
+            Err(_) => return Err(Error::InvalidServerUrl(server.url.clone())),
+        },
+    };
+
+    Ok(format_basepath(url.path()).to_string())
+}
+
+fn replace_server_variables(server: &OpenapiServer) -> Result<String, Error> {
+    let mut server_url = server.url.clone();
+    for (name, default) in &server.variables {
+        server_url = server_url.replace(&format!("{{{}}}", name), default);
+    }
+    if server_url.contains('{') || server_url.contains('}') {
+        return Err(Error::InvalidServerUrl(server.url.clone()));
+    }
+    Ok(server_url)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::format_basepath;
+    use std::{path::Path, str::FromStr};
+
+    use url::Url;
+
+    use crate::config::OpenapiSource;
+
+    use super::{format_basepath, get_basepaths_from_servers, get_spec_url, parse_openapi_version, OpenapiServer, OpenapiVersion};
 
     #[test]
     fn coverts_slash_to_empty_string() {
@@ -24,4 +109,71 @@ mod tests {
     fn ignores_emty_string() {
         assert_eq!(format_basepath(""), "");
     }
+
+    fn create_server(url: &str, variables: Vec<(&str, &str)>) -> OpenapiServer {
+        OpenapiServer {
+            url: url.to_string(),
+            variables: variables.iter().map(|(name, default)| (name.to_string(), default.to_string())).collect(),
+        }
+    }
+
+    fn create_spec_url() -> Url {
+        get_spec_url(&OpenapiSource::Path(Box::from(Path::new("docs/openapi.yaml"))))
+    }
+
+    #[test]
+    fn recognises_openapi_versions() {
+        assert_eq!(parse_openapi_version(None).unwrap(), OpenapiVersion::V2);
+        assert_eq!(parse_openapi_version(Some("3.0.3")).unwrap(), OpenapiVersion::V3);
+        assert_eq!(parse_openapi_version(Some("3.1.0")).unwrap(), OpenapiVersion::V3);
+        assert!(parse_openapi_version(Some("4.0.0")).is_err());
+    }
+
+    #[test]
+    fn missing_servers_default_to_root() {
+        assert_eq!(get_basepaths_from_servers(&[], &create_spec_url()).unwrap(), vec![""]);
+    }
+
+    #[test]
+    fn takes_path_of_absolute_server_url() {
+        let servers = vec![create_server("https://api.example.com/v1/", vec![])];
+        assert_eq!(get_basepaths_from_servers(&servers, &create_spec_url()).unwrap(), vec!["/v1"]);
+    }
+
+    #[test]
+    fn resolves_relative_server_urls_against_root_for_spec_files() {
+        let servers = vec![create_server("/api/v3", vec![]), create_server("v4", vec![]), create_server("./v5", vec![])];
+        assert_eq!(get_basepaths_from_servers(&servers, &create_spec_url()).unwrap(), vec!["/api/v3", "/v4", "/v5"]);
+    }
+
+    #[test]
+    fn resolves_relative_server_urls_against_spec_url() {
+        let spec_url = get_spec_url(&OpenapiSource::Url(Url::from_str("https://example.com/docs/openapi.yaml").unwrap()));
+        let servers = vec![create_server("/api/v3", vec![]), create_server("v4", vec![]), create_server("./v5", vec![]), create_server("../v6", vec![])];
+        assert_eq!(get_basepaths_from_servers(&servers, &spec_url).unwrap(), vec!["/api/v3", "/docs/v4", "/docs/v5", "/v6"]);
+    }
+
+    #[test]
+    fn replaces_server_variables_with_defaults() {
+        let servers = vec![create_server(
+            "https://{environment}.example.com/{basePath}",
+            vec![("environment", "staging"), ("basePath", "v2")],
+        )];
+        assert_eq!(get_basepaths_from_servers(&servers, &create_spec_url()).unwrap(), vec!["/v2"]);
+    }
+
+    #[test]
+    fn undefined_server_variables_lead_to_error() {
+        let servers = vec![create_server("https://example.com/{basePath}", vec![])];
+        assert!(get_basepaths_from_servers(&servers, &create_spec_url()).is_err());
+    }
+
+    #[test]
+    fn deduplicates_basepaths_of_servers() {
+        let servers = vec![
+            create_server("https://example.com/v1", vec![]),
+            create_server("https://staging.example.com/v1", vec![]),
+        ];
+        assert_eq!(get_basepaths_from_servers(&servers, &create_spec_url()).unwrap(), vec!["/v1"]);
+    }
 }
@@ -1,104 +1,23 @@
 use std::sync::Arc;
 
-use json::JsonValue;
-
 use crate::{
     config::Runtime,
-    models::{EndpointConfiguration, Method},
+    models::EndpointConfiguration,
     utils::Error,
 };
 
-use super::common::format_basepath;
+use super::spec_parser::parse_spec;
 
 pub fn parse_json_doc(
     json_string: &str,
     runtime: Arc<Runtime>,
 ) -> Result<Vec<EndpointConfiguration>, Error> {
-    let mut endpoints = vec![];
-
     let json_obj = match json::parse(json_string) {
         Ok(json_obj) => json_obj,
         Err(_) => return Err(Error::InvalidParseSyntax),
     };
 
-    let base_path = match &json_obj["basePath"] {
-        JsonValue::Null => "",
-        base_path => match base_path.as_str() {
-            Some(base_path) => base_path,
-            None => return Err(Error::InvalidBasePath),
-        },
-    };
-    let base_path = format_basepath(base_path);
-
-    let paths = match &json_obj["paths"] {
-        json::Null => return Err(Error::InvalidParseSyntax),
-        responses => responses,
-    };
-
-    for path_json in paths.entries() {
-        let mut path = String::from(base_path);
-
-        match path_json.0 {
-            "/" => (),
-            _ => path.push_str(path_json.0),
-        }
-        if path.is_empty() {
-            path.push_str("/");
-        }
-
-        for (method, method_json) in get_methods_from_path(path_json.1)?.into_iter() {
-            let responses = match &method_json["responses"] {
-                json::Null => return Err(Error::InvalidParseSyntax),
-                responses => responses,
-            };
-
-            if !&method_json["security"].is_null() {
-                endpoints.push(EndpointConfiguration::new(
-                    method.clone(),
-                    &path,
-                    401,
-                    runtime.clone(),
-                    false
-                )?);
-                endpoints.push(EndpointConfiguration::new(
-                    method.clone(),
-                    &path,
-                    403,
-                    runtime.clone(),
-                    false
-                )?);
-            }
-
-            for response in responses.entries() {
-                let status_code = match response.0.parse() {
-                    Ok(status_code) => status_code,
-                    Err(_) => return Err(Error::InvalidParseStatusCode(response.0.to_string())),
-                };
-                endpoints.push(EndpointConfiguration::new(
-                    method.clone(),
-                    &path,
-                    status_code,
-                    runtime.clone(),
-                    false
-                )?)
-            }
-        }
-    }
-
-    Ok(endpoints)
-}
-
-fn get_methods_from_path(path_json: &JsonValue) -> Result<Vec<(Method, &JsonValue)>, Error> {
-    let mut methods = vec![];
-
-    for method_entry in path_json.entries() {
-        let method = match Method::from_str(method_entry.0) {
-            Some(method) => method,
-            None => return Err(Error::InvalidParseMethod(method_entry.0.to_string())),
-        };
-        methods.push((method, method_entry.1));
-    }
-    Ok(methods)
+    parse_spec(&json_obj, runtime, None)
 }
 
 #[cfg(test)]
@@ -116,7 +35,7 @@ mod test {
         "paths" : {
             "/": {
                 "get": {
-                    "security": [],
+                    "security": [{ "BasicAuth": [] }],
                     "responses": {
                         "200": {
                             "description": "OK",
@@ -249,6 +168,54 @@ mod test {
         );
     }
 
+    #[test]
+    fn marks_security_endpoints_as_generated() {
+        let endpoints = parse_json_doc(JSON_STRING, Arc::from(create_mock_runtime())).unwrap();
+
+        assert!(endpoints.iter().filter(|x| x.status_code == 401 || x.status_code == 403).all(|x| x.is_generated));
+        assert!(endpoints.iter().filter(|x| x.status_code == 200).all(|x| !x.is_generated));
+    }
+
+    const JSON_STRING_GLOBAL_SECURITY: &str = r#"
+    {
+        "basePath": "/",
+        "security": [{ "BasicAuth": [] }],
+        "paths" : {
+            "/": {
+                "get": {
+                    "responses": {
+                        "200": { "description": "OK" },
+                        "401": { "description": "Unauthorized" }
+                    }
+                },
+                "post": {
+                    "security": [],
+                    "responses": {
+                        "201": { "description": "Created" }
+                    }
+                }
+            }
+        }
+    }
+    "#;
+
+    #[test]
+    fn applies_global_security_unless_removed() {
+        let endpoints = parse_json_doc(JSON_STRING_GLOBAL_SECURITY, Arc::from(create_mock_runtime())).unwrap();
+
+        assert!(endpoints.iter().any(|x| x.method == Method::GET && x.status_code == 403 && x.is_generated));
+        assert!(!endpoints.iter().any(|x| x.method == Method::POST && (x.status_code == 401 || x.status_code == 403)));
+    }
+
+    #[test]
+    fn documented_security_responses_are_not_generated() {
+        let endpoints = parse_json_doc(JSON_STRING_GLOBAL_SECURITY, Arc::from(create_mock_runtime())).unwrap();
+        let unauthorized: Vec<_> = endpoints.iter().filter(|x| x.method == Method::GET && x.status_code == 401).collect();
+
+        assert_eq!(unauthorized.len(), 1);
+        assert!(!unauthorized[0].is_generated);
+    }
+
     const JSON_STRING_DIFF_BASEPATH: &str = r#"
     {
         "basePath": "/foo",
@@ -296,4 +263,89 @@ mod test {
                 .any(|x| x.path == OpenapiPath::from_str("/foo/bar").unwrap())
         );
     }
+
+    const JSON_STRING_OPENAPI_3: &str = r##"
+    {
+        "openapi": "3.0.3",
+        "servers": [
+            {
+                "url": "https://{environment}.example.com/{basePath}",
+                "variables": {
+                    "environment": { "default": "api", "enum": ["api", "staging"] },
+                    "basePath": { "default": "v1" }
+                }
+            },
+            { "url": "/v2/" }
+        ],
+        "paths": {
+            "/weather": {
+                "get": {
+                    "responses": {
+                        "200": {
+                            "description": "OK",
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/Weather" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+        "components": {
+            "schemas": {
+                "Weather": { "type": "object" }
+            }
+        }
+    }
+    "##;
+
+    #[test]
+    fn derives_basepaths_from_openapi_3_servers() {
+        let endpoints = parse_json_doc(JSON_STRING_OPENAPI_3, Arc::from(create_mock_runtime())).unwrap();
+
+        assert_eq!(endpoints.len(), 1);
+        assert!(endpoints.iter().all(|x| x.path == OpenapiPath::from_str("/v1/weather").unwrap()));
+        assert!(endpoints.iter().all(|x| x.alternative_paths == vec![OpenapiPath::from_str("/v2/weather").unwrap()]));
+    }
+
+    #[test]
+    fn openapi_3_without_servers_defaults_to_root() {
+        let endpoints = parse_json_doc(
+            r#"{ "openapi": "3.0.0", "paths": { "/foo": { "get": { "responses": { "200": {} } } } } }"#,
+            Arc::from(create_mock_runtime()),
+        ).unwrap();
+
+        assert_eq!(endpoints.len(), 1);
+        assert_eq!(endpoints[0].path, OpenapiPath::from_str("/foo").unwrap());
+    }
+
+    #[test]
+    fn ignores_basepath_for_openapi_3() {
+        let endpoints = parse_json_doc(
+            r#"{ "openapi": "3.0.0", "basePath": "/foo", "paths": { "/bar": { "get": { "responses": { "200": {} } } } } }"#,
+            Arc::from(create_mock_runtime()),
+        ).unwrap();
+
+        assert_eq!(endpoints[0].path, OpenapiPath::from_str("/bar").unwrap());
+    }
+
+    #[test]
+    fn resolves_referenced_path_items() {
+        let json_string = r##"
+        {
+            "paths": {
+                "/foo": { "$ref": "#/x-paths/foo" }
+            },
+            "x-paths": {
+                "foo": { "get": { "responses": { "200": { "description": "OK" } } } }
+            }
+        }
+        "##;
+        let endpoints = parse_json_doc(json_string, Arc::from(create_mock_runtime())).unwrap();
+
+        assert_eq!(endpoints.len(), 1);
+        assert_eq!(endpoints[0].path, OpenapiPath::from_str("/foo").unwrap());
+    }
 }
@@ -1,12 +1,22 @@
+mod access_log_line;
+mod aggregation;
 mod common;
 mod http;
 mod json_parser;
 mod nginx_parser;
+mod observation_log;
+mod reference;
+mod rejected_lines;
+mod schema_parser;
+mod spec_parser;
 mod yaml_parser;
 
 use std::{sync::Arc, path::Path};
 
-pub use nginx_parser::parse_nginx_access_log;
+pub use aggregation::ObservationAggregator;
+pub use nginx_parser::{parse_access_log, NGINX_ACCESS_LOG};
+pub use observation_log::write_observation_log;
+pub(crate) use yaml_parser::yaml_to_json;
 
 use crate::{
     config::{OpenapiSource, Runtime, RudraConfig},
@@ -14,38 +24,35 @@ use crate::{
     utils::{read_file_to_string_or_err, Error},
 };
 
-use self::{json_parser::parse_json_doc, yaml_parser::parse_yaml_doc, http::fetch_openapi_endpoints_for_runtime};
+use self::{http::fetch_openapi_endpoints_for_runtime, reference::SpecLocation, spec_parser::parse_spec, yaml_parser::load_yaml_as_json};
 
-const OPENAPI_MOUNT_POINT: &str = "/repo";
 const PRE_MERGE_PATH_EXTENSION: &str = ".rudra.old";
 
 pub fn get_openapi_endpoint_configs(config: &RudraConfig) -> Result<Vec<EndpointConfiguration>, Error> {
     let mut openapi_endpoints = vec![];
     for runtime in &config.runtimes {
-        let mut endpoints = match get_runtime_openapi_endpoint_configs(runtime.clone()) {
-            Ok(endpoints) => endpoints,
-            Err(err) => return Err(err),
-        };
+        let mut endpoints = get_runtime_openapi_endpoint_configs(runtime.clone(), &config.repository)?;
         openapi_endpoints.append(&mut endpoints);
     }
     Ok(openapi_endpoints)
 }
 
-pub fn get_runtime_openapi_endpoint_configs(runtime: Arc<Runtime>) -> Result<Vec<EndpointConfiguration>, Error> {
+// spec paths are relative to the repository, absolute paths are used as they are
+pub fn get_runtime_openapi_endpoint_configs(runtime: Arc<Runtime>, repository: &Path) -> Result<Vec<EndpointConfiguration>, Error> {
     match runtime.openapi_source {
         OpenapiSource::Url(_) => fetch_openapi_endpoints_for_runtime(runtime),
-        OpenapiSource::Path(_) => parse_openapi_file(runtime, OPENAPI_MOUNT_POINT, ""),
+        OpenapiSource::Path(_) => parse_openapi_file(runtime, repository, ""),
     }
 }
 
-pub fn get_pre_merge_openapi_endpoints(runtime: Arc<Runtime>) -> Result<Vec<EndpointConfiguration>, Error> {
+pub fn get_pre_merge_openapi_endpoints(runtime: Arc<Runtime>, repository: &Path) -> Result<Vec<EndpointConfiguration>, Error> {
     match runtime.openapi_source {
         OpenapiSource::Url(_) => fetch_openapi_endpoints_for_runtime(runtime),
-        OpenapiSource::Path(_) => parse_openapi_file(runtime, OPENAPI_MOUNT_POINT, PRE_MERGE_PATH_EXTENSION),
+        OpenapiSource::Path(_) => parse_openapi_file(runtime, repository, PRE_MERGE_PATH_EXTENSION),
     }
 }
 
-pub fn parse_openapi_file(runtime: Arc<Runtime>, mount_point: &str, path_extension: &str) -> Result<Vec<EndpointConfiguration>, Error> {
+pub fn parse_openapi_file(runtime: Arc<Runtime>, repository: &Path, path_extension: &str) -> Result<Vec<EndpointConfiguration>, Error> {
     let openapi_path = match &runtime.openapi_source {
         OpenapiSource::Path(path) => path,
         OpenapiSource::Url(_) => return Err(Error::UnknownInternalError("open api path read on url".to_string())),
@@ -64,22 +71,31 @@ pub fn parse_openapi_file(runtime: Arc<Runtime>, mount_point: &str, path_extensi
 
     buf.set_extension(full_extension);
 
-    let openapi_path = Path::new(mount_point).join(buf);
+    let openapi_path = repository.join(buf);
 
 
-    if extension == "json" {
-        Ok(parse_json_doc(&read_file_to_string_or_err(
-            &openapi_path,
-            Error::ProblemOpeningFile(Box::from(openapi_path.as_path())),
-        )?, runtime)?)
+    let openapi_string = read_file_to_string_or_err(
+        &openapi_path,
+        Error::ProblemOpeningFile(Box::from(openapi_path.as_path())),
+    )?;
+
+    let spec = if extension == "json" {
+        match json::parse(&openapi_string) {
+            Ok(spec) => spec,
+            Err(_) => return Err(Error::InvalidParseSyntax),
+        }
     } else if extension == "yaml" || extension == "yml" {
-        Ok(parse_yaml_doc(&read_file_to_string_or_err(
-            &openapi_path,
-            Error::ProblemOpeningFile(Box::from(openapi_path.as_path())),
-        )?, runtime)?)
+        load_yaml_as_json(&openapi_string)?
     } else {
-        Err(Error::UnknownOpenApiFormat)
-    }
+        return Err(Error::UnknownOpenApiFormat);
+    };
+
+    // references to other files are resolved relative to the spec
+    let spec_location = SpecLocation {
+        path: &openapi_path,
+        repository,
+    };
+    parse_spec(&spec, runtime, Some(spec_location))
 }
 
 #[cfg(test)]
@@ -93,7 +109,7 @@ mod tests {
         let path = Path::new("./test/resource/swagger.json");
         let mut runtime = create_mock_runtime();
         runtime.openapi_source = OpenapiSource::Path(Box::from(path));
-        assert_eq!(parse_openapi_file(Arc::from(runtime), "./", "").unwrap().len(), 6);
+        assert_eq!(parse_openapi_file(Arc::from(runtime), Path::new("./"), "").unwrap().len(), 6);
     }
 
     #[test]
@@ -101,7 +117,7 @@ mod tests {
         let path = Path::new("./test/resource/swagger.yaml");
         let mut runtime = create_mock_runtime();
         runtime.openapi_source = OpenapiSource::Path(Box::from(path));
-        assert_eq!(parse_openapi_file(Arc::from(runtime), "./", "").unwrap().len(), 6);
+        assert_eq!(parse_openapi_file(Arc::from(runtime), Path::new("./"), "").unwrap().len(), 6);
     }
 
     #[test]
@@ -109,7 +125,7 @@ mod tests {
         let path = Path::new("/test");
         let mut runtime = create_mock_runtime();
         runtime.openapi_source = OpenapiSource::Path(Box::from(path));
-        assert!(parse_openapi_file(Arc::from(runtime), "./", "").is_err())
+        assert!(parse_openapi_file(Arc::from(runtime), Path::new("./"), "").is_err())
     }
 
     #[test]
@@ -117,11 +133,23 @@ mod tests {
         let path = Path::new("./test/resource/swagger.yaml");
         let mut runtime = create_mock_runtime();
         runtime.openapi_source = OpenapiSource::Path(Box::from(path));
-        assert_eq!(parse_openapi_file(Arc::from(runtime), "./", PRE_MERGE_PATH_EXTENSION).unwrap().len(), 6);
+        assert_eq!(parse_openapi_file(Arc::from(runtime), Path::new("./"), PRE_MERGE_PATH_EXTENSION).unwrap().len(), 6);
 
         let path = Path::new("./test/resource/swagger.json");
         let mut runtime = create_mock_runtime();
         runtime.openapi_source = OpenapiSource::Path(Box::from(path));
-        assert_eq!(parse_openapi_file(Arc::from(runtime), "./", PRE_MERGE_PATH_EXTENSION).unwrap().len(), 6);
+        assert_eq!(parse_openapi_file(Arc::from(runtime), Path::new("./"), PRE_MERGE_PATH_EXTENSION).unwrap().len(), 6);
+    }
+
+    #[test]
+    fn resolves_references_to_other_files() {
+        let path = Path::new("./test/resource/references/openapi.json");
+        let mut runtime = create_mock_runtime();
+        runtime.openapi_source = OpenapiSource::Path(Box::from(path));
+        let endpoints = parse_openapi_file(Arc::from(runtime), Path::new("./"), "").unwrap();
+
+        assert_eq!(endpoints.len(), 4);
+        assert!(endpoints.iter().any(|x| x.path.to_string() == "/users" && x.status_code == 404));
+        assert!(endpoints.iter().any(|x| x.path.to_string() == "/users/{id}" && x.status_code == 404));
     }
 }
@@ -7,23 +7,31 @@ use std::{
 
 use crate::{
     config::Runtime,
-    models::{EndpointConfiguration, Method},
-    utils::{Error, print_debug_message},
+    models::{Method, Observation},
+    utils::{parse_nginx_time, Error, print_debug_message},
 };
-use lazy_static::lazy_static;
-use regex::Regex;
 
-pub fn parse_nginx_access_log(
-    runtimes: &Vec<Arc<Runtime>>,
-) -> Result<Vec<EndpointConfiguration>, Error> {
-    parse_access_log(runtimes, Path::new("/var/log/nginx/access.log"))
-}
+use super::{
+    access_log_line::AccessLogLine,
+    aggregation::ObservationAggregator,
+    observation_log::{is_observation_line, parse_observation_line},
+    rejected_lines::RejectedLines,
+};
+
+// where nginx writes its access log inside of the docker image
+pub const NGINX_ACCESS_LOG: &str = "/var/log/nginx/access.log";
 
-fn parse_access_log(
+// logs of nginx and the observations recorded by the native proxy can both be evaluated,
+// lines are streamed and requests to the same endpoint aggregated, so only distinct requests are kept in memory,
+// lines which can't be parsed are skipped as long as they don't exceed the rejected lines limit
+pub fn parse_access_log(
     runtimes: &Vec<Arc<Runtime>>,
     path: &Path,
-) -> Result<Vec<EndpointConfiguration>, Error> {
-    let mut endpoints = Vec::new();
+    rejected_lines_limit: f32,
+) -> Result<Vec<Observation>, Error> {
+    let mut aggregator = ObservationAggregator::default();
+    let mut rejected_lines = RejectedLines::default();
+    let mut lines = 0;
     let reader = match File::open(path) {
         Ok(file) => BufReader::new(file),
         Err(why) => {
@@ -41,93 +49,77 @@ fn parse_access_log(
             }
         };
 
-        endpoints.push(parse_nginx_line(runtimes, &line_str)?);
+        if line_str.trim().is_empty() {
+            continue;
+        }
+        lines += 1;
+        let result = if is_observation_line(&line_str) {
+            parse_observation_line(runtimes, &line_str)
+        } else {
+            parse_nginx_line(runtimes, &line_str)
+        };
+        match result {
+            Ok(observation) => aggregator.add_observation(observation),
+            Err(err) => rejected_lines.add(&err, &line_str),
+        }
     }
 
-    Ok(endpoints)
+    rejected_lines.print_samples();
+    rejected_lines.check_limit(lines, rejected_lines_limit)?;
+    Ok(aggregator.into_observations())
 }
 
-fn parse_nginx_line(runtimes: &Vec<Arc<Runtime>>, line: &str) -> Result<EndpointConfiguration, Error> {
-    lazy_static! {
-        static ref NGINX_LINE_REGEX: Regex =
-            Regex::new("^(\\[.+\\]) \"(\\w{3, 4}) (/\\S*) HTTP/\\d\\.\\d\" (\\d{3}) (\\d{1, 5})")
-                .unwrap();
-    }
-
-    let captures = match NGINX_LINE_REGEX.captures(line) {
-        Some(captures) => captures,
-        None => return Err(Error::InvalidParseSyntax),
-    };
-
-    let status = {
-        let status_string = match captures.get(4) {
-            Some(status_string) => status_string,
-            None => return Err(Error::InvalidParseSyntax),
-        };
-
-        match status_string.as_str().parse() {
-            Ok(status) => status,
-            Err(..) => {
-                return Err(Error::InvalidParseStatusCode(
-                    status_string.as_str().to_string(),
-                ))
-            }
-        }
-    };
-
-    let method = {
-        let method_string = match captures.get(2) {
-            Some(method_string) => method_string.as_str(),
-            None => return Err(Error::UnknownInternalError("no method nginx logs".to_string())),
-        };
+fn parse_nginx_line(runtimes: &Vec<Arc<Runtime>>, line: &str) -> Result<Observation, Error> {
+    let access_log_line = AccessLogLine::parse(line)?;
 
-        match Method::from_str(method_string) {
-            Some(method) => method,
-            None => return Err(Error::UnknownInternalError(format!("invalid method nginx {}", method_string))),
-        }
+    let status = match access_log_line.status.parse() {
+        Ok(status) => status,
+        Err(_) => return Err(Error::InvalidParseStatusCode(access_log_line.status.to_string())),
     };
-
-    let path = match captures.get(3) {
-        Some(path) => String::from(path.as_str()),
-        None => return Err(Error::UnknownInternalError("invalid path nginx logs".to_string())),
+    let method = match Method::from_str(access_log_line.method) {
+        Some(method) => method,
+        None => return Err(Error::InvalidParseMethod(access_log_line.method.to_string())),
     };
-
-    let port = match captures.get(5) {
-        Some(port_string) => match port_string.as_str().parse() {
-            Ok(port) => port,
-            Err(_) => return Err(Error::UnknownInternalError("invalid port nginx logs".to_string())),
-        },
-        None => return Err(Error::UnknownInternalError("no port number nginx logs".to_string())),
+    let port = match access_log_line.port.parse() {
+        Ok(port) => port,
+        Err(_) => return Err(Error::InvalidParseSyntax),
     };
 
-    Ok(EndpointConfiguration::new(
+    // the request target still contains the query string, the observation splits it off,
+    // nginx can't log response bodies, so only their content type is known
+    Ok(Observation::new(
         method,
-        &path,
+        &access_log_line.request_target,
         status,
         find_runtime_by_port(runtimes, port)?,
-        false
-    )?)
+    )?
+    .with_request_body(&access_log_line.request_body())
+    .with_request_headers(access_log_line.request_content_type(), access_log_line.accept())
+    .with_request_header_values(access_log_line.request_headers())
+    .with_response(access_log_line.response_content_type(), &[])
+    .with_timestamp(parse_nginx_time(access_log_line.time)))
 }
 
-fn find_runtime_by_port(runtimes: &Vec<Arc<Runtime>>, port: u16) -> Result<Arc<Runtime>, Error> {
+pub(super) fn find_runtime_by_port(runtimes: &Vec<Arc<Runtime>>, port: u16) -> Result<Arc<Runtime>, Error> {
     for runtime in runtimes {
         if runtime.port == port {
             return Ok(runtime.clone())
         }
     }
-    Err(Error::UnknownInternalError("unknown port in nginx logs".to_string()))
+    Err(Error::UnknownRuntimePort(port))
 }
 
 #[cfg(test)]
 mod test {
-    use std::{path::Path, str::FromStr, sync::Arc};
+    use std::{env, fs, path::Path, str::FromStr, sync::Arc};
 
     use reqwest::Url;
 
     use crate::{
         config::{OpenapiSource, Runtime},
-        models::{Method, OpenapiPath},
+        models::{Hits, Method, OpenapiPath},
         parser::nginx_parser::{parse_access_log, parse_nginx_line},
+        utils::Error,
     };
 
     use super::find_runtime_by_port;
@@ -152,12 +144,14 @@ mod test {
         assert_eq!(
             parse_nginx_line(&generate_runtimes(), "[11/Jul/2022:08:50:03 +0000] \"GET /weather HTTP/1.1\" 200 8080")
                 .unwrap()
+                .endpoint
                 .status_code,
             200
         );
         assert_eq!(
             parse_nginx_line(&generate_runtimes(), "[11/Jul/2022:08:52:45 +0000] \"GET /usus HTTP/1.1\" 404 8080")
                 .unwrap()
+                .endpoint
                 .status_code,
             404
         );
@@ -168,34 +162,52 @@ mod test {
         assert_eq!(
             parse_nginx_line(&generate_runtimes(), "[11/Jul/2022:08:50:03 +0000] \"GET /weather HTTP/1.1\" 200 8080")
                 .unwrap()
+                .endpoint
                 .method,
             Method::GET
         );
         assert_eq!(
             parse_nginx_line(&generate_runtimes(), "[11/Jul/2022:08:50:03 +0000] \"POST /weather HTTP/1.1\" 200 8080")
                 .unwrap()
+                .endpoint
                 .method,
             Method::POST
         );
     }
 
+    #[test]
+    fn parses_every_method_and_absolute_targets() {
+        let observation =
+            parse_nginx_line(&generate_runtimes(), "[11/Jul/2022:08:50:03 +0000] \"DELETE http://localhost:8080/pets/1 HTTP/2.0\" 204 8080").unwrap();
+        assert_eq!(observation.endpoint.method, Method::DELETE);
+        assert_eq!(observation.endpoint.path, OpenapiPath::from_str("/pets/1").unwrap());
+
+        assert!(matches!(
+            parse_nginx_line(&generate_runtimes(), "[11/Jul/2022:08:50:03 +0000] \"PROPFIND /dav HTTP/1.1\" 405 8080"),
+            Err(Error::InvalidParseMethod(_))
+        ));
+    }
+
     #[test]
     fn parses_correct_path() {
         assert_eq!(
             parse_nginx_line(&generate_runtimes(), "[11/Jul/2022:08:50:03 +0000] \"GET /weather HTTP/1.1\" 200 8080")
                 .unwrap()
+                .endpoint
                 .path,
             OpenapiPath::from_str("/weather").unwrap(),
         );
         assert_eq!(
             parse_nginx_line(&generate_runtimes(), "[11/Jul/2022:08:52:45 +0000] \"GET /usus HTTP/1.1\" 404 8080")
                 .unwrap()
+                .endpoint
                 .path,
             OpenapiPath::from_str("/usus").unwrap(),
         );
         assert_eq!(
             parse_nginx_line(&generate_runtimes(), "[11/Jul/2022:08:52:45 +0000] \"GET / HTTP/1.1\" 404 8080")
                 .unwrap()
+                .endpoint
                 .path,
             OpenapiPath::from_str("/").unwrap(),
         );
@@ -206,6 +218,7 @@ mod test {
         assert_eq!(
             parse_nginx_line(&generate_runtimes(), "[11/Jul/2022:08:50:03 +0000] \"GET /weather HTTP/1.1\" 200 8080")
                 .unwrap()
+                .endpoint
                 .runtime
                 .port,
             8080
@@ -213,23 +226,113 @@ mod test {
         assert_eq!(
             parse_nginx_line(&generate_runtimes(), "[11/Jul/2022:08:50:03 +0000] \"POST /weather HTTP/1.1\" 200 13750")
                 .unwrap()
+                .endpoint
                 .runtime
                 .port,
             13750
         );
     }
 
+    #[test]
+    fn strips_query_from_path() {
+        let observation =
+            parse_nginx_line(&generate_runtimes(), "[11/Jul/2022:08:50:03 +0000] \"GET /weather?city=New+York&days=3 HTTP/1.1\" 200 8080")
+                .unwrap();
+
+        assert_eq!(observation.endpoint.path, OpenapiPath::from_str("/weather").unwrap());
+        assert_eq!(observation.get_query_parameter("city"), Some("New York"));
+        assert_eq!(observation.get_query_parameter("days"), Some("3"));
+    }
+
+    #[test]
+    fn decodes_percent_encoded_path() {
+        assert_eq!(
+            parse_nginx_line(&generate_runtimes(), "[11/Jul/2022:08:50:03 +0000] \"GET /cities/S%C3%A3o%20Paulo HTTP/1.1\" 200 8080")
+                .unwrap()
+                .endpoint
+                .path,
+            OpenapiPath::from_str("/cities/São Paulo").unwrap(),
+        );
+    }
+
+    #[test]
+    fn parses_logged_request_body() {
+        let observation = parse_nginx_line(
+            &generate_runtimes(),
+            "[11/Jul/2022:08:50:03 +0000] \"POST /pets HTTP/1.1\" 201 8080 \"{\\x22name\\x22: \\x22Rex\\x22}\"",
+        )
+        .unwrap();
+        assert_eq!(observation.request_bodies.values[0].0["name"], "Rex");
+
+        let observation =
+            parse_nginx_line(&generate_runtimes(), "[11/Jul/2022:08:50:03 +0000] \"GET /pets HTTP/1.1\" 200 8080 \"-\"").unwrap();
+        assert_eq!(observation.request_bodies.count(), 0);
+    }
+
+    #[test]
+    fn parses_logged_response_content_type() {
+        let observation = parse_nginx_line(
+            &generate_runtimes(),
+            "[11/Jul/2022:08:50:03 +0000] \"GET /pets HTTP/1.1\" 200 8080 \"-\" \"application/json; charset=utf-8\"",
+        )
+        .unwrap();
+        assert_eq!(observation.content_types.values[0].0.response.as_deref(), Some("application/json; charset=utf-8"));
+        assert_eq!(observation.response_bodies.count(), 0);
+
+        let observation =
+            parse_nginx_line(&generate_runtimes(), "[11/Jul/2022:08:50:03 +0000] \"GET /pets HTTP/1.1\" 204 8080 \"-\" \"-\"").unwrap();
+        assert_eq!(observation.content_types.values[0].0.response, None);
+    }
+
+    #[test]
+    fn parses_logged_request_headers() {
+        let observation = parse_nginx_line(
+            &generate_runtimes(),
+            "[11/Jul/2022:08:50:03 +0000] \"POST /pets HTTP/1.1\" 201 8080 \"-\" \"text/csv\" \"application/xml\" \"text/csv, */*;q=0.1\"",
+        )
+        .unwrap();
+
+        assert_eq!(observation.content_types.values[0].0.request.as_deref(), Some("application/xml"));
+        assert_eq!(observation.content_types.values[0].0.accept.as_deref(), Some("text/csv, */*;q=0.1"));
+    }
+
     #[test]
     fn parses_full_access_log() {
         let path = Path::new("./test/resource/access.log");
+        let observations = parse_access_log(&generate_runtimes(), path, 0.0).unwrap();
+
+        assert_eq!(observations.len(), 5);
+        assert_eq!(observations.iter().map(|x| x.hits.count).sum::<usize>(), 9);
         assert_eq!(
-            parse_access_log(&generate_runtimes(), &path)
-                .unwrap()
-                .len(),
-            9
+            observations[0].hits,
+            Hits {
+                count: 3,
+                first_seen: Some(1657529403),
+                last_seen: Some(1657529544),
+            }
         );
     }
 
+    #[test]
+    fn skips_rejected_lines_within_limit() {
+        let path = env::temp_dir().join(format!("rudra-rejected-lines-{}.log", std::process::id()));
+        let mut log = String::new();
+        for _ in 0..7 {
+            log.push_str("[11/Jul/2022:08:50:03 +0000] \"GET /weather HTTP/1.1\" 200 8080\n");
+        }
+        log.push_str("[11/Jul/2022:08:50:03 +0000] \"\\x16\\x03\\x01\\x00\\xF4\\x01\" 400 8080\n");
+        log.push_str("[11/Jul/2022:08:50:03 +0000] \"-\" 400 8080\n");
+        log.push_str("[11/Jul/2022:08:50:03 +0000] \"GET /weather HTTP/1.1\" 200 9090\n");
+        fs::write(&path, log).unwrap();
+
+        let observations = parse_access_log(&generate_runtimes(), &path, 0.3);
+        let rejected = parse_access_log(&generate_runtimes(), &path, 0.2);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(observations.unwrap()[0].hits.count, 7);
+        assert!(matches!(rejected, Err(Error::TooManyRejectedLines(3, 10, _))));
+    }
+
     #[test]
     fn finds_runtime_by_port() {
         let runtimes = vec![
@@ -258,4 +361,17 @@ mod test {
         let runtimes = vec![];
         assert!(find_runtime_by_port(&runtimes, 7890).is_err());
     }
+
+    #[test]
+    fn parses_logged_header_parameters() {
+        let observation = parse_nginx_line(
+            &generate_runtimes(),
+            "[11/Jul/2022:08:50:03 +0000] \"GET /pets HTTP/1.1\" 200 8080 \"-\" \"-\" \"-\" \"-\" \"x-tenant-id: acme\\x22s\" \"if-match: -\" \"x-api-key: <redacted>\"",
+        )
+        .unwrap();
+
+        assert_eq!(observation.request_headers.len(), 2);
+        assert_eq!(observation.get_request_header("x-tenant-id"), Some("acme\"s"));
+        assert_eq!(observation.get_request_header("x-api-key"), Some("<redacted>"));
+    }
 }
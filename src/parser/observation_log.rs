@@ -0,0 +1,219 @@
+use std::{fs, path::Path, sync::Arc};
+
+use json::{object, JsonValue};
+
+use crate::{
+    config::Runtime,
+    models::{ContentTypes, EndpointConfiguration, Hits, Method, Observation, ObservedParameter, ResponseBody, StatusMatcher, Variants},
+    utils::Error,
+};
+
+use super::nginx_parser::find_runtime_by_port;
+
+// observations of the native proxy are written as one json object per line, nginx lines never start with "{"
+pub fn is_observation_line(line: &str) -> bool {
+    line.trim_start().starts_with('{')
+}
+
+pub fn write_observation_log(observations: &[Observation], path: &Path) -> Result<(), Error> {
+    let mut log = String::new();
+    for observation in observations {
+        log.push_str(&observation_to_json(observation).dump());
+        log.push('\n');
+    }
+    match fs::write(path, log) {
+        Ok(_) => Ok(()),
+        Err(why) => Err(Error::UnexpectedIOIssue(format!(
+            "issue writing access log {:?} due to: {}",
+            path, why
+        ))),
+    }
+}
+
+// every line contains the aggregated requests to one endpoint, the path is stored decoded and the query already split
+// into parameters, so nothing is decoded twice, lines without hits count once
+pub fn parse_observation_line(runtimes: &Vec<Arc<Runtime>>, line: &str) -> Result<Observation, Error> {
+    let logged = match json::parse(line) {
+        Ok(logged) => logged,
+        Err(_) => return Err(Error::InvalidParseSyntax),
+    };
+
+    let method = match logged["method"].as_str().and_then(Method::from_str) {
+        Some(method) => method,
+        None => return Err(Error::InvalidParseMethod(logged["method"].to_string())),
+    };
+    let status_code = match logged["status"].as_u16() {
+        Some(status_code) => status_code,
+        None => return Err(Error::InvalidParseStatusCode(logged["status"].to_string())),
+    };
+    let port = match logged["port"].as_u16() {
+        Some(port) => port,
+        None => return Err(Error::InvalidParseSyntax),
+    };
+    let path = match logged["path"].as_str() {
+        Some(path) => path,
+        None => return Err(Error::InvalidParseSyntax),
+    };
+
+    Ok(Observation {
+        endpoint: EndpointConfiguration::new(method, path, status_code, find_runtime_by_port(runtimes, port)?, false)?,
+        hits: Hits {
+            count: logged["hits"].as_usize().unwrap_or(1),
+            first_seen: logged["first_seen"].as_u64(),
+            last_seen: logged["last_seen"].as_u64(),
+        },
+        query_parameters: get_parameters(&logged["query_parameters"]),
+        request_headers: get_parameters(&logged["request_headers"]),
+        request_bodies: get_variants(&logged["request_bodies"], |body| Some(body.clone())),
+        content_types: get_variants(&logged["content_types"], |content_types| {
+            Some(ContentTypes {
+                request: get_string(&content_types["request"]),
+                accept: get_string(&content_types["accept"]),
+                response: get_string(&content_types["response"]),
+            })
+        }),
+        response_bodies: get_variants(&logged["response_bodies"], |response_body| {
+            Some(ResponseBody {
+                content_type: response_body["content_type"].as_str()?.to_string(),
+                body: response_body["body"].clone(),
+            })
+        }),
+    })
+}
+
+fn observation_to_json(observation: &Observation) -> JsonValue {
+    let endpoint = &observation.endpoint;
+    // observations always have an exact status code
+    let status_code = match endpoint.status_code {
+        StatusMatcher::Exact(status_code) => JsonValue::from(status_code),
+        _ => JsonValue::Null,
+    };
+    object! {
+        port: endpoint.runtime.port,
+        method: endpoint.method.as_str(),
+        path: endpoint.path.to_string(),
+        status: status_code,
+        hits: observation.hits.count,
+        first_seen: observation.hits.first_seen,
+        last_seen: observation.hits.last_seen,
+        query_parameters: parameters_to_json(&observation.query_parameters),
+        request_headers: parameters_to_json(&observation.request_headers),
+        request_bodies: variants_to_json(&observation.request_bodies, |body| body.clone()),
+        content_types: variants_to_json(&observation.content_types, |content_types| object! {
+            request: content_types.request.as_deref(),
+            accept: content_types.accept.as_deref(),
+            response: content_types.response.as_deref(),
+        }),
+        response_bodies: variants_to_json(&observation.response_bodies, |response_body| object! {
+            content_type: response_body.content_type.as_str(),
+            body: response_body.body.clone(),
+        }),
+    }
+}
+
+// values are logged as [value, count] pairs, the count of the values which weren't kept separately
+fn variants_to_json<T>(variants: &Variants<T>, to_json: impl Fn(&T) -> JsonValue) -> JsonValue {
+    object! {
+        values: JsonValue::Array(variants.iter().map(|(value, count)| JsonValue::from(vec![to_json(value), count.into()])).collect()),
+        others: variants.others,
+    }
+}
+
+fn get_variants<T: PartialEq>(logged_variants: &JsonValue, from_json: impl Fn(&JsonValue) -> Option<T>) -> Variants<T> {
+    let mut variants = Variants::default();
+    for logged_value in logged_variants["values"].members() {
+        if let (Some(value), Some(count)) = (from_json(&logged_value[0]), logged_value[1].as_usize()) {
+            variants.add(value, count);
+        }
+    }
+    variants.others = logged_variants["others"].as_usize().unwrap_or(0);
+    variants
+}
+
+fn parameters_to_json(parameters: &[ObservedParameter]) -> JsonValue {
+    JsonValue::Array(
+        parameters
+            .iter()
+            .map(|parameter| object! {
+                name: parameter.name.as_str(),
+                requests: parameter.requests,
+                values: variants_to_json(&parameter.values, |value| value.as_str().into()),
+            })
+            .collect(),
+    )
+}
+
+fn get_parameters(logged_parameters: &JsonValue) -> Vec<ObservedParameter> {
+    logged_parameters
+        .members()
+        .filter_map(|parameter| {
+            Some(ObservedParameter {
+                name: parameter["name"].as_str()?.to_string(),
+                requests: parameter["requests"].as_usize()?,
+                values: get_variants(&parameter["values"], get_string),
+            })
+        })
+        .collect()
+}
+
+fn get_string(logged_value: &JsonValue) -> Option<String> {
+    logged_value.as_str().map(|value| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, sync::Arc};
+
+    use crate::{
+        models::{Hits, Method, Observation},
+        parser::parse_access_log,
+        utils::test::create_mock_runtime,
+    };
+
+    use super::{parse_observation_line, write_observation_log};
+
+    #[test]
+    fn writes_and_reads_observations() {
+        let runtimes = vec![Arc::new(create_mock_runtime())];
+        let mut observations = vec![
+            Observation::new(Method::GET, "/cities/S%C3%A3o%20Paulo?days=3&q=a%2526b", 200, runtimes[0].clone()).unwrap(),
+            Observation::new(Method::POST, "/pets", 201, runtimes[0].clone())
+                .unwrap()
+                .with_request_body(br#"{"name": "Rex"}"#)
+                .with_request_headers(Some("application/json"), Some("*/*"))
+                .with_request_header_values(vec![("X-Tenant-Id".to_string(), "acme".to_string())])
+                .with_response(Some("application/json"), br#"{"id": 1}"#)
+                .with_timestamp(Some(1709209815)),
+        ];
+        for (name, timestamp) in [("Bo", 1709209875), ("Rex", 1709209845)] {
+            observations[1].add(
+                Observation::new(Method::POST, "/pets", 201, runtimes[0].clone())
+                    .unwrap()
+                    .with_request_body(format!(r#"{{"name": "{}"}}"#, name).as_bytes())
+                    .with_request_headers(Some("application/json"), Some("*/*"))
+                    .with_timestamp(Some(timestamp)),
+            );
+        }
+        let path = env::temp_dir().join(format!("rudra-observations-{}.log", std::process::id()));
+
+        write_observation_log(&observations, &path).unwrap();
+        let parsed_observations = parse_access_log(&runtimes, &path, 0.0).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(parsed_observations, observations);
+        assert_eq!(parsed_observations[0].get_query_parameter("q"), Some("a%26b"));
+        assert_eq!(parsed_observations[1].hits, Hits { count: 3, first_seen: Some(1709209815), last_seen: Some(1709209875) });
+    }
+
+    #[test]
+    fn rejects_incomplete_observations() {
+        let runtimes = vec![Arc::new(create_mock_runtime())];
+        assert_eq!(
+            parse_observation_line(&runtimes, r#"{"port": 8080, "method": "GET", "path": "/pets", "status": 200}"#).unwrap().hits,
+            Hits::once(None)
+        );
+        assert!(parse_observation_line(&runtimes, r#"{"port": 8080, "method": "FOO", "path": "/pets", "status": 200}"#).is_err());
+        assert!(parse_observation_line(&runtimes, r#"{"port": 9090, "method": "GET", "path": "/pets", "status": 200}"#).is_err());
+        assert!(parse_observation_line(&runtimes, r#"{"port": 8080, "method": "GET", "path": "/pets""#).is_err());
+    }
+}
@@ -0,0 +1,326 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+};
+
+use json::JsonValue;
+
+use crate::utils::{percent_decode, read_file_to_string_or_err, Error};
+
+use super::yaml_parser::load_yaml_as_json;
+
+const REFERENCE_KEY: &str = "$ref";
+
+// a value of the spec together with the document it was found in, nested references are relative to that document
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpecValue {
+    pub value: JsonValue,
+    pub document: Option<PathBuf>,
+}
+
+// a spec read from the repository, the files it references have to be inside of the repository as well
+#[derive(Debug, Clone, Copy)]
+pub struct SpecLocation<'a> {
+    pub path: &'a Path,
+    pub repository: &'a Path,
+}
+
+pub struct ReferenceResolver {
+    spec_location: Option<PathBuf>,
+    root: Option<PathBuf>,
+    documents: RefCell<HashMap<Option<PathBuf>, Rc<JsonValue>>>,
+}
+
+impl ReferenceResolver {
+    // specs fetched from an url have no location, so only references within the spec can be resolved,
+    // specs outside of the repository can only reference files next to or below them
+    pub fn new(spec: &JsonValue, spec_location: Option<SpecLocation>) -> ReferenceResolver {
+        let (spec_location, root) = match spec_location {
+            Some(spec_location) => {
+                let path = canonicalize_path(spec_location.path);
+                let repository = canonicalize_path(spec_location.repository);
+                let root = match path.starts_with(&repository) {
+                    true => repository,
+                    false => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+                };
+                (Some(path), Some(root))
+            }
+            None => (None, None),
+        };
+        let mut documents = HashMap::new();
+        documents.insert(spec_location.clone(), Rc::new(spec.clone()));
+        ReferenceResolver {
+            spec_location,
+            root,
+            documents: RefCell::new(documents),
+        }
+    }
+
+    pub fn get_spec_location(&self) -> &Option<PathBuf> {
+        &self.spec_location
+    }
+
+    // follows references until a value without a reference is found
+    pub fn resolve(&self, value: &JsonValue, document: &Option<PathBuf>) -> Result<SpecValue, Error> {
+        let mut visited_references: Vec<(Option<PathBuf>, String)> = vec![];
+        let mut current = SpecValue {
+            value: value.clone(),
+            document: document.clone(),
+        };
+
+        loop {
+            let reference = match current.value[REFERENCE_KEY].as_str() {
+                Some(reference) => reference.to_string(),
+                None => return Ok(current),
+            };
+
+            let (file, pointer) = match reference.split_once('#') {
+                Some((file, pointer)) => (file, pointer.to_string()),
+                None => (reference.as_str(), String::new()),
+            };
+            let target_document = self.get_target_document(&reference, file, &current.document)?;
+
+            let visited_reference = (target_document.clone(), pointer.clone());
+            if visited_references.contains(&visited_reference) {
+                return Err(Error::UnresolvedReference(reference, "the reference is cyclic".to_string()));
+            }
+            visited_references.push(visited_reference);
+
+            let target_json = self.load_document(&reference, &target_document)?;
+            let target_value = match follow_pointer(&target_json, &pointer) {
+                Some(target_value) => target_value.clone(),
+                None => return Err(Error::UnresolvedReference(reference, "the target doesn't exist".to_string())),
+            };
+            current = SpecValue {
+                value: target_value,
+                document: target_document,
+            };
+        }
+    }
+
+    fn load_document(&self, reference: &str, document: &Option<PathBuf>) -> Result<Rc<JsonValue>, Error> {
+        if let Some(document_json) = self.documents.borrow().get(document) {
+            return Ok(document_json.clone());
+        }
+
+        // only the spec itself is stored without a path
+        let path = match document {
+            Some(path) => path,
+            None => return Err(Error::UnknownInternalError("referenced document without path".to_string())),
+        };
+        let unresolved_error = |why: &str| Error::UnresolvedReference(reference.to_string(), format!("{} {:?}", why, path));
+
+        let document_string = read_file_to_string_or_err(path, unresolved_error("can't read file"))?;
+        let document_json = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => match json::parse(&document_string) {
+                Ok(document_json) => document_json,
+                Err(_) => return Err(unresolved_error("invalid json syntax in")),
+            },
+            Some("yaml") | Some("yml") => match load_yaml_as_json(&document_string) {
+                Ok(document_json) => document_json,
+                Err(_) => return Err(unresolved_error("invalid yaml syntax in")),
+            },
+            _ => return Err(unresolved_error("only json and yaml files can be referenced, not")),
+        };
+
+        let document_json = Rc::new(document_json);
+        self.documents.borrow_mut().insert(document.clone(), document_json.clone());
+        Ok(document_json)
+    }
+
+    fn get_target_document(&self, reference: &str, file: &str, document: &Option<PathBuf>) -> Result<Option<PathBuf>, Error> {
+        if file.is_empty() {
+            return Ok(document.clone());
+        }
+        if file.contains("://") {
+            return Err(Error::UnresolvedReference(reference.to_string(), "remote references aren't supported".to_string()));
+        }
+
+        let file = Path::new(file);
+        if file.is_absolute() {
+            return Err(Error::UnresolvedReference(reference.to_string(), "only relative files can be referenced".to_string()));
+        }
+        let (document, root) = match (document, &self.root) {
+            (Some(document), Some(root)) => (document, root),
+            _ => {
+                return Err(Error::UnresolvedReference(
+                    reference.to_string(),
+                    "files can only be referenced by openapi specs read from the repository".to_string(),
+                ))
+            }
+        };
+
+        // symbolic links are resolved as well, so they can't point outside of the repository either
+        let directory = document.parent().unwrap_or_else(|| Path::new(""));
+        let target_document = canonicalize_path(&directory.join(file));
+        if !target_document.starts_with(root) {
+            return Err(Error::UnresolvedReference(
+                reference.to_string(),
+                format!("{:?} is outside of the repository", target_document),
+            ));
+        }
+        Ok(Some(target_document))
+    }
+}
+
+fn follow_pointer<'a>(document: &'a JsonValue, pointer: &str) -> Option<&'a JsonValue> {
+    if pointer.is_empty() {
+        return Some(document);
+    }
+    let pointer = pointer.strip_prefix('/')?;
+
+    let mut current = document;
+    for token in pointer.split('/') {
+        // order matters, "~01" has to become "~1"
+        let token = percent_decode(token).replace("~1", "/").replace("~0", "~");
+        current = match current {
+            JsonValue::Object(object) => object.get(&token)?,
+            JsonValue::Array(array) => array.get(token.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+// files which don't exist can't be canonicalized, they are only normalized and fail to be read later on
+fn canonicalize_path(path: &Path) -> PathBuf {
+    match fs::canonicalize(path) {
+        Ok(path) => path,
+        Err(_) => normalize_path(path),
+    }
+}
+
+// removes "." and ".." lexically, so the same file is always stored under the same path
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir) => (),
+                _ => normalized.push(".."),
+            },
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use json::object;
+
+    use crate::utils::Error;
+
+    use super::{follow_pointer, normalize_path, ReferenceResolver, SpecLocation};
+
+    #[test]
+    fn resolves_local_references() {
+        let spec = object! {
+            paths: { "/foo": { "$ref": "#/x-paths/foo" } },
+            "x-paths": { foo: { get: { responses: { "200": { description: "OK" } } } } },
+        };
+        let resolver = ReferenceResolver::new(&spec, None);
+
+        let path_item = resolver.resolve(&spec["paths"]["/foo"], &None).unwrap();
+
+        assert_eq!(path_item.value, spec["x-paths"]["foo"]);
+    }
+
+    #[test]
+    fn resolves_chained_references() {
+        let spec = object! {
+            responses: { NotFound: { "$ref": "#/responses/Missing" }, Missing: { description: "Not Found" } },
+            response: { "$ref": "#/responses/NotFound" },
+        };
+        let resolver = ReferenceResolver::new(&spec, None);
+
+        assert_eq!(resolver.resolve(&spec["response"], &None).unwrap().value["description"], "Not Found");
+    }
+
+    #[test]
+    fn detects_cyclic_references() {
+        let spec = object! {
+            a: { "$ref": "#/b" },
+            b: { "$ref": "#/a" },
+        };
+        let resolver = ReferenceResolver::new(&spec, None);
+
+        assert!(matches!(resolver.resolve(&spec["a"], &None), Err(Error::UnresolvedReference(_, _))));
+    }
+
+    #[test]
+    fn missing_target_leads_to_error() {
+        let spec = object! { a: { "$ref": "#/responses/NotFound" } };
+        let resolver = ReferenceResolver::new(&spec, None);
+
+        assert!(matches!(resolver.resolve(&spec["a"], &None), Err(Error::UnresolvedReference(_, _))));
+    }
+
+    #[test]
+    fn file_references_require_spec_location() {
+        let spec = object! { a: { "$ref": "other.yaml#/paths/~1users" } };
+        let resolver = ReferenceResolver::new(&spec, None);
+
+        assert!(matches!(resolver.resolve(&spec["a"], &None), Err(Error::UnresolvedReference(_, _))));
+    }
+
+    #[test]
+    fn resolves_relative_file_references() {
+        let spec = object! { a: { "$ref": "./../references/components.yaml#/paths/~1users" } };
+        let spec_location = SpecLocation {
+            path: Path::new("./test/resource/references/openapi.json"),
+            repository: Path::new("."),
+        };
+        let resolver = ReferenceResolver::new(&spec, Some(spec_location));
+
+        let path_item = resolver.resolve(&spec["a"], resolver.get_spec_location()).unwrap();
+
+        assert!(path_item.value["get"]["responses"]["200"].is_object());
+        assert_eq!(path_item.document, Some(fs::canonicalize("test/resource/references/components.yaml").unwrap()));
+    }
+
+    #[test]
+    fn rejects_file_references_outside_of_repository() {
+        let spec = object! {
+            a: { "$ref": "../../../../../../../../etc/passwd.yaml" },
+            b: { "$ref": "../../openapi.yaml" },
+        };
+        let spec_location = SpecLocation {
+            path: Path::new("./test/resource/references/openapi.json"),
+            repository: Path::new("./test/resource"),
+        };
+        let resolver = ReferenceResolver::new(&spec, Some(spec_location));
+
+        for key in ["a", "b"] {
+            match resolver.resolve(&spec[key], resolver.get_spec_location()) {
+                Err(Error::UnresolvedReference(_, why)) => assert!(why.ends_with("is outside of the repository"), "{}", why),
+                result => panic!("{:?} was resolved to {:?}", key, result),
+            }
+        }
+    }
+
+    #[test]
+    fn follows_escaped_pointers() {
+        let document = object! { paths: { "/users/{id}": { "a~b": [1, 2] } } };
+
+        assert_eq!(follow_pointer(&document, "/paths/~1users~1{id}/a~0b/1").unwrap(), 2);
+        assert!(follow_pointer(&document, "/paths/~1users/a~0b/5").is_none());
+        assert!(follow_pointer(&document, "paths").is_none());
+        assert_eq!(follow_pointer(&document, "").unwrap(), &document);
+    }
+
+    #[test]
+    fn normalizes_paths() {
+        assert_eq!(normalize_path(Path::new("/repo/./docs/../api/x.yaml")), Path::new("/repo/api/x.yaml"));
+        assert_eq!(normalize_path(Path::new("../a/b")), Path::new("../a/b"));
+    }
+}
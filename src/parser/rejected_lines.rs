@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+
+use crate::utils::{print_debug_message, Error};
+
+// only the first few rejected lines are kept, so a broken log doesn't end up in memory
+const MAX_REJECTED_LINE_SAMPLES: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RejectionReason {
+    MalformedRequest,
+    UnknownMethod,
+    InvalidStatusCode,
+    UnknownPort,
+    InvalidPath,
+}
+
+// lines of an access log which were skipped, counted by the reason they couldn't be parsed
+#[derive(Debug, Default)]
+pub struct RejectedLines {
+    counts: BTreeMap<RejectionReason, usize>,
+    samples: Vec<(RejectionReason, String)>,
+}
+
+impl RejectionReason {
+    fn from_error(error: &Error) -> RejectionReason {
+        match error {
+            Error::InvalidParseMethod(_) => RejectionReason::UnknownMethod,
+            Error::InvalidParseStatusCode(_) => RejectionReason::InvalidStatusCode,
+            Error::UnknownRuntimePort(_) => RejectionReason::UnknownPort,
+            Error::InvalidPath(_) => RejectionReason::InvalidPath,
+            _ => RejectionReason::MalformedRequest,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            RejectionReason::MalformedRequest => "malformed request",
+            RejectionReason::UnknownMethod => "unknown method",
+            RejectionReason::InvalidStatusCode => "invalid status code",
+            RejectionReason::UnknownPort => "unknown port",
+            RejectionReason::InvalidPath => "invalid path",
+        }
+    }
+}
+
+impl RejectedLines {
+    pub fn add(&mut self, error: &Error, line: &str) {
+        let reason = RejectionReason::from_error(error);
+        *self.counts.entry(reason).or_insert(0) += 1;
+        if self.samples.len() < MAX_REJECTED_LINE_SAMPLES {
+            self.samples.push((reason, line.to_string()));
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    // e.g. "3 malformed request, 1 unknown port"
+    pub fn summary(&self) -> String {
+        self.counts
+            .iter()
+            .map(|(reason, count)| format!("{} {}", count, reason.as_str()))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    // skipping lines is fine, as long as they make up at most the given share of the log
+    pub fn check_limit(&self, lines: usize, rejected_lines_limit: f32) -> Result<(), Error> {
+        let rejected = self.total();
+        if rejected == 0 {
+            return Ok(());
+        }
+        if rejected as f32 > lines as f32 * rejected_lines_limit {
+            return Err(Error::TooManyRejectedLines(rejected, lines, self.summary()));
+        }
+        println!("WARNING: skipped {} of {} lines of the access log: {}", rejected, lines, self.summary());
+        Ok(())
+    }
+
+    pub fn print_samples(&self) {
+        if self.samples.is_empty() {
+            return;
+        }
+        print_debug_message("Rejected lines of the access log:");
+        for (reason, line) in &self.samples {
+            print_debug_message(format!(" - {}: {}", reason.as_str(), line));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::Error;
+
+    use super::{RejectedLines, RejectionReason, MAX_REJECTED_LINE_SAMPLES};
+
+    #[test]
+    fn counts_rejected_lines_by_reason() {
+        let mut rejected_lines = RejectedLines::default();
+        for _ in 0..MAX_REJECTED_LINE_SAMPLES {
+            rejected_lines.add(&Error::InvalidParseSyntax, "\\x16\\x03\\x01");
+        }
+        rejected_lines.add(&Error::InvalidParseMethod("FOO".to_string()), "[11/Jul/2022:08:50:03 +0000] \"FOO / HTTP/1.1\" 200 8080");
+        rejected_lines.add(&Error::UnknownRuntimePort(9090), "[11/Jul/2022:08:50:03 +0000] \"GET / HTTP/1.1\" 200 9090");
+
+        assert_eq!(rejected_lines.total(), 7);
+        assert_eq!(rejected_lines.counts[&RejectionReason::MalformedRequest], 5);
+        assert_eq!(rejected_lines.counts[&RejectionReason::UnknownPort], 1);
+        assert_eq!(rejected_lines.samples.len(), MAX_REJECTED_LINE_SAMPLES);
+        assert_eq!(rejected_lines.summary(), "5 malformed request, 1 unknown method, 1 unknown port");
+    }
+
+    #[test]
+    fn only_fails_if_limit_is_exceeded() {
+        let mut rejected_lines = RejectedLines::default();
+        assert!(rejected_lines.check_limit(0, 0.0).is_ok());
+
+        rejected_lines.add(&Error::InvalidParseSyntax, "-");
+        assert!(rejected_lines.check_limit(100, 0.01).is_ok());
+        assert!(rejected_lines.check_limit(100, 0.0).is_err());
+        assert!(rejected_lines.check_limit(50, 0.01).is_err());
+    }
+}
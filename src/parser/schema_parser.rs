@@ -0,0 +1,201 @@
+use std::path::PathBuf;
+
+use json::JsonValue;
+
+use crate::{
+    models::{json_to_enum_value, Discriminator, Schema},
+    utils::Error,
+};
+
+use super::reference::ReferenceResolver;
+
+const REFERENCE_KEY: &str = "$ref";
+// deeply nested schemas are cut off, the remaining part accepts any value
+const MAX_SCHEMA_DEPTH: usize = 32;
+const TYPE_NULL: &str = "null";
+
+pub fn parse_schema(
+    schema_json: &JsonValue,
+    resolver: &ReferenceResolver,
+    document: &Option<PathBuf>,
+) -> Result<Schema, Error> {
+    parse_schema_with_stack(schema_json, resolver, document, &mut vec![])
+}
+
+// the stack contains the references currently being parsed, so recursive schemas end
+fn parse_schema_with_stack(
+    schema_json: &JsonValue,
+    resolver: &ReferenceResolver,
+    document: &Option<PathBuf>,
+    stack: &mut Vec<(Option<PathBuf>, String)>,
+) -> Result<Schema, Error> {
+    if stack.len() >= MAX_SCHEMA_DEPTH {
+        return Ok(Schema::default());
+    }
+
+    let reference = schema_json[REFERENCE_KEY].as_str().map(|reference| (document.clone(), reference.to_string()));
+    if let Some(reference) = &reference {
+        if stack.contains(reference) {
+            return Ok(Schema::default());
+        }
+    }
+    let resolved = resolver.resolve(schema_json, document)?;
+    let schema_json = &resolved.value;
+    let document = &resolved.document;
+
+    stack.push(reference.clone().unwrap_or_else(|| (document.clone(), String::new())));
+    let schema = parse_resolved_schema(schema_json, reference.as_ref().map(|x| x.1.as_str()), resolver, document, stack);
+    stack.pop();
+    schema
+}
+
+fn parse_resolved_schema(
+    schema_json: &JsonValue,
+    reference: Option<&str>,
+    resolver: &ReferenceResolver,
+    document: &Option<PathBuf>,
+    stack: &mut Vec<(Option<PathBuf>, String)>,
+) -> Result<Schema, Error> {
+    let name = match reference {
+        Some(reference) => Some(get_schema_name(reference)),
+        None => schema_json["title"].as_str().map(|title| title.to_string()),
+    };
+
+    // openapi 3.1 allows a list of types
+    let mut schema_types: Vec<String> = match &schema_json["type"] {
+        JsonValue::Array(schema_types) => schema_types.iter().filter_map(|x| x.as_str()).map(|x| x.to_string()).collect(),
+        schema_type => schema_type.as_str().map(|x| vec![x.to_string()]).unwrap_or_default(),
+    };
+    // openapi 3.0 and swagger 2.0 (as an extension) mark nullable values with a flag instead
+    let is_nullable = schema_json["nullable"].as_bool() == Some(true) || schema_json["x-nullable"].as_bool() == Some(true);
+    if is_nullable && !schema_types.is_empty() {
+        schema_types.push(TYPE_NULL.to_string());
+    }
+    let enum_values = schema_json["enum"].members().map(json_to_enum_value).collect();
+
+    let mut properties = vec![];
+    for (property_name, property_json) in schema_json["properties"].entries() {
+        properties.push((property_name.to_string(), parse_schema_with_stack(property_json, resolver, document, stack)?));
+    }
+    let required = schema_json["required"].members().filter_map(|x| x.as_str()).map(|x| x.to_string()).collect();
+    let items = match &schema_json["items"] {
+        JsonValue::Null => None,
+        items_json => Some(Box::new(parse_schema_with_stack(items_json, resolver, document, stack)?)),
+    };
+
+    Ok(Schema {
+        name,
+        schema_types,
+        enum_values,
+        properties,
+        required,
+        items,
+        all_of: parse_schema_list(&schema_json["allOf"], resolver, document, stack)?,
+        one_of: parse_schema_list(&schema_json["oneOf"], resolver, document, stack)?,
+        any_of: parse_schema_list(&schema_json["anyOf"], resolver, document, stack)?,
+        discriminator: parse_discriminator(&schema_json["discriminator"]),
+    })
+}
+
+fn parse_schema_list(
+    schemas_json: &JsonValue,
+    resolver: &ReferenceResolver,
+    document: &Option<PathBuf>,
+    stack: &mut Vec<(Option<PathBuf>, String)>,
+) -> Result<Vec<Schema>, Error> {
+    let mut schemas = vec![];
+    for schema_json in schemas_json.members() {
+        schemas.push(parse_schema_with_stack(schema_json, resolver, document, stack)?);
+    }
+    Ok(schemas)
+}
+
+// swagger 2.0 only names the property, openapi 3 can additionally map values to schemas
+fn parse_discriminator(discriminator_json: &JsonValue) -> Option<Discriminator> {
+    if let Some(property_name) = discriminator_json.as_str() {
+        return Some(Discriminator {
+            property_name: property_name.to_string(),
+            mapping: vec![],
+        });
+    }
+
+    let property_name = discriminator_json["propertyName"].as_str()?;
+    let mapping = discriminator_json["mapping"]
+        .entries()
+        .filter_map(|(value, reference)| Some((value.to_string(), get_schema_name(reference.as_str()?))))
+        .collect();
+    Some(Discriminator {
+        property_name: property_name.to_string(),
+        mapping,
+    })
+}
+
+// "#/components/schemas/Dog" and "Dog" both name the schema "Dog"
+fn get_schema_name(reference: &str) -> String {
+    match reference.rsplit_once('/') {
+        Some((_, name)) => name.to_string(),
+        None => reference.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use json::object;
+
+    use crate::{models::Schema, parser::reference::ReferenceResolver};
+
+    use super::parse_schema;
+
+    #[test]
+    fn parses_referenced_schemas() {
+        let spec = object! {
+            components: { schemas: {
+                Pet: {
+                    type: "object",
+                    required: ["name"],
+                    properties: { name: { type: "string" }, nickname: { type: "string" } },
+                    oneOf: [{ "$ref": "#/components/schemas/Cat" }, { title: "Dog", type: "object" }],
+                    discriminator: { propertyName: "kind", mapping: { cat: "#/components/schemas/Cat" } },
+                },
+                Cat: { type: "object", properties: { indoor: { type: "boolean" } } },
+            } },
+            body: { "$ref": "#/components/schemas/Pet" },
+        };
+        let resolver = ReferenceResolver::new(&spec, None);
+
+        let schema = parse_schema(&spec["body"], &resolver, &None).unwrap();
+
+        assert_eq!(schema.name, Some("Pet".to_string()));
+        assert_eq!(schema.properties.len(), 2);
+        assert!(schema.is_required("name"));
+        assert!(!schema.is_required("nickname"));
+        assert_eq!(schema.one_of[0].name, Some("Cat".to_string()));
+        assert_eq!(schema.one_of[1].name, Some("Dog".to_string()));
+        let discriminator = schema.discriminator.unwrap();
+        assert_eq!(discriminator.property_name, "kind");
+        assert_eq!(discriminator.mapping, vec![("cat".to_string(), "Cat".to_string())]);
+    }
+
+    #[test]
+    fn ends_recursive_schemas() {
+        let spec = object! {
+            definitions: { Node: { type: "object", properties: { child: { "$ref": "#/definitions/Node" } } } },
+            body: { "$ref": "#/definitions/Node" },
+        };
+        let resolver = ReferenceResolver::new(&spec, None);
+
+        let schema = parse_schema(&spec["body"], &resolver, &None).unwrap();
+
+        assert_eq!(schema.properties[0].1, Schema::default());
+    }
+
+    #[test]
+    fn nullable_schemas_accept_null() {
+        let spec = object! { body: { type: "string", nullable: true } };
+        let resolver = ReferenceResolver::new(&spec, None);
+
+        let schema = parse_schema(&spec["body"], &resolver, &None).unwrap();
+
+        assert_eq!(schema.schema_types, vec!["string".to_string(), "null".to_string()]);
+    }
+}
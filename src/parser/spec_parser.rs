@@ -0,0 +1,457 @@
+use std::{path::PathBuf, str::FromStr, sync::Arc};
+
+use json::JsonValue;
+use url::Url;
+
+use crate::{
+    config::Runtime,
+    models::{
+        is_json_media_type, json_to_enum_value, EndpointConfiguration, MediaType, Method, Parameter, ParameterLocation, Schema,
+        StatusMatcher,
+    },
+    utils::Error,
+};
+
+use super::{
+    common::{format_basepath, get_basepaths_from_servers, get_spec_url, parse_openapi_version, OpenapiServer, OpenapiVersion},
+    reference::{ReferenceResolver, SpecLocation},
+    schema_parser::parse_schema,
+};
+
+const PATH_ITEM_FIELDS: [&str; 5] = ["$ref", "summary", "description", "servers", "parameters"];
+const EXTENSION_PREFIX: &str = "x-";
+const SECURITY_STATUS_CODES: [u16; 2] = [401, 403];
+
+// json and yaml specs are both parsed into a json tree, so references between both formats can be resolved
+pub fn parse_spec(
+    spec: &JsonValue,
+    runtime: Arc<Runtime>,
+    spec_location: Option<SpecLocation>,
+) -> Result<Vec<EndpointConfiguration>, Error> {
+    let mut endpoints = vec![];
+
+    if !spec.is_object() {
+        return Err(Error::InvalidParseSyntax);
+    }
+    let resolver = ReferenceResolver::new(spec, spec_location);
+
+    let openapi_version = match &spec["openapi"] {
+        JsonValue::Null => None,
+        // yaml parses versions such as 3.0 as floating point numbers
+        openapi_version if openapi_version.is_number() => Some(openapi_version.dump()),
+        openapi_version => match openapi_version.as_str() {
+            Some(openapi_version) => Some(openapi_version.to_string()),
+            None => return Err(Error::UnsupportedOpenapiVersion(openapi_version.dump())),
+        },
+    };
+
+    let openapi_version = parse_openapi_version(openapi_version.as_deref())?;
+    let spec_url = get_spec_url(&runtime.openapi_source);
+    let base_paths = match openapi_version {
+        OpenapiVersion::V2 => vec![get_base_path(spec)?.to_string()],
+        OpenapiVersion::V3 => get_basepaths_from_servers(&get_servers(&spec["servers"])?, &spec_url)?,
+    };
+
+    let paths = match &spec["paths"] {
+        json::Null => return Err(Error::InvalidParseSyntax),
+        responses => responses,
+    };
+
+    for path_json in paths.entries() {
+        let path_item = resolver.resolve(path_json.1, resolver.get_spec_location())?;
+
+        // path level servers and parameters apply to all operations of the path, unless overwritten
+        let path_base_paths = get_overwritten_basepaths(&openapi_version, &path_item.value, &base_paths, &spec_url)?;
+        let path_parameters = get_parameters(&path_item.value["parameters"], &resolver, &path_item.document)?;
+
+        for (method, method_json) in get_methods_from_path(path_json.0, &path_item.value).into_iter() {
+            let responses = match &method_json["responses"] {
+                json::Null => return Err(Error::InvalidParseSyntax),
+                responses => responses,
+            };
+
+            let operation_base_paths = get_overwritten_basepaths(&openapi_version, method_json, &path_base_paths, &spec_url)?;
+            let parameters = merge_parameters(
+                &path_parameters,
+                get_parameters(&method_json["parameters"], &resolver, &path_item.document)?,
+            );
+            let request_body = get_request_body(&path_item.value, method_json, &resolver, &path_item.document)?.map(Arc::new);
+            let request_media_types = get_request_media_types(
+                &openapi_version,
+                method_json,
+                spec,
+                &parameters,
+                &resolver,
+                &path_item.document,
+            )?;
+
+            let mut operation_responses = vec![];
+            for (status_code, response_json) in responses.entries() {
+                let status_code = StatusMatcher::from_str(status_code)?;
+                let response = resolver.resolve(response_json, &path_item.document)?;
+                let media_types = get_response_media_types(
+                    &openapi_version,
+                    &response.value,
+                    method_json,
+                    spec,
+                    &resolver,
+                    &response.document,
+                )?;
+                operation_responses.push((status_code, media_types));
+            }
+
+            // the first server names the endpoint, requests under the base paths of the others cover it as well
+            let mut operation_paths = operation_base_paths.iter().map(|base_path| {
+                let mut path = String::from(base_path);
+                match path_json.0 {
+                    "/" => (),
+                    _ => path.push_str(path_json.0),
+                }
+                if path.is_empty() {
+                    path.push('/');
+                }
+                path
+            });
+            let path = operation_paths.next().unwrap_or_else(|| String::from(path_json.0));
+            let alternative_paths: Vec<String> = operation_paths.filter(|alternative_path| alternative_path != &path).collect();
+
+            if requires_authentication(&method_json["security"], &spec["security"]) {
+                for status_code in SECURITY_STATUS_CODES {
+                    // documented responses take precedence over generated ones, including ranges and default responses
+                    if operation_responses.iter().any(|(documented_status_code, _)| {
+                        *documented_status_code == StatusMatcher::Default || documented_status_code.matches(&StatusMatcher::Exact(status_code))
+                    }) {
+                        continue;
+                    }
+                    endpoints.push(EndpointConfiguration::new(
+                        method.clone(),
+                        &path,
+                        status_code,
+                        runtime.clone(),
+                        true
+                    )?
+                    .with_alternative_paths(&alternative_paths)?
+                    .with_parameters(parameters.clone())
+                    .with_request_body(request_body.clone())
+                    .with_request_media_types(request_media_types.clone()));
+                }
+            }
+
+            for (status_code, media_types) in &operation_responses {
+                endpoints.push(EndpointConfiguration::new_with_status_matcher(
+                    method.clone(),
+                    &path,
+                    *status_code,
+                    runtime.clone(),
+                    false
+                )?
+                .with_alternative_paths(&alternative_paths)?
+                .with_parameters(parameters.clone())
+                .with_request_body(request_body.clone())
+                .with_request_media_types(request_media_types.clone())
+                .with_response_media_types(media_types.clone()))
+            }
+        }
+    }
+
+    Ok(endpoints)
+}
+
+// openapi 3 allows path items and operations to replace the servers of the spec
+fn get_overwritten_basepaths(
+    openapi_version: &OpenapiVersion,
+    spec_element: &JsonValue,
+    base_paths: &[String],
+    spec_url: &Url,
+) -> Result<Vec<String>, Error> {
+    match openapi_version {
+        OpenapiVersion::V3 if !spec_element["servers"].is_empty() => {
+            get_basepaths_from_servers(&get_servers(&spec_element["servers"])?, spec_url)
+        }
+        _ => Ok(base_paths.to_vec()),
+    }
+}
+
+fn get_parameters(
+    parameters_json: &JsonValue,
+    resolver: &ReferenceResolver,
+    document: &Option<PathBuf>,
+) -> Result<Vec<Parameter>, Error> {
+    let mut parameters = vec![];
+
+    for parameter_json in parameters_json.members() {
+        let parameter_json = resolver.resolve(parameter_json, document)?.value;
+        let name = match parameter_json["name"].as_str() {
+            Some(name) => name,
+            None => return Err(Error::InvalidParseParameter(parameter_json.dump())),
+        };
+        let location = match parameter_json["in"].as_str() {
+            Some(location) => ParameterLocation::from_str(location)?,
+            None => return Err(Error::InvalidParseParameter(parameter_json.dump())),
+        };
+        // path parameters are always required
+        let required = location == ParameterLocation::Path || parameter_json["required"].as_bool().unwrap_or(false);
+        let enum_values = get_enum_values(&parameter_json, resolver, document)?;
+        parameters.push(Parameter::new(name, location, required).with_enum_values(enum_values));
+    }
+    Ok(parameters)
+}
+
+// openapi 3 describes parameters with a schema, swagger 2.0 inlines it, arrays list their values in items
+fn get_enum_values(
+    parameter_json: &JsonValue,
+    resolver: &ReferenceResolver,
+    document: &Option<PathBuf>,
+) -> Result<Vec<String>, Error> {
+    let schema = match &parameter_json["schema"] {
+        JsonValue::Null => parameter_json.clone(),
+        schema => resolver.resolve(schema, document)?.value,
+    };
+    let enum_json = match &schema["items"] {
+        JsonValue::Null => schema["enum"].clone(),
+        items => resolver.resolve(items, document)?.value["enum"].clone(),
+    };
+
+    // values are compared with the raw request, so numbers and booleans are kept as they would be sent
+    Ok(enum_json
+        .members()
+        .filter(|value| !value.is_null())
+        .map(json_to_enum_value)
+        .collect())
+}
+
+// openapi 3 describes bodies per media type, of which only json is recorded, swagger 2.0 uses a body parameter
+fn get_request_body(
+    path_item_json: &JsonValue,
+    method_json: &JsonValue,
+    resolver: &ReferenceResolver,
+    document: &Option<PathBuf>,
+) -> Result<Option<Schema>, Error> {
+    if !method_json["requestBody"].is_null() {
+        let request_body = resolver.resolve(&method_json["requestBody"], document)?;
+        let media_type = request_body.value["content"]
+            .entries()
+            .find(|(media_type, _)| is_json_media_type(media_type))
+            .map(|(_, media_type_json)| media_type_json);
+        return match media_type {
+            Some(media_type_json) if !media_type_json["schema"].is_null() => {
+                Ok(Some(parse_schema(&media_type_json["schema"], resolver, &request_body.document)?))
+            }
+            _ => Ok(None),
+        };
+    }
+
+    // operation parameters take precedence over the ones of the path
+    for parameter_json in method_json["parameters"].members().chain(path_item_json["parameters"].members()) {
+        let parameter = resolver.resolve(parameter_json, document)?;
+        if parameter.value["in"].as_str() == Some(ParameterLocation::Body.as_str()) {
+            return Ok(Some(parse_schema(&parameter.value["schema"], resolver, &parameter.document)?));
+        }
+    }
+    Ok(None)
+}
+
+// swagger 2.0 declares the media types an operation consumes, they only apply if it has a body or form parameters
+fn get_request_media_types(
+    openapi_version: &OpenapiVersion,
+    method_json: &JsonValue,
+    spec: &JsonValue,
+    parameters: &[Parameter],
+    resolver: &ReferenceResolver,
+    document: &Option<PathBuf>,
+) -> Result<Vec<String>, Error> {
+    let media_types = match openapi_version {
+        OpenapiVersion::V3 => {
+            if method_json["requestBody"].is_null() {
+                return Ok(vec![]);
+            }
+            let request_body = resolver.resolve(&method_json["requestBody"], document)?;
+            request_body.value["content"].entries().map(|(media_type, _)| media_type.to_string()).collect()
+        }
+        OpenapiVersion::V2 => {
+            let has_body = parameters
+                .iter()
+                .any(|parameter| parameter.location == ParameterLocation::Body || parameter.location == ParameterLocation::FormData);
+            if !has_body {
+                return Ok(vec![]);
+            }
+            let consumes = match &method_json["consumes"] {
+                JsonValue::Null => &spec["consumes"],
+                consumes => consumes,
+            };
+            consumes.members().filter_map(|x| x.as_str()).map(|x| x.to_string()).collect()
+        }
+    };
+    Ok(media_types)
+}
+
+// openapi 3 declares a schema per media type, swagger 2.0 shares one schema between all media types it produces
+fn get_response_media_types(
+    openapi_version: &OpenapiVersion,
+    response_json: &JsonValue,
+    method_json: &JsonValue,
+    spec: &JsonValue,
+    resolver: &ReferenceResolver,
+    document: &Option<PathBuf>,
+) -> Result<Vec<MediaType>, Error> {
+    let mut media_types = vec![];
+    match openapi_version {
+        OpenapiVersion::V3 => {
+            for (media_type, media_type_json) in response_json["content"].entries() {
+                let schema = match &media_type_json["schema"] {
+                    JsonValue::Null => None,
+                    schema_json => parse_response_schema(schema_json, resolver, document),
+                };
+                media_types.push(MediaType::new(media_type, schema));
+            }
+        }
+        OpenapiVersion::V2 => {
+            // responses without a schema have no body
+            if response_json["schema"].is_null() {
+                return Ok(media_types);
+            }
+            let schema = parse_response_schema(&response_json["schema"], resolver, document);
+            let produces = match &method_json["produces"] {
+                JsonValue::Null => &spec["produces"],
+                produces => produces,
+            };
+            for media_type in produces.members().filter_map(|x| x.as_str()) {
+                media_types.push(MediaType::new(media_type, schema.clone()));
+            }
+            // without any declared media type, every content type is accepted
+            if media_types.is_empty() {
+                media_types.push(MediaType::new("*/*", schema));
+            }
+        }
+    }
+    Ok(media_types)
+}
+
+// response schemas are only needed to validate responses, so broken ones don't prevent measuring the coverage
+fn parse_response_schema(
+    schema_json: &JsonValue,
+    resolver: &ReferenceResolver,
+    document: &Option<PathBuf>,
+) -> Option<Arc<Schema>> {
+    match parse_schema(schema_json, resolver, document) {
+        Ok(schema) => Some(Arc::new(schema)),
+        Err(err) => {
+            println!("WARNING: a response schema of the openapi spec can't be parsed, its responses won't be validated.");
+            err.display_error();
+            None
+        }
+    }
+}
+
+// parameters of an operation replace path level parameters with the same name and location
+fn merge_parameters(path_parameters: &[Parameter], operation_parameters: Vec<Parameter>) -> Vec<Parameter> {
+    let mut parameters: Vec<Parameter> = path_parameters
+        .iter()
+        .filter(|path_parameter| !operation_parameters.iter().any(|x| x.is_same_parameter(path_parameter)))
+        .cloned()
+        .collect();
+    parameters.extend(operation_parameters);
+    parameters
+}
+
+// operations overwrite the global security requirements, "security: []" removes them and "{}" makes them optional
+fn requires_authentication(operation_security: &JsonValue, global_security: &JsonValue) -> bool {
+    let security = match operation_security {
+        JsonValue::Null => global_security,
+        operation_security => operation_security,
+    };
+    // the requirements are alternatives, an empty one allows anonymous requests
+    !security.is_empty() && !security.members().any(JsonValue::is_empty)
+}
+
+fn get_base_path(json_obj: &JsonValue) -> Result<&str, Error> {
+    let base_path = match &json_obj["basePath"] {
+        JsonValue::Null => "",
+        base_path => match base_path.as_str() {
+            Some(base_path) => base_path,
+            None => return Err(Error::InvalidBasePath),
+        },
+    };
+    Ok(format_basepath(base_path))
+}
+
+fn get_servers(servers_json: &JsonValue) -> Result<Vec<OpenapiServer>, Error> {
+    let mut servers = vec![];
+
+    for server_json in servers_json.members() {
+        let url = match server_json["url"].as_str() {
+            Some(url) => url.to_string(),
+            None => return Err(Error::InvalidServerUrl(server_json.dump())),
+        };
+
+        let mut variables = vec![];
+        for (name, variable_json) in server_json["variables"].entries() {
+            match variable_json["default"].as_str() {
+                Some(default) => variables.push((name.to_string(), default.to_string())),
+                None => return Err(Error::InvalidServerUrl(url)),
+            }
+        }
+        servers.push(OpenapiServer { url, variables });
+    }
+    Ok(servers)
+}
+
+fn get_methods_from_path<'a>(path: &str, path_json: &'a JsonValue) -> Vec<(Method, &'a JsonValue)> {
+    let mut methods = vec![];
+
+    for method_entry in path_json.entries() {
+        match Method::from_str(method_entry.0) {
+            Some(method) => methods.push((method, method_entry.1)),
+            None if PATH_ITEM_FIELDS.contains(&method_entry.0) || method_entry.0.starts_with(EXTENSION_PREFIX) => (),
+            None => println!(
+                "WARNING: the openapi spec contains the unknown key \"{}\" in the path \"{}\", it will be ignored.",
+                method_entry.0, path
+            ),
+        }
+    }
+    methods
+}
+
+#[cfg(test)]
+mod tests {
+    use json::{array, object, JsonValue};
+
+    use crate::models::{Parameter, ParameterLocation};
+
+    use super::{merge_parameters, requires_authentication};
+
+    #[test]
+    fn operation_parameters_replace_path_parameters() {
+        let path_parameters = vec![
+            Parameter::new("id", ParameterLocation::Path, true),
+            Parameter::new("limit", ParameterLocation::Query, false),
+        ];
+        let operation_parameters = vec![Parameter::new("limit", ParameterLocation::Query, true)];
+
+        assert_eq!(
+            merge_parameters(&path_parameters, operation_parameters),
+            vec![
+                Parameter::new("id", ParameterLocation::Path, true),
+                Parameter::new("limit", ParameterLocation::Query, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn operation_security_overwrites_global_security() {
+        let global_security = array![object! { "BasicAuth": [] }];
+
+        assert!(requires_authentication(&JsonValue::Null, &global_security));
+        assert!(!requires_authentication(&array![], &global_security));
+        assert!(requires_authentication(&array![object! { "ApiKey": [] }], &JsonValue::Null));
+        assert!(!requires_authentication(&JsonValue::Null, &JsonValue::Null));
+    }
+
+    #[test]
+    fn empty_security_requirement_is_optional() {
+        assert!(!requires_authentication(&array![object! {}], &JsonValue::Null));
+        assert!(!requires_authentication(&array![object! {}, object! { "ApiKey": [] }], &JsonValue::Null));
+        assert!(!requires_authentication(&array![object! { "ApiKey": [] }, object! {}], &JsonValue::Null));
+        assert!(requires_authentication(&array![object! { "ApiKey": [] }, object! { "OAuth": ["read"] }], &JsonValue::Null));
+    }
+}
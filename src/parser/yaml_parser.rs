@@ -1,12 +1,12 @@
 use std::sync::Arc;
 
-use linked_hash_map::LinkedHashMap;
+use json::JsonValue;
 use yaml_rust::{Yaml, YamlLoader};
 
 use crate::{
     config::Runtime,
-    models::{EndpointConfiguration, Method},
-    parser::common::format_basepath,
+    models::EndpointConfiguration,
+    parser::spec_parser::parse_spec,
     utils::Error,
 };
 
@@ -14,106 +14,50 @@ pub fn parse_yaml_doc(
     yaml_string: &str,
     runtime: Arc<Runtime>,
 ) -> Result<Vec<EndpointConfiguration>, Error> {
+    parse_spec(&load_yaml_as_json(yaml_string)?, runtime, None)
+}
+
+pub fn load_yaml_as_json(yaml_string: &str) -> Result<JsonValue, Error> {
     let spec = match YamlLoader::load_from_str(yaml_string) {
         Ok(spec) => spec,
         Err(_) => return Err(Error::InvalidParseSyntax),
     };
 
-    let spec = &spec[0];
-
-    let spec = match spec.as_hash() {
-        Some(spec) => spec,
-        None => return Err(Error::UnknownInternalError("yaml spec can't be serialized".to_string())),
-    };
-
-    let basepath = match spec.get(&Yaml::from_str("basePath")) {
-        Some(basepath) => match basepath.as_str() {
-            Some(basepath) => basepath,
-            None => return Err(Error::InvalidBasePath),
-        },
-        None => "",
-    };
-    let basepath = format_basepath(basepath);
-
-    let paths = match spec.get(&Yaml::from_str("paths")) {
-        Some(paths) => match paths.as_hash() {
-            Some(paths) => paths,
-            None => return Err(Error::InvalidParseSyntax),
-        },
-        None => return Err(Error::InvalidParseSyntax),
-    };
-
-    let mut endpoints = vec![];
-
-    for path_key in paths.keys() {
-        // unwrap is fine here, as we can expect keys to be strings
-        let path = format!("{}{}", basepath, path_key.as_str().unwrap());
-        let methods = retrive_value_as_hash_map(paths, path_key)?;
-
-        for method_key in methods.keys() {
-            // unwrap is fine here, as we can expect keys to be strings
-            let method = match Method::from_str(method_key.as_str().unwrap()) {
-                Some(method) => method,
-                None => {
-                    return Err(Error::InvalidParseMethod(String::from(
-                        path_key.as_str().unwrap(),
-                    )))
-                }
-            };
-
-            let method_infos = retrive_value_as_hash_map(methods, method_key)?;
-            let statuses = retrive_value_as_hash_map(method_infos, &Yaml::from_str("responses"))?;
-            if method_infos.get(&Yaml::from_str("security")).is_some() {
-                endpoints.push(EndpointConfiguration::new(
-                    method.clone(),
-                    &path,
-                    401,
-                    runtime.clone(),
-                    true
-                )?);
-                endpoints.push(EndpointConfiguration::new(
-                    method.clone(),
-                    &path,
-                    403,
-                    runtime.clone(),
-                    true
-                )?);
-            }
+    match spec.first() {
+        Some(spec) => yaml_to_json(spec),
+        None => Err(Error::InvalidParseSyntax),
+    }
+}
 
-            for status_key in statuses.keys() {
-                let status_code = match status_key.as_str().unwrap().parse() {
-                    Ok(status_code) => status_code,
-                    Err(_) => {
-                        return Err(Error::InvalidParseStatusCode(
-                            status_key.as_str().unwrap().to_string(),
-                        ))
-                    }
+pub fn yaml_to_json(yaml: &Yaml) -> Result<JsonValue, Error> {
+    let json = match yaml {
+        Yaml::Hash(hash) => {
+            let mut object = JsonValue::new_object();
+            for (key, value) in hash {
+                // status codes don't need to be quoted in yaml
+                let key = match key {
+                    Yaml::String(key) | Yaml::Real(key) => key.clone(),
+                    Yaml::Integer(key) => key.to_string(),
+                    Yaml::Boolean(key) => key.to_string(),
+                    _ => return Err(Error::InvalidParseSyntax),
                 };
-                endpoints.push(EndpointConfiguration::new(
-                    method.clone(),
-                    &path,
-                    status_code,
-                    runtime.clone(),
-                    false
-                )?);
+                object[key.as_str()] = yaml_to_json(value)?;
             }
+            object
         }
-    }
-
-    Ok(endpoints)
-}
-
-fn retrive_value_as_hash_map<'a>(
-    parent: &'a LinkedHashMap<Yaml, Yaml>,
-    key: &Yaml,
-) -> Result<&'a LinkedHashMap<Yaml, Yaml>, Error> {
-    match parent.get(key) {
-        Some(child) => match child.as_hash() {
-            Some(child) => Ok(child),
-            None => Err(Error::InvalidParseSyntax),
+        Yaml::Array(array) => JsonValue::Array(array.iter().map(yaml_to_json).collect::<Result<Vec<JsonValue>, Error>>()?),
+        Yaml::String(value) => JsonValue::from(value.as_str()),
+        Yaml::Integer(value) => JsonValue::from(*value),
+        Yaml::Real(value) => match value.parse::<f64>() {
+            Ok(number) => JsonValue::from(number),
+            Err(_) => JsonValue::from(value.as_str()),
         },
-        None => Err(Error::UnknownInternalError("yaml spec parent should exist".to_string())),
-    }
+        Yaml::Boolean(value) => JsonValue::from(*value),
+        Yaml::Null => JsonValue::Null,
+        // aliases aren't resolved by yaml_rust's loader
+        Yaml::Alias(_) | Yaml::BadValue => return Err(Error::InvalidParseSyntax),
+    };
+    Ok(json)
 }
 
 #[cfg(test)]
@@ -121,7 +65,7 @@ mod tests {
     use std::{sync::Arc, str::FromStr};
 
     use crate::{
-        models::{Method, OpenapiPath}, parser::yaml_parser::parse_yaml_doc, utils::test::create_mock_runtime,
+        models::{Method, OpenapiPath, Parameter, ParameterLocation, StatusMatcher}, parser::yaml_parser::parse_yaml_doc, utils::{test::create_mock_runtime, Error},
     };
 
     const YAML_STRING: &str = "
@@ -155,6 +99,34 @@ paths:
             $ref: '#/definitions/controller.IsValid'
 ";
 
+    const OPENAPI_3_YAML_STRING: &str = "
+openapi: 3.0.3
+servers:
+  - url: https://{environment}.example.com/{basePath}
+    variables:
+      environment:
+        default: api
+      basePath:
+        default: v1
+  - url: /v2
+paths:
+  /weather:
+    get:
+      responses:
+        200:
+          description: OK
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/Weather'
+        '404':
+          description: Not Found
+components:
+  schemas:
+    Weather:
+      type: object
+";
+
     #[test]
     fn finds_all_paths() {
         assert!(
@@ -228,4 +200,243 @@ paths:
             1
         );
     }
+
+    #[test]
+    fn ranges_and_default_responses_document_security_status_codes() {
+        let yaml_string = "
+security:
+  - BasicAuth: []
+paths:
+  /range:
+    get:
+      responses:
+        200:
+          description: OK
+        4XX:
+          description: Client Error
+  /default:
+    get:
+      responses:
+        200:
+          description: OK
+        default:
+          description: Error
+";
+        let endpoints = parse_yaml_doc(yaml_string, Arc::from(create_mock_runtime())).unwrap();
+
+        assert_eq!(endpoints.len(), 4);
+        assert!(!endpoints.iter().any(|x| x.is_generated));
+    }
+
+    #[test]
+    fn derives_basepaths_from_openapi_3_servers() {
+        let endpoints = parse_yaml_doc(OPENAPI_3_YAML_STRING, Arc::from(create_mock_runtime())).unwrap();
+
+        assert_eq!(endpoints.len(), 2);
+        assert!(endpoints.iter().all(|x| x.path == OpenapiPath::from_str("/v1/weather").unwrap()));
+        assert!(endpoints.iter().all(|x| x.alternative_paths == vec![OpenapiPath::from_str("/v2/weather").unwrap()]));
+    }
+
+    #[test]
+    fn parses_content_based_openapi_3_responses() {
+        let endpoints = parse_yaml_doc(OPENAPI_3_YAML_STRING, Arc::from(create_mock_runtime())).unwrap();
+
+        assert!(endpoints.iter().any(|x| x.status_code == 200));
+        assert!(endpoints.iter().any(|x| x.status_code == 404));
+    }
+
+    #[test]
+    fn rejects_unsupported_openapi_version() {
+        assert!(parse_yaml_doc("openapi: 4.0.0\npaths: {}\n", Arc::from(create_mock_runtime())).is_err());
+    }
+
+    #[test]
+    fn unresolved_response_reference_leads_to_error() {
+        let yaml_string = "
+paths:
+  /foo:
+    get:
+      responses:
+        404:
+          $ref: '#/responses/NotFound'
+";
+        assert!(matches!(
+            parse_yaml_doc(yaml_string, Arc::from(create_mock_runtime())),
+            Err(Error::UnresolvedReference(_, _))
+        ));
+    }
+
+    const PATH_LEVEL_KEYS_YAML_STRING: &str = "
+openapi: 3.0.3
+servers:
+  - url: /v1
+paths:
+  /users/{id}:
+    summary: A single user
+    description: Everything about one user
+    x-owner: accounts
+    servers:
+      - url: /v2
+    parameters:
+      - name: id
+        in: path
+      - name: verbose
+        in: query
+    get:
+      parameters:
+        - name: verbose
+          in: query
+          required: true
+      responses:
+        200:
+          description: OK
+    delete:
+      servers:
+        - url: /v3
+      responses:
+        204:
+          description: No Content
+    unknown:
+      foo: bar
+";
+
+    #[test]
+    fn tolerates_path_level_keys() {
+        let endpoints = parse_yaml_doc(PATH_LEVEL_KEYS_YAML_STRING, Arc::from(create_mock_runtime())).unwrap();
+
+        assert_eq!(endpoints.len(), 2);
+    }
+
+    #[test]
+    fn path_level_servers_overwrite_spec_servers() {
+        let endpoints = parse_yaml_doc(PATH_LEVEL_KEYS_YAML_STRING, Arc::from(create_mock_runtime())).unwrap();
+
+        assert!(endpoints.iter().any(|x| x.method == Method::GET && x.path == OpenapiPath::from_str("/v2/users/{id}").unwrap()));
+        assert!(endpoints.iter().any(|x| x.method == Method::DELETE && x.path == OpenapiPath::from_str("/v3/users/{id}").unwrap()));
+    }
+
+    #[test]
+    fn carries_path_level_parameters_into_operations() {
+        let endpoints = parse_yaml_doc(PATH_LEVEL_KEYS_YAML_STRING, Arc::from(create_mock_runtime())).unwrap();
+        let get = endpoints.iter().find(|x| x.method == Method::GET).unwrap();
+        let delete = endpoints.iter().find(|x| x.method == Method::DELETE).unwrap();
+
+        assert_eq!(
+            get.parameters,
+            vec![Parameter::new("id", ParameterLocation::Path, true), Parameter::new("verbose", ParameterLocation::Query, true)]
+        );
+        assert_eq!(
+            delete.parameters,
+            vec![Parameter::new("id", ParameterLocation::Path, true), Parameter::new("verbose", ParameterLocation::Query, false)]
+        );
+    }
+
+    #[test]
+    fn parameters_without_location_lead_to_error() {
+        let yaml_string = "
+paths:
+  /foo:
+    parameters:
+      - name: foo
+    get:
+      responses:
+        200:
+          description: OK
+";
+        assert!(matches!(
+            parse_yaml_doc(yaml_string, Arc::from(create_mock_runtime())),
+            Err(Error::InvalidParseParameter(_))
+        ));
+    }
+
+    #[test]
+    fn parses_enum_values_of_parameters() {
+        let yaml_string = "
+openapi: 3.0.3
+paths:
+  /tickets/{priority}:
+    get:
+      parameters:
+        - name: priority
+          in: path
+          schema:
+            type: integer
+            enum: [1, 2, 3]
+        - name: status
+          in: query
+          schema:
+            $ref: '#/components/schemas/Status'
+        - name: tags
+          in: query
+          schema:
+            type: array
+            items:
+              enum: [bug, feature]
+        - name: verbose
+          in: query
+          schema:
+            type: boolean
+      responses:
+        200:
+          description: OK
+components:
+  schemas:
+    Status:
+      type: string
+      enum: [open, closed, archived]
+";
+        let endpoints = parse_yaml_doc(yaml_string, Arc::from(create_mock_runtime())).unwrap();
+        let enum_values: Vec<Vec<String>> = endpoints[0].parameters.iter().map(|x| x.enum_values.clone()).collect();
+
+        assert_eq!(
+            enum_values,
+            vec![
+                vec!["1".to_string(), "2".to_string(), "3".to_string()],
+                vec!["open".to_string(), "closed".to_string(), "archived".to_string()],
+                vec!["bug".to_string(), "feature".to_string()],
+                vec![],
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_inline_swagger_enum_values() {
+        let yaml_string = "
+paths:
+  /tickets:
+    get:
+      parameters:
+        - name: status
+          in: query
+          type: string
+          enum: [open, closed]
+      responses:
+        200:
+          description: OK
+";
+        let endpoints = parse_yaml_doc(yaml_string, Arc::from(create_mock_runtime())).unwrap();
+
+        assert_eq!(endpoints[0].parameters[0].enum_values, vec!["open".to_string(), "closed".to_string()]);
+    }
+
+    #[test]
+    fn parses_default_and_range_status_codes() {
+        let yaml_string = "
+paths:
+  /foo:
+    get:
+      responses:
+        200:
+          description: OK
+        4XX:
+          description: Client Error
+        default:
+          description: Unexpected Error
+";
+        let endpoints = parse_yaml_doc(yaml_string, Arc::from(create_mock_runtime())).unwrap();
+
+        assert_eq!(endpoints.len(), 3);
+        assert!(endpoints.iter().any(|x| x.status_code == StatusMatcher::Range(4)));
+        assert!(endpoints.iter().any(|x| x.status_code == StatusMatcher::Default));
+    }
 }
@@ -0,0 +1,5 @@
+mod request;
+mod server;
+
+pub use server::run_native_proxy;
+pub use server::Proxy;
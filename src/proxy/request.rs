@@ -0,0 +1,266 @@
+use std::io::{BufRead, Read};
+
+use crate::utils::Error;
+
+const MAX_LINE_LENGTH: u64 = 16 * 1024;
+const MAX_HEADER_COUNT: usize = 128;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RequestHead {
+    pub method: String,
+    pub target: String,
+    pub version: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl RequestHead {
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn header_contains_token(&self, name: &str, token: &str) -> bool {
+        self.headers
+            .iter()
+            .filter(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .flat_map(|(_, value)| value.split(','))
+            .any(|value| value.trim().eq_ignore_ascii_case(token))
+    }
+
+    pub fn is_keep_alive(&self) -> bool {
+        if self.version == "HTTP/1.0" {
+            self.header_contains_token("Connection", "keep-alive")
+        } else {
+            !self.header_contains_token("Connection", "close")
+        }
+    }
+
+    pub fn expects_continue(&self) -> bool {
+        self.header_contains_token("Expect", "100-continue")
+    }
+
+    // absolute-form targets (as sent to forward proxies) are reduced to their path and query
+    pub fn get_origin_form_target(&self) -> String {
+        if self.target.starts_with('/') {
+            return self.target.clone();
+        }
+        match self.target.find("://") {
+            Some(scheme_end) => match self.target[scheme_end + 3..].find(['/', '?']) {
+                Some(path_start) if self.target[scheme_end + 3 + path_start..].starts_with('/') => {
+                    self.target[scheme_end + 3 + path_start..].to_string()
+                }
+                Some(path_start) => format!("/{}", &self.target[scheme_end + 3 + path_start..]),
+                None => "/".to_string(),
+            },
+            None => self.target.clone(),
+        }
+    }
+}
+
+pub fn read_request_head<R: BufRead>(reader: &mut R) -> Result<Option<RequestHead>, Error> {
+    let mut request_line = match read_line(reader)? {
+        Some(request_line) => request_line,
+        None => return Ok(None),
+    };
+    // clients may send empty lines between requests on a persistent connection
+    while request_line.is_empty() {
+        request_line = match read_line(reader)? {
+            Some(request_line) => request_line,
+            None => return Ok(None),
+        };
+    }
+
+    let mut request_line_parts = request_line.split(' ').filter(|part| !part.is_empty());
+    let (method, target, version) = match (
+        request_line_parts.next(),
+        request_line_parts.next(),
+        request_line_parts.next(),
+        request_line_parts.next(),
+    ) {
+        (Some(method), Some(target), Some(version), None) => (method, target, version),
+        _ => return Err(Error::InvalidProxyRequest(format!("invalid request line \"{}\"", request_line))),
+    };
+    if !version.starts_with("HTTP/1.") {
+        return Err(Error::InvalidProxyRequest(format!("unsupported protocol version \"{}\"", version)));
+    }
+
+    let mut headers = vec![];
+    loop {
+        let header_line = match read_line(reader)? {
+            Some(header_line) => header_line,
+            None => return Err(Error::InvalidProxyRequest("connection closed within headers".to_string())),
+        };
+        if header_line.is_empty() {
+            break;
+        }
+        if headers.len() >= MAX_HEADER_COUNT {
+            return Err(Error::InvalidProxyRequest("too many headers".to_string()));
+        }
+        match header_line.split_once(':') {
+            Some((name, value)) if !name.is_empty() && !name.ends_with(' ') => {
+                headers.push((name.to_string(), value.trim().to_string()))
+            }
+            _ => return Err(Error::InvalidProxyRequest(format!("invalid header \"{}\"", header_line))),
+        }
+    }
+
+    Ok(Some(RequestHead {
+        method: method.to_string(),
+        target: target.to_string(),
+        version: version.to_string(),
+        headers,
+    }))
+}
+
+pub fn read_request_body<R: BufRead>(reader: &mut R, head: &RequestHead) -> Result<Vec<u8>, Error> {
+    if head.header_contains_token("Transfer-Encoding", "chunked") {
+        return read_chunked_body(reader);
+    }
+
+    let content_length = match head.get_header("Content-Length") {
+        Some(content_length) => match content_length.trim().parse::<u64>() {
+            Ok(content_length) => content_length,
+            Err(_) => return Err(Error::InvalidProxyRequest(format!("invalid content length \"{}\"", content_length))),
+        },
+        None => return Ok(vec![]),
+    };
+
+    read_exact_body(reader, content_length)
+}
+
+fn read_chunked_body<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, Error> {
+    let mut body = vec![];
+    loop {
+        let size_line = match read_line(reader)? {
+            Some(size_line) => size_line,
+            None => return Err(Error::InvalidProxyRequest("connection closed within chunked body".to_string())),
+        };
+        // chunk extensions are separated by a semicolon and can be ignored
+        let size_str = size_line.split(';').next().unwrap_or("").trim();
+        let size = match u64::from_str_radix(size_str, 16) {
+            Ok(size) => size,
+            Err(_) => return Err(Error::InvalidProxyRequest(format!("invalid chunk size \"{}\"", size_line))),
+        };
+
+        if size == 0 {
+            // skip trailers
+            loop {
+                match read_line(reader)? {
+                    Some(trailer) if !trailer.is_empty() => (),
+                    _ => return Ok(body),
+                }
+            }
+        }
+
+        body.append(&mut read_exact_body(reader, size)?);
+        match read_line(reader)? {
+            Some(chunk_end) if chunk_end.is_empty() => (),
+            _ => return Err(Error::InvalidProxyRequest("chunk is missing its line ending".to_string())),
+        }
+    }
+}
+
+fn read_exact_body<R: BufRead>(reader: &mut R, length: u64) -> Result<Vec<u8>, Error> {
+    let mut body = vec![];
+    match reader.by_ref().take(length).read_to_end(&mut body) {
+        Ok(read_length) if read_length as u64 == length => Ok(body),
+        Ok(_) => Err(Error::InvalidProxyRequest("connection closed within body".to_string())),
+        Err(why) => Err(Error::UnexpectedIOIssue(why.to_string())),
+    }
+}
+
+fn read_line<R: BufRead>(reader: &mut R) -> Result<Option<String>, Error> {
+    let mut line = vec![];
+    match reader.by_ref().take(MAX_LINE_LENGTH).read_until(b'\n', &mut line) {
+        Ok(0) => return Ok(None),
+        Ok(_) => (),
+        Err(why) => return Err(Error::UnexpectedIOIssue(why.to_string())),
+    }
+    if !line.ends_with(b"\n") {
+        return Err(Error::InvalidProxyRequest("line is too long or incomplete".to_string()));
+    }
+    line.pop();
+    if line.ends_with(b"\r") {
+        line.pop();
+    }
+
+    match String::from_utf8(line) {
+        Ok(line) => Ok(Some(line)),
+        // header values should be ascii, latin-1 values are kept as is
+        Err(err) => Ok(Some(err.into_bytes().iter().map(|byte| *byte as char).collect())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Cursor};
+
+    use super::{read_request_body, read_request_head, RequestHead};
+
+    fn read_head(request: &str) -> RequestHead {
+        read_request_head(&mut BufReader::new(Cursor::new(request))).unwrap().unwrap()
+    }
+
+    #[test]
+    fn parses_request_line_and_headers() {
+        let head = read_head("GET /weather?city=x HTTP/1.1\r\nHost: example.com\r\nAccept:  */*\r\n\r\n");
+
+        assert_eq!(head.method, "GET");
+        assert_eq!(head.target, "/weather?city=x");
+        assert_eq!(head.version, "HTTP/1.1");
+        assert_eq!(head.get_header("host"), Some("example.com"));
+        assert_eq!(head.get_header("ACCEPT"), Some("*/*"));
+    }
+
+    #[test]
+    fn closed_connection_leads_to_no_request() {
+        assert!(read_request_head(&mut BufReader::new(Cursor::new(""))).unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_garbage_requests() {
+        assert!(read_request_head(&mut BufReader::new(Cursor::new("\u{16}\u{3}\u{1}garbage\r\n\r\n"))).is_err());
+        assert!(read_request_head(&mut BufReader::new(Cursor::new("GET / HTTP/1.1\r\nno colon\r\n\r\n"))).is_err());
+        assert!(read_request_head(&mut BufReader::new(Cursor::new("GET / HTTP/1.1\r\nHost: x\r\n"))).is_err());
+    }
+
+    #[test]
+    fn reduces_absolute_form_to_origin_form() {
+        assert_eq!(read_head("GET /a?b=c HTTP/1.1\r\n\r\n").get_origin_form_target(), "/a?b=c");
+        assert_eq!(read_head("GET http://example.com/a?b=c HTTP/1.1\r\n\r\n").get_origin_form_target(), "/a?b=c");
+        assert_eq!(read_head("GET http://example.com?b=c HTTP/1.1\r\n\r\n").get_origin_form_target(), "/?b=c");
+    }
+
+    #[test]
+    fn recognises_keep_alive() {
+        assert!(read_head("GET / HTTP/1.1\r\n\r\n").is_keep_alive());
+        assert!(!read_head("GET / HTTP/1.1\r\nConnection: close\r\n\r\n").is_keep_alive());
+        assert!(!read_head("GET / HTTP/1.0\r\n\r\n").is_keep_alive());
+        assert!(read_head("GET / HTTP/1.0\r\nConnection: Keep-Alive\r\n\r\n").is_keep_alive());
+    }
+
+    #[test]
+    fn reads_body_by_content_length() {
+        let mut reader = BufReader::new(Cursor::new("POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nhelloGET"));
+        let head = read_request_head(&mut reader).unwrap().unwrap();
+        assert_eq!(read_request_body(&mut reader, &head).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn reads_chunked_body() {
+        let mut reader = BufReader::new(Cursor::new(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5;ext=1\r\nhello\r\n6\r\n world\r\n0\r\nTrailer: x\r\n\r\n",
+        ));
+        let head = read_request_head(&mut reader).unwrap().unwrap();
+        assert_eq!(read_request_body(&mut reader, &head).unwrap(), b"hello world");
+    }
+
+    #[test]
+    fn truncated_body_leads_to_error() {
+        let mut reader = BufReader::new(Cursor::new("POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nhello"));
+        let head = read_request_head(&mut reader).unwrap().unwrap();
+        assert!(read_request_body(&mut reader, &head).is_err());
+    }
+}
@@ -0,0 +1,592 @@
+use std::{
+    io::{BufReader, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use reqwest::{
+    blocking::Client,
+    header::{HeaderMap, HeaderName, HeaderValue},
+    redirect::Policy,
+    StatusCode,
+};
+use url::Url;
+
+use crate::{
+    config::Runtime,
+    models::{Method, Observation, MAX_RECORDED_BODY_SIZE, REDACTED_HEADER_VALUE},
+    parser::ObservationAggregator,
+    utils::{print_debug_message, unix_timestamp, Error},
+};
+
+use super::request::{read_request_body, read_request_head, RequestHead};
+
+const HOP_BY_HOP_HEADERS: [&str; 9] = [
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+const BAD_GATEWAY_MESSAGE: &str = "Rudra could not connect to your service, please double check that you specified the correct uri.";
+const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(75);
+
+type Connections = Arc<Mutex<Vec<(TcpStream, JoinHandle<()>)>>>;
+
+pub fn run_native_proxy(runtimes: &Vec<Arc<Runtime>>, redacted_headers: &[String]) -> Result<Vec<Observation>, Error> {
+    let mut proxy = Proxy::bind(runtimes, redacted_headers)?;
+
+    let (stop_sender, stop_receiver) = mpsc::channel();
+    if let Err(why) = ctrlc::set_handler(move || {
+        let _ = stop_sender.send(());
+    }) {
+        return Err(Error::UnknownInternalError(format!("registering signal handler failed: {}", why)));
+    }
+
+    proxy.start();
+    print_debug_message("Started native proxy, stop rudra (SIGINT or SIGTERM) to start the evaluation");
+
+    let _ = stop_receiver.recv();
+    print_debug_message("Stopping native proxy");
+    Ok(proxy.stop())
+}
+
+pub struct Proxy {
+    listeners: Vec<(TcpListener, Arc<Runtime>)>,
+    client: Client,
+    redacted_headers: Arc<Vec<String>>,
+    observations: Arc<Mutex<ObservationAggregator>>,
+    is_stopped: Arc<AtomicBool>,
+    accept_threads: Vec<JoinHandle<()>>,
+    // open connections and their threads, so stopping can wait for exchanges still in flight
+    connections: Connections,
+}
+
+impl Proxy {
+    pub fn bind(runtimes: &Vec<Arc<Runtime>>, redacted_headers: &[String]) -> Result<Proxy, Error> {
+        let mut listeners = vec![];
+        for runtime in runtimes {
+            match TcpListener::bind(("0.0.0.0", runtime.port)) {
+                Ok(listener) => listeners.push((listener, runtime.clone())),
+                Err(why) => return Err(Error::ProxyPortUnavailable(runtime.port, why.to_string())),
+            }
+        }
+        Proxy::from_listeners(listeners, redacted_headers)
+    }
+
+    fn from_listeners(listeners: Vec<(TcpListener, Arc<Runtime>)>, redacted_headers: &[String]) -> Result<Proxy, Error> {
+        let client = match Client::builder().redirect(Policy::none()).timeout(None).build() {
+            Ok(client) => client,
+            Err(why) => return Err(Error::UnknownInternalError(format!("building proxy client failed: {}", why))),
+        };
+
+        Ok(Proxy {
+            listeners,
+            client,
+            redacted_headers: Arc::new(redacted_headers.to_vec()),
+            observations: Arc::new(Mutex::new(ObservationAggregator::default())),
+            is_stopped: Arc::new(AtomicBool::new(false)),
+            accept_threads: vec![],
+            connections: Arc::new(Mutex::new(vec![])),
+        })
+    }
+
+    pub fn start(&mut self) {
+        for (listener, runtime) in &self.listeners {
+            let listener = match listener.try_clone() {
+                Ok(listener) => listener,
+                Err(why) => {
+                    print_debug_message(format!("Unable to listen on port {}: {}", runtime.port, why));
+                    continue;
+                }
+            };
+            let runtime = runtime.clone();
+            let client = self.client.clone();
+            let redacted_headers = self.redacted_headers.clone();
+            let observations = self.observations.clone();
+            let is_stopped = self.is_stopped.clone();
+            let connections = self.connections.clone();
+
+            self.accept_threads.push(thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if is_stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    match stream {
+                        Ok(stream) => {
+                            let connection = match stream.try_clone() {
+                                Ok(connection) => connection,
+                                Err(why) => {
+                                    print_debug_message(format!("Failed to accept connection: {}", why));
+                                    continue;
+                                }
+                            };
+                            let runtime = runtime.clone();
+                            let client = client.clone();
+                            let redacted_headers = redacted_headers.clone();
+                            let observations = observations.clone();
+                            let connection_thread = thread::spawn(move || {
+                                handle_connection(&stream, &runtime, &client, &redacted_headers, &observations);
+                                // the clone kept for stopping would otherwise keep the connection open
+                                let _ = stream.shutdown(Shutdown::Both);
+                            });
+                            if let Ok(mut connections) = connections.lock() {
+                                connections.retain(|(_, connection_thread)| !connection_thread.is_finished());
+                                connections.push((connection, connection_thread));
+                            }
+                        }
+                        Err(why) => print_debug_message(format!("Failed to accept connection: {}", why)),
+                    }
+                }
+            }));
+        }
+    }
+
+    pub fn stop(mut self) -> Vec<Observation> {
+        self.is_stopped.store(true, Ordering::SeqCst);
+
+        // wake up the blocking accept calls, so the threads can notice the stop
+        for (listener, _) in &self.listeners {
+            if let Ok(address) = listener.local_addr() {
+                let _ = TcpStream::connect(("127.0.0.1", address.port()));
+            }
+        }
+        for accept_thread in self.accept_threads.drain(..) {
+            let _ = accept_thread.join();
+        }
+
+        // idle keep-alive connections are closed, requests in flight are still answered and recorded
+        let connections = match self.connections.lock() {
+            Ok(mut connections) => std::mem::take(&mut *connections),
+            Err(_) => vec![],
+        };
+        for (connection, connection_thread) in connections {
+            let _ = connection.shutdown(Shutdown::Read);
+            let _ = connection_thread.join();
+        }
+
+        match self.observations.lock() {
+            Ok(mut observations) => std::mem::take(&mut *observations).into_observations(),
+            Err(_) => vec![],
+        }
+    }
+}
+
+struct ProxyResponse {
+    status: u16,
+    headers: Vec<(String, Vec<u8>)>,
+    body: Vec<u8>,
+}
+
+impl ProxyResponse {
+    fn from_message(status: u16, message: &str) -> ProxyResponse {
+        ProxyResponse {
+            status,
+            headers: vec![("Content-Type".to_string(), b"text/plain".to_vec())],
+            body: message.as_bytes().to_vec(),
+        }
+    }
+
+    fn get_content_type(&self) -> Option<String> {
+        self.headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| String::from_utf8_lossy(value).to_string())
+    }
+}
+
+fn handle_connection(
+    stream: &TcpStream,
+    runtime: &Arc<Runtime>,
+    client: &Client,
+    redacted_headers: &[String],
+    observations: &Mutex<ObservationAggregator>,
+) {
+    let _ = stream.set_read_timeout(Some(KEEP_ALIVE_TIMEOUT));
+    let mut reader = match stream.try_clone() {
+        Ok(stream) => BufReader::new(stream),
+        Err(_) => return,
+    };
+    let mut writer = stream;
+
+    loop {
+        let head = match read_request_head(&mut reader) {
+            Ok(Some(head)) => head,
+            Ok(None) => return,
+            Err(err) => {
+                err.display_error();
+                let _ = write_response(&mut writer, &ProxyResponse::from_message(400, "Bad Request"), false, false);
+                return;
+            }
+        };
+
+        if head.expects_continue() && writer.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").is_err() {
+            return;
+        }
+
+        let body = match read_request_body(&mut reader, &head) {
+            Ok(body) => body,
+            Err(err) => {
+                err.display_error();
+                let _ = write_response(&mut writer, &ProxyResponse::from_message(400, "Bad Request"), false, false);
+                return;
+            }
+        };
+
+        // the body is moved into the forwarded request, so only bodies which can be recorded are kept
+        let recorded_body = if body.len() <= MAX_RECORDED_BODY_SIZE { body.clone() } else { vec![] };
+        let response = forward_request(client, runtime, &head, body);
+        record_observation(observations, runtime, &head, &recorded_body, &response, redacted_headers);
+
+        let keep_alive = head.is_keep_alive();
+        if write_response(&mut writer, &response, head.method == "HEAD", keep_alive).is_err() || !keep_alive {
+            return;
+        }
+    }
+}
+
+fn forward_request(client: &Client, runtime: &Runtime, head: &RequestHead, body: Vec<u8>) -> ProxyResponse {
+    // the request target replaces the path of the application url, just like proxy_pass in nginx
+    let url = format!(
+        "{}{}",
+        runtime.app_base_url.as_str().trim_end_matches('/'),
+        head.get_origin_form_target()
+    );
+    let url = match Url::from_str(&url) {
+        Ok(url) => url,
+        Err(_) => return ProxyResponse::from_message(400, "Bad Request"),
+    };
+    let method = match reqwest::Method::from_bytes(head.method.as_bytes()) {
+        Ok(method) => method,
+        Err(_) => return ProxyResponse::from_message(400, "Bad Request"),
+    };
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in &head.headers {
+        if is_hop_by_hop_header(name) || name.eq_ignore_ascii_case("host") || name.eq_ignore_ascii_case("content-length") {
+            continue;
+        }
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+            headers.append(name, value);
+        }
+    }
+
+    let has_body = !body.is_empty() || (method != reqwest::Method::GET && method != reqwest::Method::HEAD);
+    let mut request = client.request(method, url).headers(headers);
+    if has_body {
+        request = request.body(body);
+    }
+
+    let response = match request.send() {
+        Ok(response) => response,
+        Err(why) => {
+            print_debug_message(format!("Forwarding request failed: {}", why));
+            return ProxyResponse::from_message(502, BAD_GATEWAY_MESSAGE);
+        }
+    };
+
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter(|(name, _)| !is_hop_by_hop_header(name.as_str()))
+        .map(|(name, value)| (name.as_str().to_string(), value.as_bytes().to_vec()))
+        .collect();
+    let body = match response.bytes() {
+        Ok(body) => body.to_vec(),
+        Err(why) => {
+            print_debug_message(format!("Reading response failed: {}", why));
+            return ProxyResponse::from_message(502, BAD_GATEWAY_MESSAGE);
+        }
+    };
+
+    ProxyResponse { status, headers, body }
+}
+
+fn record_observation(
+    observations: &Mutex<ObservationAggregator>,
+    runtime: &Arc<Runtime>,
+    head: &RequestHead,
+    body: &[u8],
+    response: &ProxyResponse,
+    redacted_headers: &[String],
+) {
+    let method = match Method::from_str(&head.method) {
+        Some(method) => method,
+        None => {
+            print_debug_message(format!("Ignoring request with unknown method: {}", head.method));
+            return;
+        }
+    };
+
+    match Observation::new(method, &head.get_origin_form_target(), response.status, runtime.clone()) {
+        Ok(observation) => {
+            let observation = observation
+                .with_request_body(body)
+                .with_request_headers(head.get_header("content-type"), head.get_header("accept"))
+                .with_request_header_values(redact_headers(&head.headers, redacted_headers))
+                .with_response(response.get_content_type().as_deref(), &response.body)
+                .with_timestamp(unix_timestamp());
+            // requests to the same endpoint are aggregated, just like when reading an access log
+            if let Ok(mut observations) = observations.lock() {
+                observations.add_observation(observation);
+            }
+        }
+        Err(err) => err.display_error(),
+    }
+}
+
+// redacted headers are still recorded, so their presence counts for header parameters
+fn redact_headers(headers: &[(String, String)], redacted_headers: &[String]) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| match redacted_headers.iter().any(|redacted| redacted.eq_ignore_ascii_case(name)) {
+            true => (name.clone(), REDACTED_HEADER_VALUE.to_string()),
+            false => (name.clone(), value.clone()),
+        })
+        .collect()
+}
+
+fn write_response<W: Write>(
+    writer: &mut W,
+    response: &ProxyResponse,
+    is_head_request: bool,
+    keep_alive: bool,
+) -> std::io::Result<()> {
+    let reason = match StatusCode::from_u16(response.status) {
+        Ok(status) => status.canonical_reason().unwrap_or(""),
+        Err(_) => "",
+    };
+    // responses to HEAD requests and bodyless status codes keep the headers of the application as is
+    let has_body = !is_head_request && response.status >= 200 && response.status != 204 && response.status != 304;
+
+    let mut message = format!("HTTP/1.1 {} {}\r\n", response.status, reason).into_bytes();
+    for (name, value) in &response.headers {
+        if has_body && name.eq_ignore_ascii_case("content-length") {
+            continue;
+        }
+        message.extend_from_slice(name.as_bytes());
+        message.extend_from_slice(b": ");
+        message.extend_from_slice(value);
+        message.extend_from_slice(b"\r\n");
+    }
+    if has_body {
+        message.extend_from_slice(format!("Content-Length: {}\r\n", response.body.len()).as_bytes());
+    }
+    if !keep_alive {
+        message.extend_from_slice(b"Connection: close\r\n");
+    }
+    message.extend_from_slice(b"\r\n");
+    if has_body {
+        message.extend_from_slice(&response.body);
+    }
+
+    writer.write_all(&message)?;
+    writer.flush()
+}
+
+fn is_hop_by_hop_header(name: &str) -> bool {
+    HOP_BY_HOP_HEADERS.iter().any(|header| header.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::{TcpListener, TcpStream},
+        str::FromStr,
+        sync::{mpsc, Arc, Mutex},
+        thread,
+        time::Duration,
+    };
+
+    use url::Url;
+
+    use crate::{models::Method, parser::ObservationAggregator, proxy::request::RequestHead, utils::test::create_mock_runtime};
+
+    use super::{record_observation, write_response, Proxy, ProxyResponse};
+
+    // serves a single request with the given raw response and returns the received request line
+    fn spawn_application(response: &'static str) -> (u16, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let mut stream = stream;
+            stream.write_all(response.as_bytes()).unwrap();
+            request_line
+        });
+        (port, handle)
+    }
+
+    fn start_proxy(app_port: u16) -> (Proxy, u16) {
+        let mut runtime = create_mock_runtime();
+        runtime.app_base_url = Url::from_str(&format!("http://127.0.0.1:{}", app_port)).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let mut proxy = Proxy::from_listeners(vec![(listener, Arc::new(runtime))], &["authorization".to_string()]).unwrap();
+        proxy.start();
+        (proxy, port)
+    }
+
+    fn send_request(port: u16, request: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn forwards_requests_and_records_observations() {
+        let (app_port, app) = spawn_application("HTTP/1.1 201 Created\r\nContent-Length: 2\r\nX-Test: foo\r\n\r\nok");
+        let (proxy, port) = start_proxy(app_port);
+
+        let response = send_request(port, "GET /weather?city=x HTTP/1.1\r\nHost: rudra\r\nConnection: close\r\n\r\n");
+
+        assert!(response.starts_with("HTTP/1.1 201 Created\r\n"));
+        assert!(response.contains("x-test: foo\r\n"));
+        assert!(response.ends_with("\r\n\r\nok"));
+        assert_eq!(app.join().unwrap(), "GET /weather?city=x HTTP/1.1\r\n");
+
+        let observations = proxy.stop();
+        assert_eq!(observations.len(), 1);
+        assert_eq!(observations[0].endpoint.method, Method::GET);
+        assert_eq!(observations[0].endpoint.status_code, 201);
+        assert_eq!(observations[0].endpoint.path.to_string(), "/weather");
+    }
+
+    #[test]
+    fn waits_for_requests_in_flight_when_stopping() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let app_port = listener.local_addr().unwrap().port();
+        let (received_sender, received_receiver) = mpsc::channel();
+        let app = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            received_sender.send(()).unwrap();
+            thread::sleep(Duration::from_millis(100));
+            let mut stream = stream;
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").unwrap();
+        });
+        let (proxy, port) = start_proxy(app_port);
+
+        // the client keeps its connection open, so only the stop can end it
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"GET /slow HTTP/1.1\r\nHost: rudra\r\n\r\n").unwrap();
+        received_receiver.recv().unwrap();
+
+        let observations = proxy.stop();
+        app.join().unwrap();
+        assert_eq!(observations.len(), 1);
+        assert_eq!(observations[0].endpoint.path.to_string(), "/slow");
+    }
+
+    #[test]
+    fn records_bad_gateway_if_application_is_unreachable() {
+        // bind and drop a listener to get a port nobody listens on
+        let app_port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let (proxy, port) = start_proxy(app_port);
+
+        let response = send_request(port, "DELETE /foo HTTP/1.1\r\nConnection: close\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 502 Bad Gateway\r\n"));
+
+        let observations = proxy.stop();
+        assert_eq!(observations.len(), 1);
+        assert_eq!(observations[0].endpoint.method, Method::DELETE);
+        assert_eq!(observations[0].endpoint.status_code, 502);
+    }
+
+    #[test]
+    fn rejects_malformed_requests_without_recording() {
+        let (proxy, port) = start_proxy(1);
+
+        let response = send_request(port, "garbage\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+
+        assert!(proxy.stop().is_empty());
+    }
+
+    #[test]
+    fn records_json_request_and_response_bodies() {
+        let observations = Mutex::new(ObservationAggregator::default());
+        let head = RequestHead {
+            method: "POST".to_string(),
+            target: "/pets".to_string(),
+            version: "HTTP/1.1".to_string(),
+            headers: vec![
+                ("Content-Type".to_string(), "application/json".to_string()),
+                ("X-Tenant-Id".to_string(), "acme".to_string()),
+                ("Authorization".to_string(), "Bearer secret".to_string()),
+            ],
+        };
+
+        let response = ProxyResponse {
+            status: 201,
+            headers: vec![("content-type".to_string(), b"application/json".to_vec())],
+            body: b"{\"id\": 1}".to_vec(),
+        };
+
+        let redacted_headers = vec!["authorization".to_string()];
+        record_observation(&observations, &Arc::new(create_mock_runtime()), &head, b"{\"name\": \"Rex\"}", &response, &redacted_headers);
+
+        let observations = observations.into_inner().unwrap().into_observations();
+        assert_eq!(observations[0].request_bodies.values[0].0["name"], "Rex");
+        assert_eq!(observations[0].content_types.values[0].0.request.as_deref(), Some("application/json"));
+        assert_eq!(observations[0].content_types.values[0].0.response.as_deref(), Some("application/json"));
+        assert_eq!(observations[0].response_bodies.values[0].0.body["id"], 1);
+        assert_eq!(observations[0].get_request_header("x-tenant-id"), Some("acme"));
+        assert_eq!(observations[0].get_request_header("authorization"), Some("<redacted>"));
+    }
+
+    #[test]
+    fn writes_content_length_and_connection_headers() {
+        let response = ProxyResponse {
+            status: 200,
+            headers: vec![("Content-Length".to_string(), b"999".to_vec())],
+            body: b"hello".to_vec(),
+        };
+        let mut written = vec![];
+        write_response(&mut written, &response, false, false).unwrap();
+
+        assert_eq!(
+            String::from_utf8(written).unwrap(),
+            "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello"
+        );
+    }
+
+    #[test]
+    fn keeps_headers_and_omits_body_for_head_requests() {
+        let response = ProxyResponse {
+            status: 200,
+            headers: vec![("Content-Length".to_string(), b"999".to_vec())],
+            body: vec![],
+        };
+        let mut written = vec![];
+        write_response(&mut written, &response, true, true).unwrap();
+
+        assert_eq!(String::from_utf8(written).unwrap(), "HTTP/1.1 200 OK\r\nContent-Length: 999\r\n\r\n");
+    }
+}
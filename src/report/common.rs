@@ -0,0 +1,38 @@
+use crate::{
+    config::RudraConfig,
+    models::{EndpointConfiguration, Grouping},
+};
+
+pub fn sort_endpoints<'a>(endpoints: &[&'a EndpointConfiguration]) -> Vec<&'a EndpointConfiguration> {
+    let mut sorted_endpoints = endpoints.to_vec();
+    sorted_endpoints.sort_by_cached_key(|endpoint| {
+        (
+            endpoint.runtime.port,
+            endpoint.path.to_string(),
+            endpoint.method.clone(),
+            endpoint.status_code,
+        )
+    });
+    sorted_endpoints
+}
+
+// ignored endpoints count as covered, reports still need to tell them apart
+pub fn is_ignored(endpoint: &EndpointConfiguration, groupings: &[&Grouping]) -> bool {
+    groupings
+        .iter()
+        .any(|grouping| grouping.is_ignore_group && grouping.incompases_endpoint_config(endpoint))
+}
+
+// groupings are stored in a set, sorting them keeps the indices stable between runs
+pub fn get_sorted_groupings(config: &RudraConfig) -> Vec<&Grouping> {
+    let mut groupings: Vec<&Grouping> = config.groupings.iter().collect();
+    groupings.sort_by_cached_key(|grouping| {
+        (
+            grouping.path().to_string(),
+            grouping.methods().clone(),
+            grouping.status().clone(),
+            grouping.is_ignore_group,
+        )
+    });
+    groupings
+}
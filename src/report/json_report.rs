@@ -0,0 +1,509 @@
+use std::{cmp::Ordering, fs, path::Path, sync::Arc};
+
+use json::{object, JsonValue};
+
+use crate::{
+    config::{OpenapiSource, RudraConfig, Runtime},
+    evaluator::{
+        BodyCoverage, BodyCoverageItem, DetailedEvaluation, EnumValueCoverage, Evaluation, MediaTypeDirection, MediaTypeVariant,
+        OperationParameters, ParameterEvaluation, ResponseConformance,
+    },
+    models::{EndpointConfiguration, Grouping, Parameter, StatusMatcher},
+    utils::Error,
+};
+
+use super::common::{get_sorted_groupings, is_ignored, sort_endpoints};
+
+// increase whenever fields are removed or change their meaning, adding fields is backwards compatible
+pub const JSON_REPORT_SCHEMA_VERSION: u32 = 1;
+
+pub fn write_json_report(report: &JsonValue, path: &Path) -> Result<(), Error> {
+    match fs::write(path, report.pretty(2)) {
+        Ok(_) => Ok(()),
+        Err(why) => Err(Error::UnexpectedIOIssue(format!(
+            "issue writing json report {:?} due to: {}",
+            path, why
+        ))),
+    }
+}
+
+pub fn build_json_report(
+    config: &RudraConfig,
+    evaluation: &Evaluation,
+    detailed_evaluation: &DetailedEvaluation,
+) -> JsonValue {
+    let groupings = get_sorted_groupings(config);
+    let query_parameter_evaluation = &detailed_evaluation.query_parameters;
+    let header_parameter_evaluation = &detailed_evaluation.header_parameters;
+
+    let mut runtimes = JsonValue::new_array();
+    for runtime in &config.runtimes {
+        // pushing to a json array can only fail if it isn't an array
+        runtimes.push(runtime_to_json(runtime, evaluation)).unwrap();
+    }
+
+    let mut covered_endpoints = JsonValue::new_array();
+    for endpoint in sort_endpoints(&evaluation.endpoints_covered) {
+        covered_endpoints.push(endpoint_to_json(endpoint, &groupings, evaluation.hits(endpoint))).unwrap();
+    }
+
+    let mut uncovered_endpoints = JsonValue::new_array();
+    for endpoint in sort_endpoints(&evaluation.endpoints_not_covered) {
+        uncovered_endpoints.push(endpoint_to_json(endpoint, &groupings, evaluation.hits(endpoint))).unwrap();
+    }
+
+    let mut endpoints_missing_in_spec = JsonValue::new_array();
+    for undocumented_endpoint in &evaluation.endpoints_missing_in_spec {
+        let endpoint = &undocumented_endpoint.endpoint;
+        endpoints_missing_in_spec.push(object! {
+            path: endpoint.path.to_string(),
+            method: endpoint.method.as_str(),
+            status_code: status_to_json(&endpoint.status_code),
+            runtime_port: endpoint.runtime.port,
+            hits: undocumented_endpoint.hits,
+            first_seen: undocumented_endpoint.first_seen,
+            last_seen: undocumented_endpoint.last_seen,
+        }).unwrap();
+    }
+
+    let mut enum_values = JsonValue::new_array();
+    let mut sorted_enum_value_coverages: Vec<&EnumValueCoverage> = detailed_evaluation.enum_values.iter().collect();
+    sorted_enum_value_coverages.sort_by(|a, b| {
+        compare_operations(a.operation, b.operation)
+            .then_with(|| a.parameter.location.as_str().cmp(b.parameter.location.as_str()))
+            .then_with(|| a.parameter.name.cmp(&b.parameter.name))
+    });
+    for enum_value_coverage in sorted_enum_value_coverages {
+        enum_values.push(enum_value_coverage_to_json(enum_value_coverage)).unwrap();
+    }
+
+    let mut request_bodies = JsonValue::new_array();
+    let mut sorted_body_coverages: Vec<&BodyCoverage> = detailed_evaluation.request_bodies.iter().collect();
+    sorted_body_coverages.sort_by(|a, b| compare_operations(a.operation, b.operation));
+    for body_coverage in sorted_body_coverages {
+        request_bodies.push(body_coverage_to_json(body_coverage)).unwrap();
+    }
+
+    let mut response_conformance = JsonValue::new_array();
+    let mut sorted_response_conformances: Vec<&ResponseConformance> = detailed_evaluation.response_conformances.iter().collect();
+    sorted_response_conformances.sort_by(|a, b| {
+        compare_operations(a.endpoint, b.endpoint).then_with(|| a.endpoint.status_code.cmp(&b.endpoint.status_code))
+    });
+    for conformance in sorted_response_conformances {
+        response_conformance.push(response_conformance_to_json(conformance)).unwrap();
+    }
+
+    let mut media_types = JsonValue::new_array();
+    if let Some(media_type_evaluation) = &detailed_evaluation.media_types {
+        for variant in &media_type_evaluation.variants {
+            media_types.push(media_type_variant_to_json(variant)).unwrap();
+        }
+    }
+
+    object! {
+        schema_version: JSON_REPORT_SCHEMA_VERSION,
+        configuration: object! {
+            test_coverage: config.test_coverage,
+            undocumented_limit: config.undocumented_limit,
+            query_parameter_coverage: config.query_parameter_coverage,
+            media_type_coverage: config.media_type_coverage,
+            redacted_headers: config.redacted_headers.clone(),
+            security_accounts_for_forbidden: config.security_accounts_for_forbidden,
+            security_accounts_for_unautorized: config.security_accounts_for_unautorized,
+            is_merge: config.is_merge,
+            only_account_for_merge: config.only_account_for_merge,
+            groupings: groupings.iter().map(|grouping| grouping_to_json(grouping)).collect::<Vec<JsonValue>>(),
+        },
+        result: object! {
+            test_coverage: evaluation.test_coverage,
+            meets_test_coverage: evaluation.meets_test_coverage(config.test_coverage),
+            has_gateway_issues: evaluation.has_gateway_issues,
+            observed_requests: evaluation.observed_requests,
+            undocumented_requests: evaluation.undocumented_requests(),
+            exceeds_undocumented_limit: evaluation.exceeds_undocumented_limit(config.undocumented_limit),
+            query_parameter_coverage: query_parameter_evaluation.parameter_coverage,
+            meets_query_parameter_coverage: query_parameter_evaluation.meets_parameter_coverage(config.query_parameter_coverage),
+            header_parameter_coverage: header_parameter_evaluation.parameter_coverage,
+            media_type_coverage: detailed_evaluation.media_types.as_ref().map(|x| x.media_type_coverage),
+        },
+        runtimes: runtimes,
+        covered_endpoints: covered_endpoints,
+        uncovered_endpoints: uncovered_endpoints,
+        endpoints_missing_in_spec: endpoints_missing_in_spec,
+        query_parameters: parameter_evaluation_to_json(query_parameter_evaluation),
+        header_parameters: parameter_evaluation_to_json(header_parameter_evaluation),
+        enum_values: enum_values,
+        request_bodies: request_bodies,
+        response_conformance: response_conformance,
+        media_types: media_types,
+    }
+}
+
+fn compare_operations(a: &EndpointConfiguration, b: &EndpointConfiguration) -> Ordering {
+    a.runtime.port
+        .cmp(&b.runtime.port)
+        .then_with(|| a.path.to_string().cmp(&b.path.to_string()))
+        .then_with(|| a.method.cmp(&b.method))
+}
+
+fn runtime_to_json(runtime: &Arc<Runtime>, evaluation: &Evaluation) -> JsonValue {
+    let covered = evaluation.endpoints_covered.iter().filter(|x| &x.runtime == runtime).count();
+    let not_covered = evaluation.endpoints_not_covered.iter().filter(|x| &x.runtime == runtime).count();
+    let test_coverage = if covered + not_covered == 0 {
+        1.0
+    } else {
+        covered as f32 / (covered + not_covered) as f32
+    };
+
+    let openapi_source = match &runtime.openapi_source {
+        OpenapiSource::Path(path) => path.to_string_lossy().to_string(),
+        OpenapiSource::Url(url) => url.to_string(),
+    };
+
+    object! {
+        port: runtime.port,
+        app_base_url: runtime.app_base_url.as_str(),
+        openapi_source: openapi_source,
+        test_coverage: test_coverage,
+        endpoints_covered: covered,
+        endpoints_total: covered + not_covered,
+    }
+}
+
+fn endpoint_to_json(
+    endpoint: &EndpointConfiguration,
+    groupings: &[&Grouping],
+    hits: usize,
+) -> JsonValue {
+    let grouping_indices: Vec<usize> = groupings
+        .iter()
+        .enumerate()
+        .filter(|(_, grouping)| grouping.incompases_endpoint_config(endpoint))
+        .map(|(index, _)| index)
+        .collect();
+    let is_ignored = is_ignored(endpoint, groupings);
+
+    object! {
+        path: endpoint.path.to_string(),
+        method: endpoint.method.as_str(),
+        status_code: status_to_json(&endpoint.status_code),
+        runtime_port: endpoint.runtime.port,
+        is_generated: endpoint.is_generated,
+        is_ignored: is_ignored,
+        groupings: grouping_indices,
+        hits: hits,
+    }
+}
+
+fn parameter_evaluation_to_json(parameter_evaluation: &ParameterEvaluation) -> JsonValue {
+    let mut operations: Vec<&OperationParameters> = parameter_evaluation.operations.iter().collect();
+    operations.sort_by(|a, b| compare_operations(a.operation, b.operation));
+    JsonValue::Array(operations.into_iter().map(operation_parameters_to_json).collect())
+}
+
+fn operation_parameters_to_json(operation_parameters: &OperationParameters) -> JsonValue {
+    let operation = operation_parameters.operation;
+    object! {
+        path: operation.path.to_string(),
+        method: operation.method.as_str(),
+        runtime_port: operation.runtime.port,
+        sent_parameters: operation_parameters.sent_parameters.iter().map(|x| parameter_to_json(x)).collect::<Vec<JsonValue>>(),
+        unsent_parameters: operation_parameters.unsent_parameters.iter().map(|x| parameter_to_json(x)).collect::<Vec<JsonValue>>(),
+        unenforced_parameters: operation_parameters
+            .unenforced_parameters
+            .iter()
+            .map(|x| object! { name: x.parameter.name.as_str(), successful_requests: x.successful_requests })
+            .collect::<Vec<JsonValue>>(),
+    }
+}
+
+fn enum_value_coverage_to_json(enum_value_coverage: &EnumValueCoverage) -> JsonValue {
+    let operation = enum_value_coverage.operation;
+    object! {
+        path: operation.path.to_string(),
+        method: operation.method.as_str(),
+        runtime_port: operation.runtime.port,
+        parameter: enum_value_coverage.parameter.name.as_str(),
+        location: enum_value_coverage.parameter.location.as_str(),
+        observed_values: enum_value_coverage.observed_values.clone(),
+        unobserved_values: enum_value_coverage.unobserved_values.clone(),
+    }
+}
+
+fn body_coverage_to_json(body_coverage: &BodyCoverage) -> JsonValue {
+    let operation = body_coverage.operation;
+    object! {
+        path: operation.path.to_string(),
+        method: operation.method.as_str(),
+        runtime_port: operation.runtime.port,
+        observed_bodies: body_coverage.observed_bodies,
+        optional_properties: body_coverage_items_to_json(&body_coverage.optional_properties),
+        branches: body_coverage_items_to_json(&body_coverage.branches),
+        discriminator_values: body_coverage_items_to_json(&body_coverage.discriminator_values),
+    }
+}
+
+fn body_coverage_items_to_json(items: &[BodyCoverageItem]) -> Vec<JsonValue> {
+    items
+        .iter()
+        .map(|item| object! { name: item.name.as_str(), is_exercised: item.is_exercised })
+        .collect()
+}
+
+fn response_conformance_to_json(conformance: &ResponseConformance) -> JsonValue {
+    let endpoint = conformance.endpoint;
+    object! {
+        path: endpoint.path.to_string(),
+        method: endpoint.method.as_str(),
+        status_code: status_to_json(&endpoint.status_code),
+        runtime_port: endpoint.runtime.port,
+        validated_responses: conformance.validated_responses,
+        violations: conformance
+            .violations
+            .iter()
+            .map(|violation| object! { description: violation.description.as_str(), occurrences: violation.occurrences })
+            .collect::<Vec<JsonValue>>(),
+    }
+}
+
+// request variants apply to every status code of the operation
+fn media_type_variant_to_json(variant: &MediaTypeVariant) -> JsonValue {
+    let endpoint = variant.endpoint;
+    let status_code = match variant.direction {
+        MediaTypeDirection::Request => JsonValue::Null,
+        MediaTypeDirection::Response => status_to_json(&endpoint.status_code),
+    };
+    object! {
+        path: endpoint.path.to_string(),
+        method: endpoint.method.as_str(),
+        status_code: status_code,
+        runtime_port: endpoint.runtime.port,
+        direction: variant.direction.as_str(),
+        media_type: variant.media_type,
+        hits: variant.hits,
+    }
+}
+
+fn parameter_to_json(parameter: &Parameter) -> JsonValue {
+    object! {
+        name: parameter.name.as_str(),
+        required: parameter.required,
+    }
+}
+
+// exact status codes stay numbers, ranges and default responses are written like in the spec
+fn status_to_json(status_code: &StatusMatcher) -> JsonValue {
+    match status_code {
+        StatusMatcher::Exact(status_code) => JsonValue::from(*status_code),
+        status_code => JsonValue::from(status_code.to_string()),
+    }
+}
+
+fn grouping_to_json(grouping: &Grouping) -> JsonValue {
+    object! {
+        path: grouping.path().to_string(),
+        methods: grouping.methods().iter().map(|method| method.as_str()).collect::<Vec<&str>>(),
+        status_codes: grouping.status().clone(),
+        is_ignore_group: grouping.is_ignore_group,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, str::FromStr, sync::Arc};
+
+    use crate::{
+        config::PathMatching,
+        evaluator::{evaluate, evaluate_details},
+        models::{EndpointConfiguration, Grouping, MediaType, Method, Observation, OpenapiPath, Parameter, ParameterLocation, Schema},
+        utils::test::{create_mock_config, create_mock_runtime, create_observations},
+    };
+
+    use super::{build_json_report, JSON_REPORT_SCHEMA_VERSION};
+
+    fn create_endpoint(method: Method, path: &str, status_code: u16) -> EndpointConfiguration {
+        EndpointConfiguration::new(method, path, status_code, Arc::new(create_mock_runtime()), false).unwrap()
+    }
+
+    #[test]
+    fn reports_covered_and_uncovered_endpoints() {
+        let mut config = create_mock_config();
+        config.runtimes = vec![Arc::new(create_mock_runtime())];
+        let openapi_endpoints = vec![
+            create_endpoint(Method::GET, "/foo/{bar}", 200),
+            create_endpoint(Method::POST, "/foo", 201),
+        ];
+        let observed_endpoints = vec![
+            create_endpoint(Method::GET, "/foo/1", 200),
+            create_endpoint(Method::GET, "/foo/2", 200),
+            create_endpoint(Method::GET, "/moo", 200),
+        ];
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&observed_endpoints), &HashSet::new(), &PathMatching::MostSpecific);
+        let detailed_evaluation = evaluate_details(&openapi_endpoints, &[], &HashSet::new(), false, &PathMatching::MostSpecific);
+
+        let report = build_json_report(&config, &evaluation, &detailed_evaluation);
+
+        assert_eq!(report["schema_version"], JSON_REPORT_SCHEMA_VERSION);
+        assert_eq!(report["result"]["observed_requests"], 3);
+        assert_eq!(report["result"]["undocumented_requests"], 1);
+        assert_eq!(report["runtimes"][0]["port"], 8080);
+        assert_eq!(report["runtimes"][0]["endpoints_total"], 2);
+        assert_eq!(report["covered_endpoints"].len(), 1);
+        assert_eq!(report["covered_endpoints"][0]["path"], "/foo/{bar}");
+        assert_eq!(report["covered_endpoints"][0]["hits"], 2);
+        assert_eq!(report["uncovered_endpoints"].len(), 1);
+        assert_eq!(report["uncovered_endpoints"][0]["method"], "POST");
+        assert_eq!(report["endpoints_missing_in_spec"][0]["path"], "/moo");
+    }
+
+    #[test]
+    fn reports_grouping_membership() {
+        let mut config = create_mock_config();
+        config.groupings = HashSet::new();
+        config.groupings.insert(Grouping::new(vec![Method::POST], vec![201], OpenapiPath::from_str("/foo").unwrap(), true));
+        let openapi_endpoints = vec![create_endpoint(Method::POST, "/foo", 201)];
+        let evaluation = evaluate(&openapi_endpoints, &None, &[], &config.groupings, &PathMatching::MostSpecific);
+        let detailed_evaluation = evaluate_details(&openapi_endpoints, &[], &config.groupings, false, &PathMatching::MostSpecific);
+
+        let report = build_json_report(&config, &evaluation, &detailed_evaluation);
+
+        assert_eq!(report["configuration"]["groupings"][0]["path"], "/foo");
+        assert_eq!(report["configuration"]["groupings"][0]["is_ignore_group"], true);
+        assert_eq!(report["covered_endpoints"][0]["groupings"][0], 0);
+        assert_eq!(report["covered_endpoints"][0]["is_ignored"], true);
+    }
+
+    #[test]
+    fn reports_query_parameters_per_operation() {
+        let mut config = create_mock_config();
+        config.query_parameter_coverage = Some(1.0);
+        let openapi_endpoints = vec![create_endpoint(Method::GET, "/search", 200).with_parameters(vec![
+            Parameter::new("filter", ParameterLocation::Query, true),
+            Parameter::new("page", ParameterLocation::Query, false),
+        ])];
+        let observations = vec![Observation::new(Method::GET, "/search?page=1", 200, Arc::new(create_mock_runtime())).unwrap()];
+        let evaluation = evaluate(&openapi_endpoints, &None, &observations, &HashSet::new(), &PathMatching::MostSpecific);
+        let detailed_evaluation = evaluate_details(&openapi_endpoints, &observations, &HashSet::new(), false, &PathMatching::MostSpecific);
+
+        let report = build_json_report(&config, &evaluation, &detailed_evaluation);
+
+        assert_eq!(report["result"]["query_parameter_coverage"], 0.5);
+        assert_eq!(report["result"]["meets_query_parameter_coverage"], false);
+        assert_eq!(report["query_parameters"][0]["path"], "/search");
+        assert_eq!(report["query_parameters"][0]["sent_parameters"][0]["name"], "page");
+        assert_eq!(report["query_parameters"][0]["unsent_parameters"][0]["name"], "filter");
+        assert_eq!(report["query_parameters"][0]["unsent_parameters"][0]["required"], true);
+    }
+
+    #[test]
+    fn reports_header_parameters_per_operation() {
+        let config = create_mock_config();
+        let openapi_endpoints = vec![create_endpoint(Method::GET, "/search", 200).with_parameters(vec![
+            Parameter::new("X-Tenant-Id", ParameterLocation::Header, true),
+            Parameter::new("If-None-Match", ParameterLocation::Header, false),
+        ])];
+        let observations = vec![Observation::new(Method::GET, "/search", 200, Arc::new(create_mock_runtime()))
+            .unwrap()
+            .with_request_header_values(vec![("If-None-Match".to_string(), "\"abc\"".to_string())])];
+        let evaluation = evaluate(&openapi_endpoints, &None, &observations, &HashSet::new(), &PathMatching::MostSpecific);
+        let detailed_evaluation = evaluate_details(&openapi_endpoints, &observations, &HashSet::new(), false, &PathMatching::MostSpecific);
+
+        let report = build_json_report(&config, &evaluation, &detailed_evaluation);
+
+        assert_eq!(report["configuration"]["redacted_headers"][0], "authorization");
+        assert_eq!(report["result"]["header_parameter_coverage"], 0.5);
+        assert!(report["query_parameters"].is_empty());
+        assert_eq!(report["header_parameters"][0]["sent_parameters"][0]["name"], "If-None-Match");
+        assert_eq!(report["header_parameters"][0]["unsent_parameters"][0]["name"], "X-Tenant-Id");
+        assert_eq!(report["header_parameters"][0]["unenforced_parameters"][0]["name"], "X-Tenant-Id");
+        assert_eq!(report["header_parameters"][0]["unenforced_parameters"][0]["successful_requests"], 1);
+    }
+
+    #[test]
+    fn reports_enum_values_per_parameter() {
+        let config = create_mock_config();
+        let openapi_endpoints = vec![create_endpoint(Method::GET, "/tickets", 200).with_parameters(vec![
+            Parameter::new("status", ParameterLocation::Query, false)
+                .with_enum_values(vec!["open".to_string(), "archived".to_string()]),
+        ])];
+        let observations = vec![Observation::new(Method::GET, "/tickets?status=open", 200, Arc::new(create_mock_runtime())).unwrap()];
+        let evaluation = evaluate(&openapi_endpoints, &None, &observations, &HashSet::new(), &PathMatching::MostSpecific);
+        let detailed_evaluation = evaluate_details(&openapi_endpoints, &observations, &HashSet::new(), false, &PathMatching::MostSpecific);
+
+        let report = build_json_report(&config, &evaluation, &detailed_evaluation);
+
+        assert_eq!(report["enum_values"][0]["parameter"], "status");
+        assert_eq!(report["enum_values"][0]["location"], "query");
+        assert_eq!(report["enum_values"][0]["observed_values"][0], "open");
+        assert_eq!(report["enum_values"][0]["unobserved_values"][0], "archived");
+    }
+
+    #[test]
+    fn reports_request_body_cases_per_operation() {
+        let config = create_mock_config();
+        let schema = Schema {
+            properties: vec![("name".to_string(), Schema::default()), ("nickname".to_string(), Schema::default())],
+            required: vec!["name".to_string()],
+            ..Schema::default()
+        };
+        let openapi_endpoints = vec![create_endpoint(Method::POST, "/pets", 201).with_request_body(Some(Arc::new(schema)))];
+        let observations = vec![Observation::new(Method::POST, "/pets", 201, Arc::new(create_mock_runtime()))
+            .unwrap()
+            .with_request_body(br#"{"name": "Rex"}"#)];
+        let evaluation = evaluate(&openapi_endpoints, &None, &observations, &HashSet::new(), &PathMatching::MostSpecific);
+        let detailed_evaluation = evaluate_details(&openapi_endpoints, &observations, &HashSet::new(), false, &PathMatching::MostSpecific);
+
+        let report = build_json_report(&config, &evaluation, &detailed_evaluation);
+
+        assert_eq!(report["request_bodies"][0]["path"], "/pets");
+        assert_eq!(report["request_bodies"][0]["observed_bodies"], 1);
+        assert_eq!(report["request_bodies"][0]["optional_properties"][0]["name"], "$.nickname");
+        assert_eq!(report["request_bodies"][0]["optional_properties"][0]["is_exercised"], false);
+    }
+
+    #[test]
+    fn reports_response_violations_per_endpoint() {
+        let config = create_mock_config();
+        let openapi_endpoints = vec![create_endpoint(Method::GET, "/pets", 200)
+            .with_response_media_types(vec![MediaType::new("application/json", None)])];
+        let observations = vec![Observation::new(Method::GET, "/pets", 200, Arc::new(create_mock_runtime()))
+            .unwrap()
+            .with_response(Some("text/html"), b"")];
+        let evaluation = evaluate(&openapi_endpoints, &None, &observations, &HashSet::new(), &PathMatching::MostSpecific);
+        let detailed_evaluation = evaluate_details(&openapi_endpoints, &observations, &HashSet::new(), false, &PathMatching::MostSpecific);
+
+        let report = build_json_report(&config, &evaluation, &detailed_evaluation);
+
+        assert_eq!(report["response_conformance"][0]["status_code"], 200);
+        assert_eq!(report["response_conformance"][0]["validated_responses"], 1);
+        assert_eq!(report["response_conformance"][0]["violations"][0]["description"], "undeclared content type text/html");
+        assert_eq!(report["response_conformance"][0]["violations"][0]["occurrences"], 1);
+    }
+
+    #[test]
+    fn reports_media_types_only_if_enabled() {
+        let mut config = create_mock_config();
+        let openapi_endpoints = vec![create_endpoint(Method::GET, "/reports", 200).with_response_media_types(vec![
+            MediaType::new("application/json", None),
+            MediaType::new("text/csv", None),
+        ])];
+        let observations = vec![Observation::new(Method::GET, "/reports", 200, Arc::new(create_mock_runtime()))
+            .unwrap()
+            .with_response(Some("text/csv"), b"")];
+        let evaluation = evaluate(&openapi_endpoints, &None, &observations, &HashSet::new(), &PathMatching::MostSpecific);
+
+        let detailed_evaluation = evaluate_details(&openapi_endpoints, &observations, &HashSet::new(), false, &PathMatching::MostSpecific);
+        let report = build_json_report(&config, &evaluation, &detailed_evaluation);
+        assert!(report["result"]["media_type_coverage"].is_null());
+        assert!(report["media_types"].is_empty());
+
+        config.media_type_coverage = true;
+        let detailed_evaluation = evaluate_details(&openapi_endpoints, &observations, &HashSet::new(), true, &PathMatching::MostSpecific);
+        let report = build_json_report(&config, &evaluation, &detailed_evaluation);
+        assert_eq!(report["result"]["media_type_coverage"], 0.5);
+        assert_eq!(report["media_types"][1]["media_type"], "text/csv");
+        assert_eq!(report["media_types"][1]["direction"], "response");
+        assert_eq!(report["media_types"][1]["status_code"], 200);
+        assert_eq!(report["media_types"][1]["hits"], 1);
+    }
+}
@@ -0,0 +1,200 @@
+use std::{fmt::Write, fs, path::Path, sync::Arc};
+
+use crate::{
+    config::{RudraConfig, Runtime},
+    evaluator::Evaluation,
+    models::{EndpointConfiguration, Grouping},
+    utils::Error,
+};
+
+use super::common::{get_sorted_groupings, is_ignored, sort_endpoints};
+
+const TESTSUITES_NAME: &str = "rudra";
+
+enum TestcaseResult {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+pub fn write_junit_report(config: &RudraConfig, evaluation: &Evaluation, path: &Path) -> Result<(), Error> {
+    let report = build_junit_report(config, evaluation);
+    match fs::write(path, report) {
+        Ok(_) => Ok(()),
+        Err(why) => Err(Error::UnexpectedIOIssue(format!(
+            "issue writing junit report {:?} due to: {}",
+            path, why
+        ))),
+    }
+}
+
+pub fn build_junit_report(config: &RudraConfig, evaluation: &Evaluation) -> String {
+    let groupings = get_sorted_groupings(config);
+
+    let mut testsuites = String::new();
+    let mut total_tests = 0;
+    let mut total_failures = 0;
+    let mut total_skipped = 0;
+    for runtime in &config.runtimes {
+        let testcases = get_testcases(runtime, evaluation, &groupings);
+        let failures = testcases.iter().filter(|(_, result)| matches!(result, TestcaseResult::Failed)).count();
+        let skipped = testcases.iter().filter(|(_, result)| matches!(result, TestcaseResult::Skipped)).count();
+        total_tests += testcases.len();
+        total_failures += failures;
+        total_skipped += skipped;
+
+        // writing to a string can't fail
+        writeln!(
+            testsuites,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\">",
+            escape_xml(&get_testsuite_name(runtime)),
+            testcases.len(),
+            failures,
+            skipped
+        )
+        .unwrap();
+        for (endpoint, result) in testcases {
+            write_testcase(&mut testsuites, runtime, endpoint, result);
+        }
+        writeln!(testsuites, "  </testsuite>").unwrap();
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\">\n{}</testsuites>\n",
+        TESTSUITES_NAME, total_tests, total_failures, total_skipped, testsuites
+    )
+}
+
+fn get_testcases<'a>(
+    runtime: &Arc<Runtime>,
+    evaluation: &Evaluation<'a>,
+    groupings: &[&Grouping],
+) -> Vec<(&'a EndpointConfiguration, TestcaseResult)> {
+    let covered_endpoints: Vec<&EndpointConfiguration> = evaluation
+        .endpoints_covered
+        .iter()
+        .filter(|endpoint| &endpoint.runtime == runtime)
+        .copied()
+        .collect();
+    let not_covered_endpoints: Vec<&EndpointConfiguration> = evaluation
+        .endpoints_not_covered
+        .iter()
+        .filter(|endpoint| &endpoint.runtime == runtime)
+        .copied()
+        .collect();
+
+    let mut testcases = vec![];
+    for endpoint in sort_endpoints(&covered_endpoints) {
+        if is_ignored(endpoint, groupings) {
+            testcases.push((endpoint, TestcaseResult::Skipped));
+        } else {
+            testcases.push((endpoint, TestcaseResult::Passed));
+        }
+    }
+    for endpoint in sort_endpoints(&not_covered_endpoints) {
+        testcases.push((endpoint, TestcaseResult::Failed));
+    }
+    testcases
+}
+
+fn write_testcase(output: &mut String, runtime: &Runtime, endpoint: &EndpointConfiguration, result: TestcaseResult) {
+    let name = escape_xml(&format!("{} {} {}", endpoint.method, endpoint.path, endpoint.status_code));
+    let classname = escape_xml(&get_testsuite_name(runtime));
+    match result {
+        TestcaseResult::Passed => {
+            writeln!(output, "    <testcase name=\"{}\" classname=\"{}\"/>", name, classname).unwrap();
+        }
+        TestcaseResult::Failed => {
+            writeln!(output, "    <testcase name=\"{}\" classname=\"{}\">", name, classname).unwrap();
+            writeln!(
+                output,
+                "      <failure message=\"endpoint was not covered by any request\" type=\"uncovered\"/>"
+            )
+            .unwrap();
+            writeln!(output, "    </testcase>").unwrap();
+        }
+        TestcaseResult::Skipped => {
+            writeln!(output, "    <testcase name=\"{}\" classname=\"{}\">", name, classname).unwrap();
+            writeln!(output, "      <skipped message=\"endpoint is part of an ignored grouping\"/>").unwrap();
+            writeln!(output, "    </testcase>").unwrap();
+        }
+    }
+}
+
+fn get_testsuite_name(runtime: &Runtime) -> String {
+    format!("{}.{}", TESTSUITES_NAME, runtime.port)
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // control characters aren't allowed in xml 1.0
+            character if character.is_control() && !['\t', '\n', '\r'].contains(&character) => (),
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, str::FromStr, sync::Arc};
+
+    use crate::{
+        config::PathMatching,
+        evaluator::evaluate,
+        models::{EndpointConfiguration, Grouping, Method, OpenapiPath},
+        utils::test::{create_mock_config, create_mock_runtime, create_observations},
+    };
+
+    use super::{build_junit_report, escape_xml};
+
+    #[test]
+    fn creates_testcase_per_endpoint() {
+        let runtime = Arc::new(create_mock_runtime());
+        let mut config = create_mock_config();
+        config.runtimes = vec![runtime.clone()];
+        config.groupings = HashSet::new();
+        config.groupings.insert(Grouping::new(vec![Method::DELETE], vec![204], OpenapiPath::from_str("/foo").unwrap(), true));
+        let openapi_endpoints = vec![
+            EndpointConfiguration::new(Method::GET, "/foo", 200, runtime.clone(), false).unwrap(),
+            EndpointConfiguration::new(Method::POST, "/foo", 201, runtime.clone(), false).unwrap(),
+            EndpointConfiguration::new(Method::DELETE, "/foo", 204, runtime.clone(), false).unwrap(),
+        ];
+        let observed_endpoints = [EndpointConfiguration::new(Method::GET, "/foo", 200, runtime, false).unwrap()];
+        let evaluation = evaluate(&openapi_endpoints, &None, &create_observations(&observed_endpoints), &config.groupings, &PathMatching::MostSpecific);
+
+        let report = build_junit_report(&config, &evaluation);
+
+        assert!(report.contains("<testsuite name=\"rudra.8080\" tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\">"));
+        assert!(report.contains("<testcase name=\"GET /foo 200\" classname=\"rudra.8080\"/>"));
+        assert!(report.contains("<testcase name=\"POST /foo 201\" classname=\"rudra.8080\">\n      <failure"));
+        assert!(report.contains("<testcase name=\"DELETE /foo 204\" classname=\"rudra.8080\">\n      <skipped"));
+    }
+
+    #[test]
+    fn creates_testsuite_per_runtime() {
+        let mut other_runtime = create_mock_runtime();
+        other_runtime.port = 8081;
+        let mut config = create_mock_config();
+        config.runtimes = vec![Arc::new(create_mock_runtime()), Arc::new(other_runtime)];
+        let openapi_endpoints = vec![];
+        let evaluation = evaluate(&openapi_endpoints, &None, &[], &HashSet::new(), &PathMatching::MostSpecific);
+
+        let report = build_junit_report(&config, &evaluation);
+
+        assert!(report.contains("<testsuite name=\"rudra.8080\" tests=\"0\""));
+        assert!(report.contains("<testsuite name=\"rudra.8081\" tests=\"0\""));
+    }
+
+    #[test]
+    fn escapes_special_characters() {
+        assert_eq!(escape_xml("/a<b>&\"c'\u{1}"), "/a&lt;b&gt;&amp;&quot;c&apos;");
+    }
+}
@@ -0,0 +1,12 @@
+mod common;
+mod json_report;
+mod junit_report;
+mod text_report;
+pub use json_report::build_json_report;
+pub use json_report::write_json_report;
+pub use json_report::JSON_REPORT_SCHEMA_VERSION;
+pub use junit_report::build_junit_report;
+pub use junit_report::write_junit_report;
+pub use text_report::build_text_report;
+pub use text_report::get_json_report_exit_code;
+pub use text_report::read_json_report;
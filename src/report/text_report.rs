@@ -0,0 +1,255 @@
+use std::{fmt::Write, path::Path};
+
+use json::JsonValue;
+
+use crate::utils::{read_file_to_string_or_err, Error, ExitCode};
+
+use super::JSON_REPORT_SCHEMA_VERSION;
+
+pub fn read_json_report(path: &Path) -> Result<JsonValue, Error> {
+    let report_string = read_file_to_string_or_err(path, Error::InvalidJsonReport(format!("{:?} can't be opened", path)))?;
+    let report = match json::parse(&report_string) {
+        Ok(report) => report,
+        Err(why) => return Err(Error::InvalidJsonReport(why.to_string())),
+    };
+    match report["schema_version"].as_u32() {
+        Some(JSON_REPORT_SCHEMA_VERSION) => Ok(report),
+        Some(schema_version) => Err(Error::InvalidJsonReport(format!("schema version {} isn't supported", schema_version))),
+        None => Err(Error::InvalidJsonReport("schema version is missing".to_string())),
+    }
+}
+
+// renders a saved json report like the evaluation stage prints its results
+pub fn build_text_report(report: &JsonValue) -> String {
+    let configuration = &report["configuration"];
+    let result = &report["result"];
+    let mut output = String::new();
+
+    if result["has_gateway_issues"].as_bool().unwrap_or(false) {
+        writeln!(output, "WARNING: an unusual amount of 502 status codes were found, your setup might have gateway issues.").unwrap();
+    }
+
+    writeln!(output, "Test Coverage: {}%", get_percentage(&result["test_coverage"])).unwrap();
+    write_list(&mut output, "The following endpoints were missed:", &report["uncovered_endpoints"], |endpoint| {
+        format!("{} {} {}", endpoint["path"], endpoint["method"], endpoint["status_code"])
+    });
+    write_list(&mut output, "The following requests are missing in the openapi spec:", &report["endpoints_missing_in_spec"], |endpoint| {
+        format!("{} {} {} ({} hits)", endpoint["path"], endpoint["method"], endpoint["status_code"], endpoint["hits"])
+    });
+
+    write_parameters(&mut output, "Query", &result["query_parameter_coverage"], &report["query_parameters"]);
+    write_parameters(&mut output, "Header", &result["header_parameter_coverage"], &report["header_parameters"]);
+
+    let incomplete_enum_values = filter(&report["enum_values"], |x| !x["unobserved_values"].is_empty());
+    write_list(&mut output, "The following enum values were never sent:", &incomplete_enum_values, |x| {
+        format!("{} {} {}: {}", x["path"], x["method"], x["parameter"], join(&x["unobserved_values"], |value| value.to_string()))
+    });
+
+    let incomplete_request_bodies = filter(&report["request_bodies"], |x| !get_unexercised_items(x).is_empty());
+    write_list(&mut output, "The following request body cases were never sent:", &incomplete_request_bodies, |x| {
+        format!("{} {} ({} bodies observed): {}", x["path"], x["method"], x["observed_bodies"], get_unexercised_items(x).join(", "))
+    });
+
+    let nonconforming_responses = filter(&report["response_conformance"], |x| !x["violations"].is_empty());
+    if !nonconforming_responses.is_empty() {
+        writeln!(output, "The following responses don't conform to the openapi spec:").unwrap();
+        for conformance in nonconforming_responses.members() {
+            writeln!(output, "- {} {} {}:", conformance["path"], conformance["method"], conformance["status_code"]).unwrap();
+            for violation in conformance["violations"].members() {
+                writeln!(
+                    output,
+                    "  - {} ({} of {} responses)",
+                    violation["description"], violation["occurrences"], conformance["validated_responses"]
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    if !result["media_type_coverage"].is_null() {
+        writeln!(output, "Media Type Coverage: {}%", get_percentage(&result["media_type_coverage"])).unwrap();
+        let uncovered_media_types = filter(&report["media_types"], |x| x["hits"] == 0);
+        write_list(&mut output, "The following media types were never exercised:", &uncovered_media_types, |x| {
+            match x["status_code"].is_null() {
+                true => format!("{} {} {}: {}", x["path"], x["method"], x["direction"], x["media_type"]),
+                false => format!("{} {} {} {}: {}", x["path"], x["method"], x["status_code"], x["direction"], x["media_type"]),
+            }
+        });
+    }
+
+    let test_coverage = get_percentage(&result["test_coverage"]);
+    let required_test_coverage = get_percentage(&configuration["test_coverage"]);
+    match result["meets_test_coverage"].as_bool().unwrap_or(false) {
+        true => writeln!(output, "PASSED: test coverage of {:.2}% meets the required {:.2}%", test_coverage, required_test_coverage).unwrap(),
+        false => writeln!(
+            output,
+            "FAILED: test coverage of {:.2}% is {:.2}% below the required {:.2}%",
+            test_coverage,
+            required_test_coverage - test_coverage,
+            required_test_coverage
+        )
+        .unwrap(),
+    }
+
+    if result["exceeds_undocumented_limit"].as_bool().unwrap_or(false) {
+        writeln!(
+            output,
+            "FAILED: {} requests are missing in the openapi spec, only {} are allowed",
+            result["undocumented_requests"], configuration["undocumented_limit"]
+        )
+        .unwrap();
+    }
+
+    if !configuration["query_parameter_coverage"].is_null() {
+        let query_parameter_coverage = get_percentage(&result["query_parameter_coverage"]);
+        let required_query_parameter_coverage = get_percentage(&configuration["query_parameter_coverage"]);
+        match result["meets_query_parameter_coverage"].as_bool().unwrap_or(false) {
+            true => writeln!(
+                output,
+                "PASSED: query parameter coverage of {:.2}% meets the required {:.2}%",
+                query_parameter_coverage, required_query_parameter_coverage
+            )
+            .unwrap(),
+            false => writeln!(
+                output,
+                "FAILED: query parameter coverage of {:.2}% is {:.2}% below the required {:.2}%",
+                query_parameter_coverage,
+                required_query_parameter_coverage - query_parameter_coverage,
+                required_query_parameter_coverage
+            )
+            .unwrap(),
+        }
+    }
+
+    output
+}
+
+// same precedence as the evaluation stage
+pub fn get_json_report_exit_code(report: &JsonValue) -> ExitCode {
+    let result = &report["result"];
+    if result["has_gateway_issues"].as_bool().unwrap_or(false) {
+        ExitCode::GatewayIssues
+    } else if !result["meets_test_coverage"].as_bool().unwrap_or(false) {
+        ExitCode::CoverageBelowThreshold
+    } else if result["exceeds_undocumented_limit"].as_bool().unwrap_or(false) {
+        ExitCode::UndocumentedTraffic
+    } else if !result["meets_query_parameter_coverage"].as_bool().unwrap_or(true) {
+        ExitCode::ParameterCoverageBelowThreshold
+    } else {
+        ExitCode::Success
+    }
+}
+
+fn write_parameters(output: &mut String, location: &str, parameter_coverage: &JsonValue, operations: &JsonValue) {
+    if !operations.is_empty() {
+        writeln!(output, "{} Parameter Coverage: {}%", location, get_percentage(parameter_coverage)).unwrap();
+    }
+
+    let operations_with_unsent_parameters = filter(operations, |x| !x["unsent_parameters"].is_empty());
+    let heading = format!("The following {} parameters were never sent:", location.to_lowercase());
+    write_list(output, &heading, &operations_with_unsent_parameters, |x| {
+        let unsent_parameters = join(&x["unsent_parameters"], |parameter| match parameter["required"].as_bool() {
+            Some(true) => format!("{} (required)", parameter["name"]),
+            _ => parameter["name"].to_string(),
+        });
+        format!("{} {}: {}", x["path"], x["method"], unsent_parameters)
+    });
+
+    let operations_with_unenforced_parameters = filter(operations, |x| !x["unenforced_parameters"].is_empty());
+    let heading = format!("The following required {} parameters were missing in successful requests:", location.to_lowercase());
+    write_list(output, &heading, &operations_with_unenforced_parameters, |x| {
+        let unenforced_parameters = join(&x["unenforced_parameters"], |parameter| {
+            format!("{} ({} requests)", parameter["name"], parameter["successful_requests"])
+        });
+        format!("{} {}: {}", x["path"], x["method"], unenforced_parameters)
+    });
+}
+
+fn write_list<F: Fn(&JsonValue) -> String>(output: &mut String, heading: &str, items: &JsonValue, format_item: F) {
+    if items.is_empty() {
+        return;
+    }
+    writeln!(output, "{}", heading).unwrap();
+    for item in items.members() {
+        writeln!(output, "- {}", format_item(item)).unwrap();
+    }
+}
+
+fn filter<F: Fn(&JsonValue) -> bool>(items: &JsonValue, predicate: F) -> JsonValue {
+    JsonValue::Array(items.members().filter(|item| predicate(item)).cloned().collect())
+}
+
+fn join<F: Fn(&JsonValue) -> String>(items: &JsonValue, format_item: F) -> String {
+    items.members().map(format_item).collect::<Vec<String>>().join(", ")
+}
+
+fn get_unexercised_items(request_body: &JsonValue) -> Vec<String> {
+    ["optional_properties", "branches", "discriminator_values"]
+        .iter()
+        .flat_map(|kind| request_body[*kind].members())
+        .filter(|item| item["is_exercised"] == false)
+        .map(|item| item["name"].to_string())
+        .collect()
+}
+
+fn get_percentage(coverage: &JsonValue) -> f32 {
+    coverage.as_f32().unwrap_or(0.0) * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Arc};
+
+    use crate::{
+        config::PathMatching,
+        evaluator::{evaluate, evaluate_details},
+        models::{EndpointConfiguration, Method, Observation, Parameter, ParameterLocation},
+        report::build_json_report,
+        utils::{
+            test::{create_mock_config, create_mock_runtime},
+            ExitCode,
+        },
+    };
+
+    use super::{build_text_report, get_json_report_exit_code};
+
+    #[test]
+    fn renders_saved_results() {
+        let mut config = create_mock_config();
+        config.runtimes = vec![Arc::new(create_mock_runtime())];
+        config.test_coverage = 0.75;
+        let runtime = Arc::new(create_mock_runtime());
+        let openapi_endpoints = vec![
+            EndpointConfiguration::new(Method::GET, "/search", 200, runtime.clone(), false)
+                .unwrap()
+                .with_parameters(vec![Parameter::new("page", ParameterLocation::Query, true)]),
+            EndpointConfiguration::new(Method::POST, "/foo", 201, runtime.clone(), false).unwrap(),
+        ];
+        let observations = vec![
+            Observation::new(Method::GET, "/search", 200, runtime.clone()).unwrap(),
+            Observation::new(Method::GET, "/moo", 200, runtime).unwrap(),
+        ];
+        let evaluation = evaluate(&openapi_endpoints, &None, &observations, &HashSet::new(), &PathMatching::MostSpecific);
+        let detailed_evaluation = evaluate_details(&openapi_endpoints, &observations, &HashSet::new(), false, &PathMatching::MostSpecific);
+        let report = build_json_report(&config, &evaluation, &detailed_evaluation);
+
+        let text_report = build_text_report(&report);
+
+        assert_eq!(
+            text_report,
+            "Test Coverage: 50%
+The following endpoints were missed:
+- /foo POST 201
+The following requests are missing in the openapi spec:
+- /moo GET 200 (1 hits)
+Query Parameter Coverage: 0%
+The following query parameters were never sent:
+- /search GET: page (required)
+The following required query parameters were missing in successful requests:
+- /search GET: page (1 requests)
+FAILED: test coverage of 50.00% is 25.00% below the required 75.00%
+"
+        );
+        assert_eq!(get_json_report_exit_code(&report), ExitCode::CoverageBelowThreshold);
+    }
+}
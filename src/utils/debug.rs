@@ -2,6 +2,8 @@ use std::{fmt::Display, process};
 
 use crate::config::RudraConfig;
 
+use super::ExitCode;
+
 pub fn print_debug_message<T: Display>(debug_message: T) {
     if RudraConfig::global_is_debug() {
         println!("{}", debug_message);
@@ -9,7 +11,11 @@ pub fn print_debug_message<T: Display>(debug_message: T) {
 }
 
 pub fn print_error_and_exit<T: Display>(debug_message: T) -> ! {
+    print_error_and_exit_with_code(debug_message, ExitCode::UnknownError)
+}
+
+pub fn print_error_and_exit_with_code<T: Display>(debug_message: T, exit_code: ExitCode) -> ! {
     eprintln!("{}", debug_message);
-    process::exit(1);
+    process::exit(exit_code.as_i32());
 }
 
@@ -0,0 +1,78 @@
+// decodes %XX sequences, malformed sequences are kept as they are
+pub fn percent_decode(value: &str) -> String {
+    decode_percent_sequences(value, |_| true)
+}
+
+// an encoded "/" belongs to its segment, decoding it would split the segment in two
+pub fn percent_decode_path(path: &str) -> String {
+    decode_percent_sequences(path, |byte| byte != b'/')
+}
+
+fn decode_percent_sequences(value: &str, should_decode: fn(u8) -> bool) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' && index + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).unwrap_or("");
+            match u8::from_str_radix(hex, 16) {
+                Ok(byte) if should_decode(byte) => {
+                    decoded.push(byte);
+                    index += 3;
+                    continue;
+                }
+                _ => (),
+            }
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+// nginx logs quotes, backslashes and non printable bytes of variables as \xXX
+pub fn decode_nginx_escapes(value: &str) -> Vec<u8> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'\\' && index + 3 < bytes.len() && bytes[index + 1] == b'x' {
+            let hex = std::str::from_utf8(&bytes[index + 2..index + 4]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                index += 4;
+                continue;
+            }
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_nginx_escapes, percent_decode, percent_decode_path};
+
+    #[test]
+    fn decodes_percent_encoded_characters() {
+        assert_eq!(percent_decode("%7Bid%7D"), "{id}");
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%2"), "%zz%2");
+    }
+
+    #[test]
+    fn keeps_encoded_slashes_in_path_segments() {
+        assert_eq!(percent_decode_path("/files/a%2Fb%20c"), "/files/a%2Fb c");
+        assert_eq!(percent_decode_path("/files/a%2fb"), "/files/a%2fb");
+        assert_eq!(percent_decode("a%2Fb"), "a/b");
+    }
+
+    #[test]
+    fn decodes_nginx_escapes() {
+        assert_eq!(decode_nginx_escapes("{\\x22name\\x22: \\x22Jos\\xC3\\xA9\\x22}"), "{\"name\": \"José\"}".as_bytes());
+        assert_eq!(decode_nginx_escapes("a\\x0"), b"a\\x0");
+        assert_eq!(decode_nginx_escapes("\\xzz"), b"\\xzz");
+    }
+}
@@ -1,6 +1,6 @@
 use std::path::Path;
 
-use super::print_error_and_exit;
+use super::{print_error_and_exit_with_code, ExitCode};
 
 #[derive(Debug)]
 pub enum Error {
@@ -14,6 +14,7 @@ pub enum Error {
     InvalidMethodString(String),
     InvalidParseStatusCode(String),
     InvalidParseMethod(String),
+    InvalidParseParameter(String),
     ProblemOpeningFile(Box<Path>),
     UnknownInternalError(String),
     UnknownOpenApiFormat,
@@ -28,10 +29,26 @@ pub enum Error {
     OpenapiPathIsAbsolute(Box<Path>),
     MappingDuplicatePorts,
     InvalidPath(String),
+    UnsupportedOpenapiVersion(String),
+    InvalidServerUrl(String),
+    InvalidProxyBackend(String),
+    ProxyPortUnavailable(u16, String),
+    InvalidProxyRequest(String),
+    InvalidUndocumentedLimit(String),
+    UnresolvedReference(String, String),
+    InvalidDefaultResponsePolicy(String),
+    InvalidPathMatching(String),
+    InvalidRejectedLinesLimit(String),
+    TooManyRejectedLines(usize, usize, String),
+    UnknownRuntimePort(u16),
+    InvalidConfigFile(String),
+    InvalidConfigFileKey(String, String),
+    InvalidCliArgument(String),
+    InvalidJsonReport(String),
 }
 
 impl Error {
-    fn get_error_msg(&self) -> String {
+    pub fn get_error_msg(&self) -> String {
         match self {
             Error::InvalidApplicationURL(err_msg) => format!("Invalid application URL provided: {}", err_msg),
             Error::MissingConfiguration => format!("Your configuration is missing wither a mapping or an openapi source with it's respective application URL."),
@@ -40,6 +57,7 @@ impl Error {
             Error::ProblemOpeningFile(path) => format!("An issue opening the openapi ({:?}) file occured.", path),
             Error::InvalidParseSyntax => format!("The syntax of the openapi file is incorrect."),
             Error::InvalidParseMethod(method) => format!("The openapi file contains an invalid method: {}", method),
+            Error::InvalidParseParameter(parameter) => format!("The openapi file contains an invalid parameter: {}", parameter),
             Error::InvalidParseStatusCode(code) => format!("The openapi file contains an invalid status code: {}", code),
             Error::UnknownInternalError(err) => format!("An unknown internal error occured, please open an issue on github for this [{}].", err),
             Error::InvalidBasePath => format!("Basepath provided in openapi spec isn't valid."),
@@ -57,15 +75,78 @@ impl Error {
             Error::OpenapiPathIsAbsolute(path) => format!("The following path is absolute, please only specify relative paths: {}", path.to_str().or(Some("<empty>")).unwrap()),
             Error::MappingDuplicatePorts => format!("The mapping contains duplicate ports, every port can only be used once."),
             Error::InvalidPath(path) => format!("The following path failed to parse: {}", path),
+            Error::UnsupportedOpenapiVersion(version) => format!("Rudra only supports swagger 2.0 and openapi 3.x specs, the following version isn't supported: {}", version),
+            Error::InvalidProxyBackend(backend) => format!("The following proxy backend is unknown, please use either \"native\" or \"nginx\": {}", backend),
+            Error::ProxyPortUnavailable(port, why) => format!("Rudra could not listen on port {}: {}", port, why),
+            Error::InvalidProxyRequest(why) => format!("Received a malformed http request: {}", why),
+            Error::InvalidUndocumentedLimit(limit) => format!("The limit of undocumented requests has to be a positive integer: \"{}\"", limit),
+            Error::UnresolvedReference(reference, why) => format!("The reference \"{}\" in the openapi spec can't be resolved: {}", reference, why),
+            Error::InvalidDefaultResponsePolicy(policy) => format!("The following default response policy is unknown, please use either \"ignore\" or \"match-undocumented\": {}", policy),
+            Error::InvalidPathMatching(path_matching) => format!("The following path matching is unknown, please use either \"most-specific\" or \"all\": {}", path_matching),
+            Error::InvalidRejectedLinesLimit(limit) => format!("The limit of rejected access log lines has to be a value between 0 and 1 or a percentage between 0% and 100%: \"{}\"", limit),
+            Error::TooManyRejectedLines(rejected, total, reasons) => format!("{} of {} lines of the access log can't be parsed, which is more than the rejected-lines-limit allows: {}", rejected, total, reasons),
+            Error::UnknownRuntimePort(port) => format!("No service is configured for port {}", port),
+            Error::InvalidConfigFile(why) => format!("The configuration file can't be read: {}", why),
+            Error::InvalidConfigFileKey(key, why) => format!("The configuration file contains an invalid value for \"{}\": {}", key, why),
+            Error::InvalidCliArgument(why) => format!("Invalid command line arguments: {}, see \"rudra help\" for all options", why),
+            Error::InvalidJsonReport(why) => format!("The json report can't be read: {}", why),
+            Error::InvalidServerUrl(url) => format!("The following server url in the openapi spec is invalid or uses undefined variables: {}", url),
+        }
+    }
+
+    pub fn get_exit_code(&self) -> ExitCode {
+        match self {
+            Error::InvalidApplicationURL(_)
+            | Error::MissingConfiguration
+            | Error::ConflictingConfiguration
+            | Error::InvalidStatusCode(_)
+            | Error::InvalidMethodString(_)
+            | Error::InvalidTestCoverage
+            | Error::InvalidPortNumber(_)
+            | Error::InvalidMappingSyntax(_)
+            | Error::MissingMapping
+            | Error::MappingMissingSemicolon(_)
+            | Error::OpenapiPathIsAbsolute(_)
+            | Error::MappingDuplicatePorts
+            | Error::InvalidProxyBackend(_)
+            | Error::InvalidUndocumentedLimit(_)
+            | Error::InvalidDefaultResponsePolicy(_)
+            | Error::InvalidPathMatching(_)
+            | Error::InvalidRejectedLinesLimit(_)
+            | Error::InvalidConfigFile(_)
+            | Error::InvalidConfigFileKey(_, _)
+            | Error::InvalidCliArgument(_)
+            | Error::InvalidJsonReport(_) => ExitCode::ConfigurationError,
+            _ => ExitCode::UnknownError,
         }
     }
 
     pub fn display_error_and_exit(&self) -> ! {
         print!("Error: ");
-        print_error_and_exit(self.get_error_msg())
+        print_error_and_exit_with_code(self.get_error_msg(), self.get_exit_code())
     }
 
     pub fn display_error(&self) {
         eprintln!("{}", self.get_error_msg());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::ExitCode;
+
+    use super::Error;
+
+    #[test]
+    fn configuration_errors_use_configuration_exit_code() {
+        assert_eq!(Error::MissingConfiguration.get_exit_code(), ExitCode::ConfigurationError);
+        assert_eq!(Error::InvalidTestCoverage.get_exit_code(), ExitCode::ConfigurationError);
+        assert_eq!(Error::InvalidPortNumber("foo".to_string()).get_exit_code(), ExitCode::ConfigurationError);
+    }
+
+    #[test]
+    fn other_errors_use_unknown_exit_code() {
+        assert_eq!(Error::InvalidParseSyntax.get_exit_code(), ExitCode::UnknownError);
+        assert_eq!(Error::OpenapiFetchConnectionFailure.get_exit_code(), ExitCode::UnknownError);
+    }
+}
@@ -0,0 +1,50 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    Success,
+    UnknownError,
+    ConfigurationError,
+    CoverageBelowThreshold,
+    GatewayIssues,
+    UndocumentedTraffic,
+    ParameterCoverageBelowThreshold,
+}
+
+impl ExitCode {
+    pub fn as_i32(&self) -> i32 {
+        match self {
+            ExitCode::Success => 0,
+            ExitCode::UnknownError => 1,
+            ExitCode::ConfigurationError => 2,
+            ExitCode::CoverageBelowThreshold => 3,
+            ExitCode::GatewayIssues => 4,
+            ExitCode::UndocumentedTraffic => 5,
+            ExitCode::ParameterCoverageBelowThreshold => 6,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExitCode;
+
+    #[test]
+    fn exit_codes_are_distinct() {
+        let codes = [
+            ExitCode::Success,
+            ExitCode::UnknownError,
+            ExitCode::ConfigurationError,
+            ExitCode::CoverageBelowThreshold,
+            ExitCode::GatewayIssues,
+            ExitCode::UndocumentedTraffic,
+            ExitCode::ParameterCoverageBelowThreshold,
+        ];
+        for (index, code) in codes.iter().enumerate() {
+            assert!(codes[index + 1..].iter().all(|other| other.as_i32() != code.as_i32()));
+        }
+    }
+
+    #[test]
+    fn success_exits_with_zero() {
+        assert_eq!(ExitCode::Success.as_i32(), 0);
+    }
+}
@@ -1,7 +1,10 @@
 mod io;
 mod debug;
+mod encoding;
 mod error;
+mod exit_code;
 mod runtime;
+mod time;
 #[cfg(test)]
 pub mod test;
 
@@ -9,5 +12,9 @@ pub use io::read_file_to_string_or_err;
 pub use io::print_endpoints;
 pub use debug::print_debug_message;
 pub use debug::print_error_and_exit;
+pub use debug::print_error_and_exit_with_code;
+pub use encoding::{decode_nginx_escapes, percent_decode, percent_decode_path};
 pub use error::Error;
+pub use exit_code::ExitCode;
 pub use runtime::sort_by_runtime;
+pub use time::{parse_nginx_time, unix_timestamp};
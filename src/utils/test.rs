@@ -2,7 +2,10 @@ use std::{collections::HashMap, str::FromStr};
 
 use reqwest::Url;
 
-use crate::config::{OpenapiSource, RudraConfig, Runtime};
+use crate::{
+    config::{OpenapiSource, RudraConfig, Runtime},
+    models::{EndpointConfiguration, Observation},
+};
 
 pub fn create_mock_config() -> RudraConfig {
     let mut env_vars = HashMap::new();
@@ -24,3 +27,11 @@ pub fn create_mock_runtime() -> Runtime {
         port: 8080,
     }
 }
+
+// observations of requests without any parameters or bodies, each observed once
+pub fn create_observations(endpoints: &[EndpointConfiguration]) -> Vec<Observation> {
+    endpoints
+        .iter()
+        .map(|endpoint| Observation::from_endpoint(endpoint.clone()))
+        .collect()
+}
@@ -0,0 +1,61 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+// nginx logs $time_local like "18/Oct/2026:10:00:00 +0200", returns seconds since the unix epoch
+pub fn parse_nginx_time(time: &str) -> Option<u64> {
+    let (date_time, offset) = time.split_once(' ')?;
+    let mut parts = date_time.split(&['/', ':'][..]);
+    let day: i64 = parts.next()?.parse().ok()?;
+    let month_name = parts.next()?;
+    let month = MONTHS.iter().position(|month| *month == month_name)? as i64 + 1;
+    let year: i64 = parts.next()?.parse().ok()?;
+    let hour: i64 = parts.next()?.parse().ok()?;
+    let minute: i64 = parts.next()?.parse().ok()?;
+    let second: i64 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() || offset.len() != 5 {
+        return None;
+    }
+
+    let offset_sign = match &offset[..1] {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let offset_hours: i64 = offset[1..3].parse().ok()?;
+    let offset_minutes: i64 = offset[3..].parse().ok()?;
+
+    let seconds = days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second
+        - offset_sign * (offset_hours * 3600 + offset_minutes * 60);
+    u64::try_from(seconds).ok()
+}
+
+// requests recorded by the native proxy are timestamped when they are observed
+pub fn unix_timestamp() -> Option<u64> {
+    SystemTime::now().duration_since(UNIX_EPOCH).ok().map(|duration| duration.as_secs())
+}
+
+// days since 1970-01-01 of a date of the proleptic gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_nginx_time;
+
+    #[test]
+    fn parses_nginx_local_time() {
+        assert_eq!(parse_nginx_time("01/Jan/1970:00:00:00 +0000"), Some(0));
+        assert_eq!(parse_nginx_time("29/Feb/2024:12:30:15 +0000"), Some(1709209815));
+        assert_eq!(parse_nginx_time("29/Feb/2024:14:30:15 +0200"), Some(1709209815));
+        assert_eq!(parse_nginx_time("29/Feb/2024:07:00:15 -0530"), Some(1709209815));
+        assert_eq!(parse_nginx_time("29/Foo/2024:12:30:15 +0000"), None);
+        assert_eq!(parse_nginx_time("29/Feb/2024:12:30:15"), None);
+    }
+}